@@ -0,0 +1,109 @@
+//! Throughput of parsing and routing broadcast data as received from an ANT+ USB device with
+//! many channels open at 8 Hz, and with 8 channels open at 4 Hz as on small ARM boards reading a
+//! few sensors. Run with `cargo bench`.
+use std::thread;
+use std::time::{Duration, Instant};
+
+use libant::message::{self, BroadcastDataMessage, ReadBuffer};
+use libant::transport::MockTransport;
+use libant::{Request, Response, RunConfig};
+
+// Channels open and the rate each receives broadcast data at in Hz.
+const WORKLOADS: [(usize, usize); 2] = [(15, 8), (8, 4)];
+// Seconds of broadcast data received.
+const SECONDS: usize = 600;
+// Largest read from the ANT+ USB device.
+const READ_SIZE: usize = 512;
+
+fn main() {
+    for &(channels, rate) in &WORKLOADS {
+        println!("{} channels at {} Hz", channels, rate);
+        let frames: Vec<Vec<u8>> = (0..channels * rate * SECONDS)
+            .map(|i| {
+                let data = [0x04, 0, 0, 0, 0, 0, 0, 72];
+                BroadcastDataMessage::new((i % channels) as u8, &data)
+                    .to_message()
+                    .encode()
+            })
+            .collect();
+        let data = frames.concat();
+        let total = frames.len();
+
+        read_buffer(&data);
+        parse_frames(&frames);
+        run_loop("run loop, 1 frame per read", channels, total, frames);
+        run_loop(
+            "run loop, 512 byte reads",
+            channels,
+            total,
+            data.chunks(READ_SIZE).map(<[u8]>::to_vec).collect(),
+        );
+    }
+}
+
+fn report(name: &str, frames: usize, elapsed: Duration) {
+    println!(
+        "{:<30} {:>7} frames in {:>10.2?} ({:>9.0} frames/s)",
+        name,
+        frames,
+        elapsed,
+        frames as f64 / elapsed.as_secs_f64()
+    );
+}
+
+// Reads split across 512 byte reads, carrying frames split across reads over to the next read.
+fn read_buffer(data: &[u8]) {
+    let start = Instant::now();
+    let mut read_buffer = ReadBuffer::new();
+    let mut frames = 0;
+    for chunk in data.chunks(READ_SIZE) {
+        read_buffer.inner_as_mut()[..chunk.len()].copy_from_slice(chunk);
+        read_buffer.len(chunk.len());
+        frames += (&mut read_buffer).count();
+    }
+    report("ReadBuffer, 512 byte reads", frames, start.elapsed());
+}
+
+// parse_frames doesn't carry frames over, so reads hold as many whole frames as fit.
+fn parse_frames(frames: &[Vec<u8>]) {
+    let reads: Vec<Vec<u8>> = frames
+        .chunks(READ_SIZE / frames[0].len())
+        .map(<[Vec<u8>]>::concat)
+        .collect();
+    let start = Instant::now();
+    let parsed: usize = reads
+        .iter()
+        .map(|read| message::parse_frames(read).len())
+        .sum();
+    report("parse_frames, whole frames", parsed, start.elapsed());
+}
+
+// Time from queueing the reads on a MockTransport until the run loop has sent every frame.
+fn run_loop(name: &str, channels: usize, total: usize, reads: Vec<Vec<u8>>) {
+    let stick = MockTransport::stick(channels as u8);
+    let mock = stick.clone();
+    let (request_tx, request_rx) = libant::unbounded();
+    let (message_tx, message_rx) = libant::unbounded();
+    let config = RunConfig::new()
+        .read_timeout(Duration::from_millis(10))
+        .reset_delay(Duration::from_millis(10));
+    let handle =
+        thread::spawn(move || libant::ant::run_transport(stick, request_rx, message_tx, config));
+    // Requests are only handled once the run loop is ready.
+    request_tx.send(Request::GetMetrics).unwrap();
+    while !matches!(message_rx.recv().unwrap(), Response::Metrics(_)) {}
+
+    let start = Instant::now();
+    for read in &reads {
+        mock.push_bytes(read);
+    }
+    let mut frames = 0;
+    while frames < total {
+        if let Response::BroadcastData(_) = message_rx.recv().unwrap() {
+            frames += 1;
+        }
+    }
+    report(name, frames, start.elapsed());
+    request_tx.send(Request::Quit).unwrap();
+    handle.join().unwrap();
+}
@@ -0,0 +1,18 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use libant::message::{self, ReadBuffer};
+
+// Parses the data as read from the ANT+ USB device, both all at once and split across reads
+// through a ReadBuffer, which carries incomplete messages over to the next read. Reads are at
+// most 512 bytes, which the ReadBuffer always has room for.
+fuzz_target!(|data: &[u8]| {
+    let _ = message::parse_frames(data);
+
+    let mut read_buffer = ReadBuffer::new();
+    for chunk in data.chunks(512) {
+        read_buffer.inner_as_mut()[..chunk.len()].copy_from_slice(chunk);
+        read_buffer.len(chunk.len());
+        for _ in &mut read_buffer {}
+    }
+});
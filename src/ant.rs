@@ -5,44 +5,473 @@ use crossbeam_channel::{Receiver, Sender};
 use super::Result;
 use crate::{
     channel::{Channel, Config},
+    device::common::{self, Transmission},
     error::AntError,
     message::Response as DeviceResponse,
-    message::{self, BroadcastDataMessage, ChannelResponseCode, Message, ReadBuffer},
-    usb::{UsbContext, UsbDevice},
+    message::{
+        self, AcknowledgeDataMessage, BroadcastDataMessage, BurstBuffer, ChannelResponseCode,
+        ChannelStatus, EventBufferConfig, Message, MessageId, ReadBuffer,
+    },
+    metrics::Metrics,
+    transport::{Reader, Transport},
+    usb::{self, Selector, StickAddress, StickInfo, StickModel, UsbDevice},
 };
 
+pub use crate::backoff::Backoff;
+#[cfg(feature = "nusb")]
+use crate::nusb_device::NusbDevice;
 use log::{debug, error, info, trace};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 // Default to ANT network 1. The ANT+ USB device can support up to three networks, and appears
 // through testing that devices work on ANT network 1 even though 0 is the public network.
 const ANT_NETWORK: u8 = 1;
 const ANT_NETWORK_KEY: [u8; 8] = [0xB9, 0xA5, 0x21, 0xFB, 0xBD, 0x72, 0xC3, 0x45];
 
+// Defaults for resetting and reading from the ANT+ USB device. These work with the test USB
+// devices, but slower hosts or other ANT+ USB devices may need them tuned through RunConfig.
+const RESET_ATTEMPTS: u8 = 3;
+const RESET_COMMANDS: u8 = 2;
+const RESET_DELAY: Duration = Duration::from_millis(500);
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+const PAGE_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// RunConfig configures the run loop. By default the ANT+ network key is set on ANT network 1
+/// and channels are assigned to that network. Keys for additional networks, such as private
+/// networks or ANT-FS, can be set with network_key, and channels can be assigned to a network
+/// other than the default with channel::Config::network. When more than one ANT+ USB device is
+/// plugged in, stick or selector picks which one the run loop uses so a run loop can be started
+/// for each. How the ANT+ USB device is reset and read from can be tuned for slower hosts or
+/// ANT+ USB devices that are slow to respond. A tap can be set to receive the raw bytes written
+/// to and read from the ANT+ USB device for protocol analyzers and other debugging tools. A
+/// watchdog can be set to reset ANT+ USB devices that stop sending messages while channels are
+/// open. Event buffering can be set so applications with many channels open receive fewer,
+/// larger USB transfers.
+#[derive(Clone, Debug)]
+pub struct RunConfig {
+    network: u8,
+    network_keys: BTreeMap<u8, [u8; 8]>,
+    stick: Selector,
+    stick_models: Vec<StickModel>,
+    detach_kernel_driver: bool,
+    #[cfg(feature = "nusb")]
+    nusb: bool,
+    reset_attempts: u8,
+    reset_commands: u8,
+    reset_delay: Duration,
+    read_timeout: Duration,
+    discovery: Backoff,
+    watchdog: Option<Duration>,
+    page_request_timeout: Duration,
+    event_buffer: Option<(EventBufferConfig, u16, u16)>,
+    tap: Option<Sender<RawFrame>>,
+}
+
+impl RunConfig {
+    pub fn new() -> Self {
+        let mut network_keys = BTreeMap::new();
+        network_keys.insert(ANT_NETWORK, ANT_NETWORK_KEY);
+        Self {
+            network: ANT_NETWORK,
+            network_keys,
+            stick: Selector::First,
+            stick_models: usb::KNOWN_STICKS.to_vec(),
+            detach_kernel_driver: true,
+            #[cfg(feature = "nusb")]
+            nusb: false,
+            reset_attempts: RESET_ATTEMPTS,
+            reset_commands: RESET_COMMANDS,
+            reset_delay: RESET_DELAY,
+            read_timeout: READ_TIMEOUT,
+            discovery: Backoff::new(),
+            watchdog: None,
+            page_request_timeout: PAGE_REQUEST_TIMEOUT,
+            event_buffer: None,
+            tap: None,
+        }
+    }
+
+    /// Sets the network channels are assigned to by default.
+    pub fn network(mut self, network: u8) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Sets the network key for a network, replacing any key already set for the network.
+    pub fn network_key(mut self, network: u8, key: [u8; 8]) -> Self {
+        self.network_keys.insert(network, key);
+        self
+    }
+
+    /// Uses the ANT+ USB device at the address instead of the first one found.
+    pub fn stick(mut self, stick: StickAddress) -> Self {
+        self.stick = Selector::Address(stick);
+        self
+    }
+
+    /// Uses the ANT+ USB device matching the selector instead of the first one found, such as
+    /// by its USB serial number so a run loop binds to the same ANT+ USB device across reboots.
+    pub fn selector(mut self, selector: Selector) -> Self {
+        self.stick = selector;
+        self
+    }
+
+    /// Adds a model of ANT+ USB device, such as a clone with a vendor ID other than Dynastream's
+    /// or with other endpoints. Models added are matched before the known ANT+ USB devices.
+    pub fn stick_model(mut self, model: StickModel) -> Self {
+        self.stick_models.insert(0, model);
+        self
+    }
+
+    /// Sets whether a kernel driver that claimed the ANT+ USB device, such as usb_serial or
+    /// cdc_acm on Linux, is detached so the run loop can use the ANT+ USB device. Defaults to
+    /// true. Only supported on Linux, and ignored on other platforms.
+    pub fn detach_kernel_driver(mut self, detach: bool) -> Self {
+        self.detach_kernel_driver = detach;
+        self
+    }
+
+    /// Sets whether the ANT+ USB device is used through nusb instead of libusb. nusb reads with
+    /// asynchronous transfers, so data is received without waiting on blocking reads. Defaults
+    /// to false.
+    #[cfg(feature = "nusb")]
+    pub fn nusb(mut self, nusb: bool) -> Self {
+        self.nusb = nusb;
+        self
+    }
+
+    /// Removes all network keys, including the default ANT+ network key, so only keys set
+    /// afterwards are used.
+    pub fn clear_network_keys(mut self) -> Self {
+        self.network_keys.clear();
+        self
+    }
+
+    /// Sets how many times the USB device is re-initialized when the ANT+ USB device does not
+    /// respond to reset commands before the run loop gives up. Defaults to 3.
+    pub fn reset_attempts(mut self, attempts: u8) -> Self {
+        self.reset_attempts = attempts;
+        self
+    }
+
+    /// Sets how many reset commands are sent each time the USB device is initialized before it
+    /// is re-initialized. Defaults to 2.
+    pub fn reset_commands(mut self, commands: u8) -> Self {
+        self.reset_commands = commands;
+        self
+    }
+
+    /// Sets how long to wait for the ANT+ USB device after sending a reset command. Defaults to
+    /// 500ms.
+    pub fn reset_delay(mut self, delay: Duration) -> Self {
+        self.reset_delay = delay;
+        self
+    }
+
+    /// Sets how long to wait for data on each read from the ANT+ USB device. Requests are
+    /// handled as they arrive, so this only bounds how quickly an idle ANT+ USB device is reset,
+    /// including one that stops answering while network keys are set or capabilities are
+    /// requested, and how long stopping the reader thread takes. Defaults to 100ms.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Sets how often to look for the ANT+ USB device while it is not plugged in. Defaults to
+    /// every second, forever.
+    pub fn discovery(mut self, backoff: Backoff) -> Self {
+        self.discovery = backoff;
+        self
+    }
+
+    /// Resets the ANT+ USB device and re-assigns channels when nothing has been read from it for
+    /// the timeout while channels are open, as ANT+ USB devices occasionally stop sending
+    /// messages without an error. StickReset is sent when the watchdog resets the ANT+ USB
+    /// device. The timeout should be longer than the channel periods and search timeouts of the
+    /// open channels. Disabled by default.
+    pub fn watchdog(mut self, timeout: Duration) -> Self {
+        self.watchdog = Some(timeout);
+        self
+    }
+
+    /// Sets how long to wait for a page requested with Request::RequestPage before requesting
+    /// it again. Defaults to 2 seconds.
+    pub fn page_request_timeout(mut self, timeout: Duration) -> Self {
+        self.page_request_timeout = timeout;
+        self
+    }
+
+    /// Buffers the events set in config on the ANT+ USB device until size bytes are buffered or
+    /// time in 10ms has passed, trading latency for fewer USB transfers. A size and time of 0
+    /// disables either threshold. Applied whenever the ANT+ USB device is reset, and replaced by
+    /// Request::ConfigureEventBuffer. Disabled by default.
+    pub fn event_buffer(mut self, config: EventBufferConfig, size: u16, time: u16) -> Self {
+        self.event_buffer = Some((config, size, time));
+        self
+    }
+
+    /// Sends every message written to the ANT+ USB device and all data read from it to the
+    /// Sender. The tap is removed if its Receiver is dropped.
+    pub fn tap(mut self, tap: Sender<RawFrame>) -> Self {
+        self.tap = Some(tap);
+        self
+    }
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // Manages the state of the ANT+ USB devices.
 #[derive(Debug, PartialEq)]
 enum State {
     NotReady,
     Reset,
     SetNetworkKey,
+    GetCapabilities,
     Running,
 }
 
 // TODO: Rename this to Command
 /// Requests that can be sent to the run loop to either Open/Close a channel, send a message to an
-/// ANT+ device, or Quit the loop closing all open channels.
+/// ANT+ device, broadcast data on a master channel, enable extended data on received messages
+/// with Lib Config flags, or Quit the loop closing all open channels.
+/// OpenScanMode opens channel 0 in continuous scan mode with channel ID extended data enabled so
+/// broadcast data from every device nearby is delivered along with the device it came from. All
+/// other channels must be closed first.
+/// ConfigureEventBuffer sets event buffering on the ANT+ USB device with the events to buffer,
+/// the buffer size in bytes, and the time in 10ms before the buffer is flushed, replacing any
+/// set with RunConfig::event_buffer. The configuration is reapplied whenever the ANT+ USB device
+/// is reset. FlushEventBuffer flushes any buffered events by setting the configuration again,
+/// as the ANT+ USB device sends its buffered events whenever event buffering is configured. An
+/// EventBufferNotConfigured error is sent if event buffering was never configured.
+/// SetEventFilter stops the ANT+ USB device from sending the events set in the filter, such as
+/// EVENT_FILTER_RX_FAIL, instead of the run loop receiving and dropping them. The filter is
+/// reapplied whenever the ANT+ USB device is reset.
+/// SetEncryptionKey stores an AES-128 key in a volatile key index, and SetEncryptionId sets the
+/// encryption ID used during negotiation. Both are reapplied whenever the ANT+ USB device is
+/// reset. StoreEncryptionKey saves a key to a key slot in the ANT+ USB device's non-volatile
+/// memory, and LoadEncryptionKey loads a key from an NVM key slot into a volatile key index so
+/// keys don't have to be resent on every startup.
+/// SetSduMask stores a selective data update mask in a mask index for channels configured with
+/// selective data updates. Masks are reapplied whenever the ANT+ USB device is reset.
+/// GetChannelStatus requests the status of a channel from the ANT+ USB device, returned as
+/// ChannelStatus.
+/// GetAntVersion requests the firmware version of the ANT+ USB device, returned as AntVersion.
+/// GetSerialNumber requests the serial number of the ANT+ USB device, returned as SerialNumber.
+/// GetMetrics requests the counters kept by the run loop, returned as Metrics.
+/// GetChannelState requests the library's view of a channel, returned as ChannelState.
+/// SendAcknowledged tracks the acknowledged data sent to an ANT+ device, retrying up to the
+/// number of retries given if the transfer fails. TransferCompleted or TransferFailed is sent
+/// back once the transfer is done. Transfers sent while another transfer is in progress on the
+/// channel are queued and sent in order. Transfers in progress or queued when the channel is
+/// closed are failed.
+/// SendBurst transmits data to an ANT+ device as a burst transfer on a channel, padding the last
+/// packet with zeros. TransferCompleted or TransferFailed is sent back once the transfer is done.
+/// SendAcknowledgedWithToken is SendAcknowledged with a token chosen by the application. Once the
+/// transfer is done, TransferResult is sent back with the token instead so the application can
+/// match the result to the data it sent.
+/// OpenChannelWithSender opens a channel like OpenChannel, but responses for the channel are
+/// sent to the given Sender instead of the run loop's Sender, so broadcast data is delivered
+/// straight to the part of the application that owns the ANT+ device. Responses fall back to the
+/// run loop's Sender if the given Sender's Receiver is dropped.
+/// RequestPage requests a page from an ANT+ device with common page 70, requesting it again until
+/// the page arrives or the attempts are used up. PageRequestResult is sent back once the page
+/// arrives, the attempts are used up, or the channel is closed.
 pub enum Request {
     OpenChannel(u8, Config),
+    OpenChannelWithSender(u8, Config, Sender<Response>),
+    OpenScanMode(Config),
     CloseChannel(u8),
+    EnableExtendedMessages(u8),
+    ConfigureEventBuffer(EventBufferConfig, u16, u16),
+    FlushEventBuffer,
+    SetEventFilter(u16),
+    SetEncryptionKey(u8, [u8; 16]),
+    SetEncryptionId(u32),
+    StoreEncryptionKey(u8, [u8; 16]),
+    LoadEncryptionKey {
+        nvm_index: u8,
+        volatile_index: u8,
+    },
+    SetSduMask(u8, [u8; 8]),
+    GetChannelStatus(u8),
+    GetAntVersion,
+    GetSerialNumber,
+    GetMetrics,
+    GetChannelState(u8),
     Send(Message),
+    SendAcknowledged(AcknowledgeDataMessage, u8),
+    SendAcknowledgedWithToken {
+        mesg: AcknowledgeDataMessage,
+        retries: u8,
+        token: u64,
+    },
+    SendBurst {
+        channel: u8,
+        data: Vec<u8>,
+    },
+    SendBroadcast(u8, [u8; 8]),
+    RequestPage {
+        channel: u8,
+        page: u8,
+        transmission: Transmission,
+        attempts: u8,
+    },
     Quit,
 }
 
-/// Responses that can be sent out of the run loop. BroadcastData from an ANT+ device or any types
-/// of error that should be handled by the upstream application.
+impl Request {
+    // Channel the request is for, if any.
+    fn channel(&self) -> Option<u8> {
+        match self {
+            Request::OpenChannel(number, _)
+            | Request::OpenChannelWithSender(number, _, _)
+            | Request::CloseChannel(number)
+            | Request::GetChannelStatus(number)
+            | Request::GetChannelState(number)
+            | Request::SendBroadcast(number, _) => Some(*number),
+            Request::OpenScanMode(_) => Some(0),
+            Request::SendAcknowledged(mesg, _)
+            | Request::SendAcknowledgedWithToken { mesg, .. } => Some(mesg.channel()),
+            Request::SendBurst { channel, .. } | Request::RequestPage { channel, .. } => {
+                Some(*channel)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Raw bytes sent to a RunConfig tap. Tx is an encoded message written to the ANT+ USB device,
+/// and Rx is the data of a single read from the ANT+ USB device, which may hold more than one
+/// message or part of a message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RawFrame {
+    Tx(Vec<u8>),
+    Rx(Vec<u8>),
+}
+
+/// Responses that can be sent out of the run loop. BroadcastData or AcknowledgeData from an ANT+
+/// device, ChannelOpened, ChannelClosed, ChannelReopened, and SearchTimeout as the state of a
+/// channel changes, ChannelLost when a channel's reopen policy gives up and the channel is
+/// closed, BurstData with the reassembled data of a burst transfer, TransferCompleted,
+/// TransferFailed, or TransferResult when an acknowledged data transfer is done,
+/// PageRequestResult when a page requested with RequestPage arrives or never does, EventTx when a
+/// master channel has transmitted and is ready for the next data page, EncryptionNegotiated or
+/// EncryptionFailed when an encrypted channel finishes negotiating, ChannelIdAcquired with the
+/// device a channel configured with a wildcard device ID paired with, ChannelStatus with the state
+/// of a channel as reported by the ANT+ USB device, AntVersion and SerialNumber to identify the
+/// ANT+ USB device, StickInfo with the USB strings and IDs of the ANT+ USB device once it is
+/// initialized or plugged back in, StickDisconnected and StickReconnected when the ANT+ USB
+/// device is unplugged and plugged back in, StickReset when the watchdog resets an ANT+ USB
+/// device that stopped sending messages, WaitingForDevice once when the ANT+ USB device is not
+/// plugged in at startup, ChannelState with a copy of a channel's state and configuration as
+/// kept by the library, or None if the channel is closed, Metrics with a snapshot of the counters
+/// kept by the run loop, Unknown with the ID and data of a message from the ANT+ USB device that
+/// is not supported, or any types of error that should be handled by the upstream application.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Response {
     BroadcastData(BroadcastDataMessage),
-    Error(AntError),
+    AcknowledgeData(AcknowledgeDataMessage),
+    BurstData(u8, Vec<u8>),
+    EventTx(u8),
+    TransferCompleted(u8),
+    TransferFailed(u8),
+    TransferResult {
+        token: u64,
+        ok: bool,
+    },
+    EncryptionNegotiated(u8),
+    EncryptionFailed(u8),
+    ChannelIdAcquired {
+        channel: u8,
+        device_id: u16,
+        device_type: u8,
+        transmission_type: u8,
+    },
+    ChannelStatus(u8, ChannelStatus),
+    AntVersion(String),
+    SerialNumber(u32),
+    StickInfo(StickInfo),
+    ChannelOpened(u8),
+    ChannelClosed(u8),
+    ChannelReopened(u8),
+    ChannelLost(u8),
+    SearchTimeout(u8),
+    StickDisconnected,
+    StickReconnected,
+    StickReset,
+    PageRequestResult {
+        channel: u8,
+        page: u8,
+        ok: bool,
+    },
+    WaitingForDevice,
+    ChannelState(u8, Option<Channel>),
+    Metrics(Metrics),
+    Unknown {
+        id: u8,
+        data: Vec<u8>,
+    },
+    // Errors are serialized as their message and can't be deserialized.
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+    Error(
+        #[cfg_attr(feature = "serde", serde(serialize_with = "crate::error::serialize"))] AntError,
+    ),
+}
+
+impl Response {
+    /// Channel the response is for, if any.
+    pub fn channel(&self) -> Option<u8> {
+        match self {
+            Response::BroadcastData(mesg) => Some(mesg.channel()),
+            Response::AcknowledgeData(mesg) => Some(mesg.channel()),
+            Response::BurstData(number, _)
+            | Response::EventTx(number)
+            | Response::TransferCompleted(number)
+            | Response::TransferFailed(number)
+            | Response::EncryptionNegotiated(number)
+            | Response::EncryptionFailed(number)
+            | Response::ChannelStatus(number, _)
+            | Response::ChannelOpened(number)
+            | Response::ChannelClosed(number)
+            | Response::ChannelReopened(number)
+            | Response::ChannelLost(number)
+            | Response::SearchTimeout(number)
+            | Response::ChannelState(number, _) => Some(*number),
+            Response::ChannelIdAcquired { channel, .. }
+            | Response::PageRequestResult { channel, .. } => Some(*channel),
+            _ => None,
+        }
+    }
+}
+
+// An acknowledged data or burst transfer waiting on EVENT_TRANSFER_TX_COMPLETED or
+// EVENT_TRANSFER_TX_FAILED. Retries resend all of the messages. Transfers sent by the run loop
+// itself, such as page requests, don't notify the application when they are done.
+struct Transfer {
+    channel: u8,
+    messages: Vec<Message>,
+    retries: u8,
+    token: Option<u64>,
+    notify: bool,
+}
+
+// A page requested with common page 70, waiting for the page to arrive on the channel. The
+// request is sent again each time the timeout passes until the attempts are used up.
+struct PageRequest {
+    channel: u8,
+    page: u8,
+    transmission: Transmission,
+    attempts: u8,
+    deadline: Instant,
 }
 
 /// run is a public function that handles getting a USB context and
@@ -55,92 +484,298 @@ pub enum Response {
 /// not being plugged in. When the ANT+ stick can be initialized, the function
 /// will call ANT::init().run() that will reset and startup the ANT+ stick
 /// and get it ready for communication.
-pub fn run(rx: Receiver<Request>, tx: Sender<Response>) {
+pub fn run(rx: Receiver<Request>, tx: Sender<Response>, config: RunConfig) {
+    run_until(rx, tx, config, &AtomicBool::new(false))
+}
+
+// run_until is run with a flag to stop waiting for an ANT+ stick to be plugged in or to be
+// ready, as a Quit request is only handled once the ANT+ stick is running.
+pub(crate) fn run_until(
+    rx: Receiver<Request>,
+    tx: Sender<Response>,
+    config: RunConfig,
+    quit: &AtomicBool,
+) {
+    #[cfg(feature = "nusb")]
+    {
+        if config.nusb {
+            return run_devices(rx, tx, config, quit, |config| {
+                NusbDevice::init(
+                    &config.stick,
+                    &config.stick_models,
+                    config.detach_kernel_driver,
+                )
+            });
+        }
+    }
     // Get the USB context. If there is an error, send an Error
     // response over the transmit channel and return.
     let mut ctx = match crate::Context::new() {
         Ok(ctx) => ctx,
         Err(e) => {
             error!("Error getting USB Context: {:?}", e);
-            // unwrap() is called here as the only error we should receive
-            // here is if the other end of the channel has disconnected, i.e.
-            // the other thread either no longer exists, or the channel has been
-            // dropped. Without a transmit channel, nothing can actually be done
-            // so might as well panic to kill this thread.
-            tx.send(Response::Error(AntError::UsbDeviceError(e)))
-                .unwrap();
+            // The send can only fail if the other end of the channel has disconnected, and
+            // we're returning either way.
+            let _ = tx.send(Response::Error(AntError::UsbDeviceError(e)));
             return;
         }
     };
+    run_devices(rx, tx, config, quit, |config| {
+        UsbDevice::init_with_models(
+            &mut ctx,
+            &config.stick,
+            &config.stick_models,
+            config.detach_kernel_driver,
+        )
+    })
+}
 
+// Runs the run loop on the ANT+ stick returned by init, calling init again to look for the
+// ANT+ stick until it is plugged in.
+fn run_devices<D, F>(
+    rx: Receiver<Request>,
+    tx: Sender<Response>,
+    config: RunConfig,
+    quit: &AtomicBool,
+    mut init: F,
+) where
+    D: Transport,
+    F: FnMut(&RunConfig) -> Result<D>,
+{
     // Loop here looking for the ANT+ stick. If the user has not plugged
-    // in the ANT+ stick, check every 1 second.
+    // in the ANT+ stick, check again as configured by RunConfig::discovery.
     // From time to time, sending a reset message fails. Lets try re-initialzing the UsbDevice
-    // if we receive a Reset error. Try three times, then fail.
+    // if we receive a Reset error. Try RunConfig::reset_attempts times, then fail.
+    // If the ANT+ stick is unplugged, the run loop state is kept while polling for the ANT+
+    // stick to be plugged back in so known channels can be re-assigned.
     let mut reset_attempts = 0;
+    let mut ant: Option<Ant<D>> = None;
+    let mut disconnected = false;
+    let mut discovery_attempts = 0;
     loop {
-        match UsbDevice::init(&mut ctx) {
+        match init(&config) {
             Ok(device) => {
-                let e = match Ant::init(device, rx.clone(), tx.clone()).run() {
+                discovery_attempts = 0;
+                // The ANT+ stick is only re-initialized after a reset fails, or after being
+                // unplugged, which may have been for a different ANT+ stick.
+                let new_stick = ant.is_none() || disconnected;
+                if disconnected {
+                    info!("ANT+ stick reconnected");
+                    disconnected = false;
+                    if tx.send(Response::StickReconnected).is_err() {
+                        debug!("Response receiver disconnected. Exiting...");
+                        break;
+                    }
+                }
+                if let Some(stick_info) = device.stick_info().filter(|_| new_stick) {
+                    info!("Using ANT+ stick {:?}", stick_info);
+                    if tx.send(Response::StickInfo(stick_info)).is_err() {
+                        debug!("Response receiver disconnected. Exiting...");
+                        break;
+                    }
+                }
+                let mut running = match ant.take() {
+                    Some(mut ant) => {
+                        ant.reconnect(device);
+                        ant
+                    }
+                    None => Ant::init(device, rx.clone(), tx.clone(), config.clone()),
+                };
+                let e = match running.run(quit) {
                     Ok(()) => {
                         debug!("Ant::init()::run() exited successfully. Exiting...");
                         break;
                     }
                     Err(e @ AntError::Reset) => {
-                        // Tried resetting the ANT+ stick three times and failed.
-                        if reset_attempts <= 2 {
+                        // Tried resetting the ANT+ stick and failed.
+                        if reset_attempts < config.reset_attempts {
                             debug!("Failed to reset ANT+ stick. Re-intializing USB device");
                             reset_attempts += 1;
+                            ant = Some(running);
                             continue;
                         }
                         error!("Error resetting the ANT+ stick");
                         e
                     }
+                    Err(e) if disconnected_error(&e) => {
+                        info!("ANT+ stick disconnected: {}", e);
+                        disconnected = true;
+                        ant = Some(running);
+                        if tx.send(Response::StickDisconnected).is_err() {
+                            debug!("Response receiver disconnected. Exiting...");
+                            break;
+                        }
+                        continue;
+                    }
                     Err(e) => {
                         error!("Error initializing ANT+ stick: {:?}", e);
                         e
                     }
                 };
-                tx.send(Response::Error(e)).unwrap();
+                let _ = tx.send(Response::Error(e));
                 break;
             }
             Err(e @ AntError::UsbDeviceError(rusb::Error::NoDevice)) => {
-                // If no USB stick is present, the error is returned over the channel
-                // and the thread sleeps for 1s. This is an infinite loop waiting on the user
-                // to plug in an ANT+ USB device.
-                debug!("No ANT+ stick found. Sleeping for 1s");
-                tx.send(Response::Error(e)).unwrap();
-                std::thread::sleep(std::time::Duration::from_millis(1000));
+                // If no USB stick is present, WaitingForDevice is sent once and the thread
+                // sleeps before looking again, waiting on the user to plug in an ANT+ USB
+                // device. StickDisconnected has already been sent if the ANT+ stick was
+                // unplugged. The error is only returned if the run loop gives up.
+                discovery_attempts += 1;
+                let delay = match config.discovery.delay(discovery_attempts) {
+                    Some(delay) => delay,
+                    None => {
+                        error!("No ANT+ stick found after {} attempts", discovery_attempts);
+                        let _ = tx.send(Response::Error(e));
+                        break;
+                    }
+                };
+                debug!("No ANT+ stick found. Sleeping for {:?}", delay);
+                if !disconnected
+                    && discovery_attempts == 1
+                    && tx.send(Response::WaitingForDevice).is_err()
+                {
+                    debug!("Response receiver disconnected. Exiting...");
+                    break;
+                }
+                if quit.load(Ordering::SeqCst) {
+                    debug!("Quit while waiting for ANT+ stick. Exiting...");
+                    break;
+                }
+                std::thread::sleep(delay);
             }
             Err(e) => {
                 // Error initializng the USB device. Send the error up and then break the loop.
                 error!("Error initializing ANT+ USB stick: {:?}", e);
-                tx.send(Response::Error(e)).unwrap();
+                let _ = tx.send(Response::Error(e));
                 break;
             }
         }
     }
 }
 
-struct Ant<T: UsbContext> {
-    usb_device: UsbDevice<T>,
+// libusb reports an unplugged ANT+ USB device as NoDevice, except on Windows where transfers in
+// progress fail with an I/O error instead. Either way the ANT+ USB device is looked for again.
+// Elsewhere an I/O error is returned like any other error.
+fn disconnected_error(e: &AntError) -> bool {
+    match e {
+        AntError::UsbDeviceError(rusb::Error::NoDevice) => true,
+        #[cfg(windows)]
+        AntError::UsbDeviceError(rusb::Error::Io) => true,
+        _ => false,
+    }
+}
+
+/// Runs the run loop on a transport instead of an ANT+ USB device, such as a
+/// replay::Replay of a captured trace. The run loop exits on a Quit request, or once the
+/// transport returns NoDevice, which is sent as StickDisconnected. Any other error is sent as an
+/// Error response.
+pub fn run_transport<D: Transport>(
+    transport: D,
+    rx: Receiver<Request>,
+    tx: Sender<Response>,
+    config: RunConfig,
+) {
+    let mut ant = Ant::init(transport, rx, tx.clone(), config);
+    let response = match ant.run(&AtomicBool::new(false)) {
+        Ok(()) => return,
+        Err(AntError::UsbDeviceError(rusb::Error::NoDevice)) => Response::StickDisconnected,
+        Err(e) => Response::Error(e),
+    };
+    let _ = tx.send(response);
+}
+
+/// Lists the addresses of all ANT+ USB devices plugged in. Start a run loop for each with
+/// RunConfig::stick to use more than one ANT+ USB device at a time. Only the known ANT+ USB
+/// devices are listed.
+pub fn list_sticks() -> Result<Vec<StickAddress>> {
+    list_sticks_with(&RunConfig::new())
+}
+
+/// Lists the addresses of all ANT+ USB devices plugged in that the run loop would use with the
+/// config, including models added with RunConfig::stick_model.
+pub fn list_sticks_with(config: &RunConfig) -> Result<Vec<StickAddress>> {
+    let ctx = crate::Context::new()?;
+    usb::list_devices_with_models(&ctx, &config.stick_models)
+}
+
+struct Ant<D: Transport> {
+    transport: Arc<D>,
     state: State,
     request: Receiver<Request>,
     message: Sender<Response>,
-    // By default we support 8 channels. A typical device could support 3 networks of 8 channels
-    // each, but from testing ANT+ devices I have, they only send data on one network, so only
-    // configure for 8 channels.
-    channels: [Option<Channel>; 8],
+    config: RunConfig,
+    // Number of network keys set on the ANT+ USB device since it was reset.
+    network_keys_set: usize,
+    // Channels supported by the ANT+ USB device, sized from the max channels in the capabilities
+    // message once the device is ready.
+    channels: Vec<Option<Channel>>,
+    // Burst transfers being reassembled for each channel.
+    bursts: Vec<BurstBuffer>,
+    // Acknowledged data transfers in progress for each channel. Acknowledged data can only be
+    // sent once per channel period, so transfers sent while one is in progress are queued and
+    // sent once it is done.
+    transfers: Vec<Option<Transfer>>,
+    queued_transfers: Vec<VecDeque<Transfer>>,
+    // Lib config flags for extended data applied when the ANT+ USB device is ready.
+    lib_config: Option<Message>,
+    // Event buffering configuration applied when the ANT+ USB device is ready.
+    event_buffer: Option<Message>,
+    // Event filter applied when the ANT+ USB device is ready.
+    event_filter: Option<Message>,
+    // Encryption keys for each volatile key index and the encryption ID applied when the ANT+
+    // USB device is ready.
+    encryption_keys: BTreeMap<u8, Message>,
+    encryption_id: Option<Message>,
+    // Selective data update masks for each mask index applied when the ANT+ USB device is ready.
+    sdu_masks: BTreeMap<u8, Message>,
+    // Senders registered for channels opened with OpenChannelWithSender. Kept until the
+    // channel is closed so the ChannelClosed response is also delivered to the channel's Sender.
+    subscribers: BTreeMap<u8, Sender<Response>>,
+    // Error from writing to an ANT+ USB device that is no longer available.
+    usb_error: Option<AntError>,
+    // Pages requested with Request::RequestPage that haven't arrived yet.
+    page_requests: Vec<PageRequest>,
+    // When data was last read from the ANT+ USB device, or when the watchdog was last idle as no
+    // channels were open.
+    last_read: Instant,
+    metrics: Metrics,
+    // Set once the application drops the Receiver for responses, so the run loop exits.
+    disconnected: bool,
 }
 
-impl<T: UsbContext> Ant<T> {
-    fn init(usb_device: UsbDevice<T>, rx: Receiver<Request>, tx: Sender<Response>) -> Ant<T> {
+impl<D: Transport> Ant<D> {
+    fn init(
+        transport: D,
+        rx: Receiver<Request>,
+        tx: Sender<Response>,
+        config: RunConfig,
+    ) -> Ant<D> {
+        let event_buffer = config
+            .event_buffer
+            .map(|(buffer, size, time)| message::set_event_buffer_config(buffer, size, time));
         Ant {
-            usb_device,
+            transport: Arc::new(transport),
             state: State::NotReady,
             request: rx,
             message: tx,
-            channels: Default::default(),
+            config,
+            network_keys_set: 0,
+            channels: Vec::new(),
+            bursts: Vec::new(),
+            transfers: Vec::new(),
+            queued_transfers: Vec::new(),
+            lib_config: None,
+            event_buffer,
+            event_filter: None,
+            encryption_keys: BTreeMap::new(),
+            encryption_id: None,
+            sdu_masks: BTreeMap::new(),
+            subscribers: BTreeMap::new(),
+            usb_error: None,
+            page_requests: Vec::new(),
+            last_read: Instant::now(),
+            metrics: Metrics::new(),
+            disconnected: false,
         }
     }
 
@@ -150,26 +785,66 @@ impl<T: UsbContext> Ant<T> {
     // the state of the system will decide if the system needs to be configured, or if configured,
     // check to see if any messages are waiting to be sent. Should a channel close due to error or
     // timeout, and the channel is still known, then the channel will be reopened until a request
-    // is sent to close the channel by an upstream application.
-    fn run(&mut self) -> Result<()> {
+    // is sent to close the channel by an upstream application. Quit requests are only handled
+    // once the ANT+ USB device is running, so quit is checked in every other state in case the
+    // ANT+ USB device never becomes ready.
+    fn run(&mut self, quit: &AtomicBool) -> Result<()> {
         // Check to see if we're already running
         if self.state == State::Running {
             return Err(AntError::AlreadyRunning);
         }
-        // ANT+ run loop to process/send messages
+        // ANT+ run loop to process/send messages. Data is read from the ANT+ USB device on a
+        // reader thread so the run loop can wait on both data and requests at the same time. If
+        // neither arrive within the read timeout, the state of the system will decide if the
+        // system needs to be configured.
+        let (data_tx, data_rx) = crossbeam_channel::unbounded();
+        let _reader = Reader::spawn(&self.transport, data_tx, self.config.read_timeout);
         let mut read_buffer = ReadBuffer::new();
         let mut reset_attempts = 0;
         loop {
-            // See if there are any messages to read
-            match self.usb_device.read(read_buffer.inner_as_mut()) {
-                Ok(len) => {
-                    read_buffer.len(len);
-                    for mesg in &mut read_buffer {
-                        trace! {"Routing message response: {:x?}", mesg};
-                        self.route(&mesg)
+            // Return if the ANT+ USB device went away while writing to it.
+            if let Some(e) = self.usb_error.take() {
+                return Err(e);
+            }
+            if self.disconnected {
+                debug!("Response receiver disconnected. Exiting...");
+                self.reset()?;
+                return Ok(());
+            }
+            if self.state != State::Running && quit.load(Ordering::SeqCst) {
+                debug!("Quit while waiting for ANT+ stick to be ready. Exiting...");
+                return Ok(());
+            }
+            if self.state == State::Running && self.watchdog_expired() {
+                error!("No data read from ANT+ USB device. Resetting");
+                self.restart();
+                reset_attempts = 0;
+                self.send(Response::StickReset);
+            }
+            // We only handle requests once in the running state
+            let requests = match self.state {
+                State::Running => self.request.clone(),
+                _ => crossbeam_channel::never(),
+            };
+            crossbeam_channel::select! {
+                recv(data_rx) -> data => {
+                    // The reader thread only exits early after sending the error it exited on.
+                    let data = data.map_err(|_| AntError::UsbDeviceError(rusb::Error::NoDevice))?;
+                    // Everything read since the last wakeup is handled before any requests, so
+                    // messages don't fall behind when many channels are open.
+                    for data in std::iter::once(data).chain(data_rx.try_iter()) {
+                        self.read(&mut read_buffer, data)?;
                     }
                 }
-                Err(AntError::UsbDeviceError(rusb::Error::Timeout)) => match self.state {
+                recv(requests) -> request => match request {
+                    Ok(request) => {
+                        if self.handle_request(request)? {
+                            return Ok(());
+                        }
+                    }
+                    Err(_) => break,
+                },
+                default(self.config.read_timeout) => match self.state {
                     State::NotReady => {
                         debug! {"Setting state to Reset"};
                         self.state = State::Reset;
@@ -181,8 +856,8 @@ impl<T: UsbContext> Ant<T> {
                         // thread exiting. If the ANT+ stick gets stuck
                         // in this state,no messages will be received and acted
                         // on, and reset messages will just continue to be sent.
-                        // This is configured to try three times then exit.
-                        if reset_attempts < 2 {
+                        // This is configured with RunConfig::reset_commands.
+                        if reset_attempts < self.config.reset_commands {
                             debug! {"Sending reset command"};
                             self.reset()?;
                             reset_attempts += 1;
@@ -190,66 +865,243 @@ impl<T: UsbContext> Ant<T> {
                             return Err(AntError::Reset);
                         }
                     }
+                    State::SetNetworkKey | State::GetCapabilities => {
+                        // The ANT+ stick stopped answering while being set up, so it is reset
+                        // again instead of waiting on it forever.
+                        error!("No response from ANT+ USB device. Resetting");
+                        debug! {"Setting state to Reset"};
+                        self.state = State::Reset;
+                    }
                     _ => {}
                 },
-                Err(e) => return Err(e),
             }
-            // Messages handled, let's see if there are any requests to
-            // operate on. We only handle requests once in the running state
             if let State::Running = self.state {
-                match self.request.try_recv() {
-                    Ok(request) => match request {
-                        Request::OpenChannel(number, device) => {
-                            if self.channels[number as usize].is_some() {
-                                error!("Channel {} already exists", number);
-                                self.message
-                                    .send(Response::Error(AntError::ChannelExists(number)))
-                                    .unwrap();
-                                continue;
-                            }
-                            // TODO: Handle error properly. For now, we'll just unwrap
-                            // so the thread panics if there are any issues
-                            // writing out to the ANT+ stick
-                            let channel = Channel::new(number, device);
-                            self.usb_device
-                                .write(&channel.assign(ANT_NETWORK).encode())
-                                .unwrap();
-                            self.channels[number as usize] = Some(channel);
-                        }
-                        Request::CloseChannel(number) => {
-                            if self.channels[number as usize].is_some() {
-                                debug!("Closing channel {}", number);
-                                self.usb_device
-                                    .write(&message::close_channel(number).encode())
-                                    .unwrap();
-                                self.channels[number as usize] = None
-                            }
-                        }
-                        Request::Send(mesg) => {
-                            self.usb_device.write(&mesg.encode()).unwrap();
-                        }
-                        Request::Quit => {
-                            self.reset()?;
-                            std::thread::sleep(std::time::Duration::from_millis(500));
-                            return Ok(());
-                        }
-                    },
-                    Err(crossbeam_channel::TryRecvError::Disconnected) => break,
-                    Err(_) => continue,
+                self.poll_reopen();
+                self.poll_page_requests();
+            }
+        }
+        Ok(())
+    }
+
+    // Routes every complete message in the data read from the ANT+ USB device.
+    fn read(&mut self, read_buffer: &mut ReadBuffer, data: Result<Vec<u8>>) -> Result<()> {
+        match data {
+            Ok(data) => {
+                trace!("Read {}", message::Hex(&data));
+                self.last_read = Instant::now();
+                self.metrics.bytes_read += data.len() as u64;
+                read_buffer.inner_as_mut()[..data.len()].copy_from_slice(&data);
+                read_buffer.len(data.len());
+                self.tap(RawFrame::Rx(data));
+                for mesg in &mut *read_buffer {
+                    trace! {"Routing message response: {:x?}", mesg};
+                    self.route(mesg)
                 }
+                self.metrics.parse_errors += read_buffer.take_errors();
+            }
+            Err(AntError::UsbDeviceError(e))
+                if e == rusb::Error::Interrupted || e == rusb::Error::Overflow =>
+            {
+                // Transient read errors drop the data read, but the ANT+ USB device is still
+                // usable.
+                error!("Error reading from ANT+ USB device: {}", e);
+                self.send(Response::Error(AntError::UsbDeviceError(e)));
             }
+            Err(e) => return Err(e),
         }
         Ok(())
     }
 
+    // Handles a request from the application. Returns true if the run loop should exit.
+    fn handle_request(&mut self, request: Request) -> Result<bool> {
+        if !self.valid_channel(&request) {
+            let number = request.channel().unwrap_or_default();
+            error!("Channel {} is not supported by the ANT+ USB device", number);
+            self.send(Response::Error(AntError::InvalidChannel(number)));
+            return Ok(false);
+        }
+        match request {
+            Request::OpenChannel(number, device) => self.open_channel(number, device, None),
+            Request::OpenChannelWithSender(number, device, sender) => {
+                self.open_channel(number, device, Some(sender))
+            }
+            Request::OpenScanMode(device) => {
+                if let Some(channel) = self.channels.iter().flatten().next() {
+                    let number = channel.number();
+                    error!("Scan mode requires channel {} to be closed", number);
+                    self.send(Response::Error(AntError::ChannelExists(number)));
+                    return Ok(false);
+                }
+                debug!("Opening channel 0 in scan mode");
+                let mesg = message::lib_config(message::LIB_CONFIG_CHANNEL_ID);
+                self.write(&mesg);
+                self.lib_config = Some(mesg);
+                let channel = Channel::new(0, device.scan());
+                self.write(&channel.assign(self.network(&channel)));
+                self.channels[0] = Some(channel);
+                self.subscribers.remove(&0);
+            }
+            Request::CloseChannel(number) => {
+                if self.channels[number as usize].is_some() {
+                    debug!("Closing channel {}", number);
+                    self.write(&message::close_channel(number));
+                    self.channels[number as usize] = None;
+                    self.fail_transfers(number);
+                    self.fail_page_requests(number);
+                }
+            }
+            Request::EnableExtendedMessages(flags) => {
+                debug!("Setting lib config flags to {:#x}", flags);
+                let mesg = message::lib_config(flags);
+                self.write(&mesg);
+                self.lib_config = Some(mesg);
+            }
+            Request::ConfigureEventBuffer(config, size, time) => {
+                debug!("Configuring event buffer: {:?} {} {}", config, size, time);
+                let mesg = message::set_event_buffer_config(config, size, time);
+                self.write(&mesg);
+                self.event_buffer = Some(mesg);
+            }
+            Request::FlushEventBuffer => match self.event_buffer.clone() {
+                // Reconfiguring the event buffer flushes any buffered events.
+                Some(mesg) => self.write(&mesg),
+                None => {
+                    error!("Event buffering is not configured");
+                    self.send(Response::Error(AntError::EventBufferNotConfigured));
+                }
+            },
+            Request::SetEventFilter(filter) => {
+                debug!("Setting event filter to {:#x}", filter);
+                let mesg = message::set_event_filter(filter);
+                self.write(&mesg);
+                self.event_filter = Some(mesg);
+            }
+            Request::SetEncryptionKey(index, key) => {
+                let mesg = message::set_encryption_key(index, &key);
+                self.write(&mesg);
+                self.encryption_keys.insert(index, mesg);
+            }
+            Request::SetEncryptionId(id) => {
+                let mesg = message::set_encryption_id(id);
+                self.write(&mesg);
+                self.encryption_id = Some(mesg);
+            }
+            Request::StoreEncryptionKey(nvm_index, key) => {
+                debug!("Storing encryption key in NVM key slot {}", nvm_index);
+                self.write(&message::store_encryption_key(nvm_index, &key));
+            }
+            Request::LoadEncryptionKey {
+                nvm_index,
+                volatile_index,
+            } => {
+                let mesg = message::load_encryption_key(nvm_index, volatile_index);
+                self.write(&mesg);
+                self.encryption_keys.insert(volatile_index, mesg);
+            }
+            Request::SetSduMask(index, mask) => {
+                let mesg = message::set_sdu_mask(index, &mask);
+                self.write(&mesg);
+                self.sdu_masks.insert(index, mesg);
+            }
+            Request::GetChannelStatus(number) => {
+                self.write(&message::get_channel_status(number));
+            }
+            Request::GetAntVersion => {
+                self.write(&message::get_ant_version());
+            }
+            Request::GetMetrics => self.send(Response::Metrics(self.metrics.clone())),
+            Request::GetChannelState(number) => self.send(Response::ChannelState(
+                number,
+                self.channels[number as usize].clone(),
+            )),
+            Request::GetSerialNumber => {
+                self.write(&message::get_serial_number());
+            }
+            Request::Send(mesg) => {
+                self.write(&mesg);
+            }
+            Request::SendAcknowledged(mesg, retries) => {
+                self.start_transfer(Transfer {
+                    channel: mesg.channel(),
+                    messages: vec![mesg.to_message()],
+                    retries,
+                    token: None,
+                    notify: true,
+                });
+            }
+            Request::SendAcknowledgedWithToken {
+                mesg,
+                retries,
+                token,
+            } => {
+                self.start_transfer(Transfer {
+                    channel: mesg.channel(),
+                    messages: vec![mesg.to_message()],
+                    retries,
+                    token: Some(token),
+                    notify: true,
+                });
+            }
+            Request::SendBurst { channel, data } => {
+                self.start_transfer(Transfer {
+                    channel,
+                    messages: message::burst_data(channel, &data),
+                    retries: 0,
+                    token: None,
+                    notify: true,
+                });
+            }
+            Request::SendBroadcast(number, data) => match &self.channels[number as usize] {
+                Some(channel) if channel.is_master() => {
+                    self.write(&channel.broadcast(&data));
+                }
+                _ => {
+                    error!("Channel {} is not a master channel", number);
+                    self.send(Response::Error(AntError::NotMasterChannel(number)));
+                }
+            },
+            Request::RequestPage {
+                channel,
+                page,
+                transmission,
+                attempts,
+            } => {
+                // A request for a page already requested replaces the earlier request.
+                self.page_requests
+                    .retain(|request| (request.channel, request.page) != (channel, page));
+                let request = PageRequest {
+                    channel,
+                    page,
+                    transmission,
+                    attempts: attempts.max(1),
+                    deadline: Instant::now(),
+                };
+                self.request_page(request);
+            }
+            Request::Quit => {
+                self.reset()?;
+                std::thread::sleep(self.config.reset_delay);
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     // Route handles what to do with the message based on the state of the system.
-    fn route(&mut self, message: &DeviceResponse) {
+    // Messages are routed by value so data received from ANT+ devices is moved to the
+    // application instead of copied.
+    fn route(&mut self, message: DeviceResponse) {
         match self.state {
             State::NotReady => {} // Drop message
             State::Reset => match message {
                 DeviceResponse::Startup(_mesg) => {
+                    if self.config.network_keys.is_empty() {
+                        self.request_capabilities();
+                        return;
+                    }
                     debug! {"Setting state to SetNetworkKey"};
                     self.state = State::SetNetworkKey;
+                    self.network_keys_set = 0;
                     debug! {"Setting network key"};
                     if let Err(e) = self.set_network_key() {
                         error! {"Error setting network key: {:?}", e};
@@ -261,28 +1113,57 @@ impl<T: UsbContext> Ant<T> {
             },
             State::SetNetworkKey => match message {
                 DeviceResponse::Startup(_mesg) => self.state = State::Reset,
-                DeviceResponse::ChannelResponse(mesg) => {
-                    if mesg.code() == ChannelResponseCode::ResponseNoError {
-                        debug! {"Setting state to Running"};
-                        self.state = State::Running;
+                DeviceResponse::ChannelResponse(mesg)
+                    if mesg.code() == ChannelResponseCode::ResponseNoError
+                        && mesg.message_id() == MessageId::NetworkKey =>
+                {
+                    self.network_keys_set += 1;
+                    if self.network_keys_set == self.config.network_keys.len() {
+                        self.request_capabilities();
+                    } else if let Err(e) = self.set_network_key() {
+                        error! {"Error setting network key: {:?}", e};
+                        debug! {"Setting state to Reset"};
+                        self.state = State::Reset;
                     }
                 }
                 _ => {}
             },
+            State::GetCapabilities => match message {
+                DeviceResponse::Startup(_mesg) => self.state = State::Reset,
+                DeviceResponse::Capabilities(capabilities) => {
+                    info!(
+                        "ANT+ USB device supports {} channels",
+                        capabilities.max_channels
+                    );
+                    self.set_channel_count(capabilities.max_channels as usize);
+                    self.ready();
+                }
+                _ => {}
+            },
             State::Running => match message {
                 DeviceResponse::Startup(_mesg) => self.state = State::Reset,
+                DeviceResponse::ChannelResponse(mesg)
+                    if mesg.channel() as usize >= self.channels.len() =>
+                {
+                    // The ANT+ USB device doesn't support the channel, so there is nothing to
+                    // update for it.
+                    debug!("Dropping response for unknown channel: {:x?}", mesg);
+                }
                 DeviceResponse::ChannelResponse(mesg) => {
                     // Check to see if we have an event
-                    if mesg.message_id() == 1 {
+                    if mesg.message_id() == MessageId::Event {
                         match mesg.code() {
                             ChannelResponseCode::EventRxFail => {
                                 trace!("EVENT_RX_FAIL received on channel {}", mesg.channel());
+                                self.metrics.channel_mut(mesg.channel()).rx_fails += 1;
                             }
                             ChannelResponseCode::EventRxSearchTimeout => {
                                 trace!(
                                     "EVENT_RX_SEARCH_TIMEOUT received on channel {}",
                                     mesg.channel()
                                 );
+                                self.metrics.channel_mut(mesg.channel()).search_timeouts += 1;
+                                self.send(Response::SearchTimeout(mesg.channel()));
                             }
                             ChannelResponseCode::EventRxFailGoToSearch => {
                                 trace!(
@@ -290,51 +1171,127 @@ impl<T: UsbContext> Ant<T> {
                                     mesg.channel()
                                 );
                             }
+                            ChannelResponseCode::EventTransferRxFailed => {
+                                trace!(
+                                    "EVENT_TRANSFER_RX_FAILED received on channel {}",
+                                    mesg.channel()
+                                );
+                                if let Some(burst) = self.bursts.get_mut(mesg.channel() as usize) {
+                                    burst.clear();
+                                }
+                            }
+                            ChannelResponseCode::EventTransferTxCompleted => {
+                                trace!(
+                                    "EVENT_TRANSFER_TX_COMPLETED received on channel {}",
+                                    mesg.channel()
+                                );
+                                let transfer = self
+                                    .transfers
+                                    .get_mut(mesg.channel() as usize)
+                                    .and_then(Option::take);
+                                if let Some(transfer) = transfer {
+                                    self.finish_transfer(mesg.channel(), transfer, true);
+                                    self.next_transfer(mesg.channel());
+                                }
+                            }
+                            ChannelResponseCode::EventTransferTxFailed => {
+                                trace!(
+                                    "EVENT_TRANSFER_TX_FAILED received on channel {}",
+                                    mesg.channel()
+                                );
+                                self.retry_transfer(mesg.channel());
+                            }
+                            ChannelResponseCode::EventTx => {
+                                // A master channel transmitted its data. The ANT+ USB device
+                                // will keep sending the same data until new data is sent, so
+                                // let the application know it can send the next data page.
+                                trace!("EVENT_TX received on channel {}", mesg.channel());
+                                if let Some(Some(_)) = self.channels.get(mesg.channel() as usize) {
+                                    self.send(Response::EventTx(mesg.channel()));
+                                }
+                            }
+                            ChannelResponseCode::EventEncryptNegotiationSuccess => {
+                                debug!("Encryption negotiated on channel {}", mesg.channel());
+                                self.send(Response::EncryptionNegotiated(mesg.channel()));
+                            }
+                            ChannelResponseCode::EventEncryptNegotiationFail => {
+                                error!(
+                                    "Encryption negotiation failed on channel {}",
+                                    mesg.channel()
+                                );
+                                self.send(Response::EncryptionFailed(mesg.channel()));
+                            }
                             ChannelResponseCode::EventChannelClosed => {
                                 // If a channel closed message is received, but the
                                 // the channel was not requested to be closed, re-open
-                                // the channel.
+                                // the channel as allowed by the channel's reopen policy.
                                 trace!(
                                     "EVENT_CHANNEL_CLOSED received on channel {}",
                                     mesg.channel()
                                 );
-                                let send_mesg = match &mut self.channels[mesg.channel() as usize] {
-                                    Some(c) => {
-                                        // If a channel closed message is received, but the
-                                        // the channel was not requested to be closed, re-open
-                                        // the channel.
-                                        info!("Re-opening channel {}", mesg.channel());
-                                        c.open().encode()
+                                let number = mesg.channel();
+                                let now = Instant::now();
+                                let reopen = self
+                                    .channels
+                                    .get_mut(number as usize)
+                                    .and_then(Option::as_mut)
+                                    .map(|c| c.closed(now));
+                                let send_mesg = match reopen {
+                                    Some(true) => {
+                                        info!("Re-opening channel {}", number);
+                                        self.channels[number as usize]
+                                            .as_mut()
+                                            .and_then(|c| c.poll_reopen(now))
+                                    }
+                                    Some(false) => {
+                                        info!("Giving up re-opening channel {}", number);
+                                        self.channels[number as usize] = None;
+                                        self.fail_transfers(number);
+                                        self.fail_page_requests(number);
+                                        self.send(Response::ChannelLost(number));
+                                        self.subscribers.remove(&number);
+                                        Some(message::unassign_channel(number))
                                     }
                                     None => {
                                         // Unassign channel that was closed
-                                        debug!("Unassigning channel {}", mesg.channel());
-                                        crate::message::unassign_channel(mesg.channel()).encode()
+                                        debug!("Unassigning channel {}", number);
+                                        self.send(Response::ChannelClosed(number));
+                                        self.subscribers.remove(&number);
+                                        Some(message::unassign_channel(number))
                                     }
                                 };
-                                self.usb_device.write(&send_mesg).unwrap();
+                                if let Some(send_mesg) = send_mesg {
+                                    self.write(&send_mesg);
+                                }
                             }
                             _ => {
                                 trace!("Unhandled event received: {:x?}", mesg);
                             }
                         }
                         return;
-                        //unimplemented!();
                     }
                     // TODO: There will be other codes, but for now just have one.
                     // Currently if something else is received, the code will
                     // panic until we add support for it. Happy path for now.
                     match mesg.code() {
                         ChannelResponseCode::ResponseNoError => {
-                            if let Some(c) = &mut self.channels[mesg.channel() as usize] {
-                                // Should use this to update state and then
-                                // then configure the next message. We
-                                // don't have a copy of the TX side of our
-                                // request channel here. May have to rethink
-                                // how that gets created and handled, or figure out
-                                // a better way to send the next message.
-                                if let Some(mesg) = c.route(mesg) {
-                                    let _ = self.usb_device.write(&mesg.encode());
+                            if let Some(Some(c)) = self.channels.get_mut(mesg.channel() as usize) {
+                                // The channel returns the next message to configure it, and
+                                // lets the application know once the channel is open.
+                                let reopening = c.is_reopening();
+                                let was_open = c.is_open();
+                                let next = c.route(&mesg);
+                                let queued = c.take_queued();
+                                let response = match (c.is_open() && !was_open, reopening) {
+                                    (true, true) => Some(Response::ChannelReopened(c.number())),
+                                    (true, false) => Some(Response::ChannelOpened(c.number())),
+                                    _ => None,
+                                };
+                                for mesg in next.iter().chain(&queued) {
+                                    self.write(mesg);
+                                }
+                                if let Some(response) = response {
+                                    self.send(response);
                                 }
                             }
                         }
@@ -347,29 +1304,1192 @@ impl<T: UsbContext> Ant<T> {
                         _ => trace!("Unhandled channel response received: {:x?}", mesg),
                     }
                 }
-                DeviceResponse::BroadcastData(mesg) => self
-                    .message
-                    .send(Response::BroadcastData(mesg.clone()))
-                    .unwrap(),
+                DeviceResponse::BroadcastData(mesg) => {
+                    self.metrics.channel_mut(mesg.channel()).broadcasts += 1;
+                    // Learn the device a wildcard channel paired with once it starts tracking.
+                    let request = match self.channels.get_mut(mesg.channel() as usize) {
+                        Some(Some(channel)) => {
+                            channel.received();
+                            channel.request_channel_id()
+                        }
+                        _ => None,
+                    };
+                    if let Some(request) = request {
+                        self.write(&request);
+                    }
+                    self.page_received(mesg.channel(), &mesg.data());
+                    self.send(Response::BroadcastData(mesg))
+                }
+                DeviceResponse::AcknowledgeData(mesg) => {
+                    self.page_received(mesg.channel(), mesg.data());
+                    self.send(Response::AcknowledgeData(mesg))
+                }
+                DeviceResponse::BurstData(mesg) => {
+                    let channel = mesg.channel();
+                    let response = match self.bursts.get_mut(channel as usize) {
+                        Some(burst) => match burst.push(&mesg) {
+                            Ok(Some(data)) => Response::BurstData(channel, data),
+                            Ok(None) => return,
+                            Err(e) => Response::Error(e),
+                        },
+                        None => return,
+                    };
+                    self.send(response);
+                }
+                DeviceResponse::ChannelId(mesg) => self.send(Response::ChannelIdAcquired {
+                    channel: mesg.channel(),
+                    device_id: mesg.device_id(),
+                    device_type: mesg.device_type(),
+                    transmission_type: mesg.transmission_type(),
+                }),
+                DeviceResponse::ChannelStatus(mesg) => {
+                    self.send(Response::ChannelStatus(mesg.channel(), mesg.status()))
+                }
+                DeviceResponse::AntVersion(mesg) => {
+                    info!("ANT+ USB device version {}", mesg.version());
+                    self.send(Response::AntVersion(mesg.version().to_string()));
+                }
+                DeviceResponse::SerialNumber(mesg) => {
+                    info!("ANT+ USB device serial number {}", mesg.serial_number());
+                    self.send(Response::SerialNumber(mesg.serial_number()));
+                }
+                DeviceResponse::Unknown { id, data } => self.send(Response::Unknown { id, data }),
+                _ => debug!("{:x?}", message),
             },
         }
     }
 
-    fn reset(&self) -> Result<()> {
-        self.usb_device.write(&message::reset().encode())?;
-        std::thread::sleep(std::time::Duration::from_millis(500));
+    // Writes a message to the ANT+ USB device. A failed write is sent to the application with the
+    // ID of the message that failed rather than stopping the run loop. If the ANT+ USB device is
+    // gone, the error is kept so the run loop returns and the ANT+ USB device can be
+    // re-initialized.
+    fn write(&mut self, mesg: &Message) {
+        match self.write_message(mesg) {
+            Ok(_) => {}
+            Err(e) if disconnected_error(&e) => self.usb_error = Some(e),
+            Err(AntError::UsbDeviceError(e)) => {
+                error!("Error writing message {:#x}: {}", mesg.id, e);
+                self.send(Response::Error(AntError::WriteError {
+                    id: mesg.id,
+                    source: e,
+                }));
+            }
+            Err(e) => self.send(Response::Error(e)),
+        }
+    }
+
+    // Sends a response to the application. If the application has dropped its receiver, the
+    // response is dropped and the run loop exits.
+    fn send(&mut self, response: Response) {
+        let subscriber = response
+            .channel()
+            .and_then(|number| self.subscribers.get(&number));
+        let response = match subscriber {
+            Some(subscriber) => match subscriber.send(response) {
+                Ok(_) => return,
+                Err(e) => e.into_inner(),
+            },
+            None => response,
+        };
+        if self.message.send(response).is_err() {
+            self.disconnected = true;
+        }
+    }
+
+    // Assigns a channel, registering the Sender responses for the channel are sent to if given.
+    fn open_channel(&mut self, number: u8, device: Config, subscriber: Option<Sender<Response>>) {
+        if self.scanning() {
+            error!("Channel 0 is open in scan mode");
+            self.send(Response::Error(AntError::ChannelExists(0)));
+            return;
+        }
+        if self.channels[number as usize].is_some() {
+            error!("Channel {} already exists", number);
+            self.send(Response::Error(AntError::ChannelExists(number)));
+            return;
+        }
+        match subscriber {
+            Some(subscriber) => self.subscribers.insert(number, subscriber),
+            None => self.subscribers.remove(&number),
+        };
+        let channel = Channel::new(number, device);
+        self.write(&channel.assign(self.network(&channel)));
+        self.channels[number as usize] = Some(channel);
+    }
+
+    // Requests the capabilities of the ANT+ USB device to find how many channels it supports.
+    fn request_capabilities(&mut self) {
+        debug! {"Setting state to GetCapabilities"};
+        self.state = State::GetCapabilities;
+        if let Err(e) = self.get_capabilities() {
+            error! {"Error requesting capabilities: {:?}", e};
+            debug! {"Setting state to Reset"};
+            self.state = State::Reset;
+        }
+    }
+
+    // Sizes the channel table to the number of channels supported by the ANT+ USB device. Known
+    // channels beyond the number supported are dropped.
+    fn set_channel_count(&mut self, count: usize) {
+        for channel in self.channels.iter().skip(count).flatten() {
+            error!("Dropping unsupported channel {}", channel.number());
+        }
+        self.channels.resize_with(count, Default::default);
+        self.bursts.resize_with(count, Default::default);
+        self.transfers.resize_with(count, Default::default);
+        self.queued_transfers.resize_with(count, Default::default);
+    }
+
+    // Requests for a channel need the channel to be supported by the ANT+ USB device.
+    fn valid_channel(&self, request: &Request) -> bool {
+        match request.channel() {
+            Some(number) => (number as usize) < self.channels.len(),
+            None => true,
+        }
+    }
+
+    // Reopens channels waiting on their reopen policy's backoff.
+    fn poll_reopen(&mut self) {
+        let now = Instant::now();
+        let reopen: Vec<Message> = self
+            .channels
+            .iter_mut()
+            .flatten()
+            .filter_map(|channel| channel.poll_reopen(now))
+            .collect();
+        for mesg in reopen {
+            self.write(&mesg);
+        }
+    }
+
+    // Sends the page request for another attempt, waiting the page request timeout for the page.
+    fn request_page(&mut self, mut request: PageRequest) {
+        debug!(
+            "Requesting page {:#x} on channel {}",
+            request.page, request.channel
+        );
+        request.attempts -= 1;
+        request.deadline = Instant::now() + self.config.page_request_timeout;
+        let mesg = common::request_data_page(request.channel, request.page, request.transmission);
+        self.start_transfer(Transfer {
+            channel: request.channel,
+            messages: vec![mesg.to_message()],
+            retries: 0,
+            token: None,
+            notify: false,
+        });
+        self.page_requests.push(request);
+    }
+
+    // Completes the request for a page once it arrives.
+    fn page_received(&mut self, channel: u8, data: &[u8]) {
+        let page = match self.channels.get(channel as usize) {
+            Some(Some(c)) => c.page_number(data),
+            _ => data[0],
+        };
+        let len = self.page_requests.len();
+        self.page_requests
+            .retain(|request| (request.channel, request.page) != (channel, page));
+        if self.page_requests.len() != len {
+            self.send(Response::PageRequestResult {
+                channel,
+                page,
+                ok: true,
+            });
+        }
+    }
+
+    // Requests pages again that haven't arrived in time, or lets the application know the page
+    // never arrived once the attempts are used up.
+    fn poll_page_requests(&mut self) {
+        let now = Instant::now();
+        let (expired, waiting) = std::mem::take(&mut self.page_requests)
+            .into_iter()
+            .partition(|request| request.deadline <= now);
+        self.page_requests = waiting;
+        for request in expired {
+            if request.attempts > 0 {
+                self.request_page(request);
+            } else {
+                self.send(Response::PageRequestResult {
+                    channel: request.channel,
+                    page: request.page,
+                    ok: false,
+                });
+            }
+        }
+    }
+
+    // Scan mode uses channel 0 and blocks any other channels from being opened.
+    fn scanning(&self) -> bool {
+        matches!(self.channels.first(), Some(Some(channel)) if channel.is_scan())
+    }
+
+    // Resends a failed acknowledged data transfer if there are retries left, otherwise lets the
+    // application know the transfer failed.
+    fn retry_transfer(&mut self, channel: u8) {
+        let transfer = match self.transfers.get_mut(channel as usize) {
+            Some(transfer) => transfer,
+            None => return,
+        };
+        match transfer {
+            Some(t) if t.retries > 0 => {
+                debug!("Retrying transfer on channel {}", channel);
+                t.retries -= 1;
+                let messages = t.messages.clone();
+                for mesg in &messages {
+                    self.write(mesg);
+                }
+            }
+            Some(_) => {
+                if let Some(transfer) = transfer.take() {
+                    self.finish_transfer(channel, transfer, false);
+                    self.next_transfer(channel);
+                }
+            }
+            None => {}
+        }
+    }
+
+    // Fails the transfer in progress and any queued transfers on a channel that is closed or
+    // lost, as they will never complete.
+    fn fail_transfers(&mut self, channel: u8) {
+        let number = channel as usize;
+        let queued = std::mem::take(&mut self.queued_transfers[number]);
+        for transfer in self.transfers[number].take().into_iter().chain(queued) {
+            self.finish_transfer(channel, transfer, false);
+        }
+    }
+
+    // Encodes a message on the stack and writes it to the ANT+ USB device.
+    fn write_message(&mut self, mesg: &Message) -> Result<usize> {
+        let mut buf = [0; message::MESG_MAX_SIZE];
+        let len = mesg.encode_into(&mut buf);
+        self.write_bytes(&buf[..len])
+    }
+
+    // Writes to the ANT+ USB device, counting the bytes written.
+    fn write_bytes(&mut self, data: &[u8]) -> Result<usize> {
+        trace!("Write {}", message::Hex(data));
+        let len = self.transport.write_with_timeout(data, WRITE_TIMEOUT)?;
+        self.metrics.bytes_written += len as u64;
+        self.tap(RawFrame::Tx(data.to_vec()));
+        Ok(len)
+    }
+
+    // Sends raw bytes to the tap, if set.
+    fn tap(&mut self, frame: RawFrame) {
+        if let Some(tap) = &self.config.tap {
+            if tap.send(frame).is_err() {
+                debug!("Tap receiver disconnected. Removing tap");
+                self.config.tap = None;
+            }
+        }
+    }
+
+    // Starts an acknowledged data or burst transfer, or queues it if a transfer is already in
+    // progress on the channel.
+    fn start_transfer(&mut self, transfer: Transfer) {
+        let number = transfer.channel as usize;
+        if self.transfers[number].is_some() {
+            debug!(
+                "Transfer in progress on channel {}. Queueing transfer",
+                number
+            );
+            self.queued_transfers[number].push_back(transfer);
+            return;
+        }
+        for mesg in &transfer.messages {
+            self.write(mesg);
+        }
+        self.transfers[number] = Some(transfer);
+    }
+
+    // Starts the next queued transfer on the channel once the previous transfer is done.
+    fn next_transfer(&mut self, channel: u8) {
+        if let Some(transfer) = self.queued_transfers[channel as usize].pop_front() {
+            self.start_transfer(transfer);
+        }
+    }
+
+    // Lets the application know an acknowledged data or burst transfer is done, with the token if
+    // the transfer was sent with one.
+    fn finish_transfer(&mut self, channel: u8, transfer: Transfer, ok: bool) {
+        let metrics = self.metrics.channel_mut(channel);
+        if ok {
+            metrics.transfers_completed += 1;
+        } else {
+            metrics.transfers_failed += 1;
+        }
+        if !transfer.notify {
+            return;
+        }
+        let response = match (transfer.token, ok) {
+            (Some(token), ok) => Response::TransferResult { token, ok },
+            (None, true) => Response::TransferCompleted(channel),
+            (None, false) => Response::TransferFailed(channel),
+        };
+        self.send(response);
+    }
+
+    // Lets the application know the pages requested on a channel that is closed or lost will
+    // never arrive.
+    fn fail_page_requests(&mut self, channel: u8) {
+        let (failed, waiting) = std::mem::take(&mut self.page_requests)
+            .into_iter()
+            .partition(|request: &PageRequest| request.channel == channel);
+        self.page_requests = waiting;
+        for request in failed {
+            self.send(Response::PageRequestResult {
+                channel,
+                page: request.page,
+                ok: false,
+            });
+        }
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.write_message(&message::reset())?;
+        std::thread::sleep(self.config.reset_delay);
         Ok(())
     }
 
-    fn set_network_key(&self) -> Result<()> {
-        self.usb_device
-            .write(&message::set_network_key(ANT_NETWORK, &ANT_NETWORK_KEY).encode())?;
+    // Sets the next network key that has not been set since the ANT+ USB device was reset.
+    fn set_network_key(&mut self) -> Result<()> {
+        let mesg = match self.config.network_keys.iter().nth(self.network_keys_set) {
+            Some((network, key)) => {
+                debug!("Setting network key for network {}", network);
+                message::set_network_key(*network, key)
+            }
+            None => return Ok(()),
+        };
+        self.write_message(&mesg)?;
         Ok(())
     }
 
-    fn get_capabilities(&self) -> Result<()> {
-        self.usb_device
-            .write(&message::get_capabilities().encode())?;
+    // Checks whether the watchdog timeout has passed since data was last read. The watchdog only
+    // runs while channels are open, as an ANT+ USB device without open channels has nothing to
+    // send.
+    fn watchdog_expired(&mut self) -> bool {
+        let timeout = match self.config.watchdog {
+            Some(timeout) => timeout,
+            None => return false,
+        };
+        if self.channels.iter().flatten().next().is_none() {
+            self.last_read = Instant::now();
+            return false;
+        }
+        self.last_read.elapsed() >= timeout
+    }
+
+    // Swaps in the USB device for an ANT+ stick that was plugged back in.
+    fn reconnect(&mut self, usb_device: D) {
+        self.transport = Arc::new(usb_device);
+        self.restart();
+    }
+
+    // Resets the ANT+ stick. Transfers in progress and queued are failed, and channels are
+    // re-assigned once the ANT+ stick is ready.
+    fn restart(&mut self) {
+        self.state = State::NotReady;
+        self.last_read = Instant::now();
+        self.usb_error = None;
+        for burst in self.bursts.iter_mut() {
+            burst.clear();
+        }
+        for channel in 0..self.transfers.len() {
+            self.fail_transfers(channel as u8);
+        }
+    }
+
+    // The ANT+ USB device is ready to accept channel configurations. Any known channels are
+    // re-assigned as the ANT+ USB device has been reset.
+    fn ready(&mut self) {
+        debug! {"Setting state to Running"};
+        self.state = State::Running;
+        // Log the firmware version of the ANT+ USB device once it is ready.
+        if let Err(e) = self.get_ant_version() {
+            error! {"Error requesting ANT version: {:?}", e};
+        }
+        if let Err(e) = self.restore_config() {
+            error! {"Error restoring configuration: {:?}", e};
+        }
+        let mut assign = Vec::new();
+        for channel in self.channels.iter_mut().flatten() {
+            info!("Re-assigning channel {}", channel.number());
+            channel.reset();
+            let network = channel.network().unwrap_or(self.config.network);
+            assign.push(channel.assign(network));
+        }
+        for mesg in assign {
+            self.write(&mesg);
+        }
+    }
+
+    // Channels use the network from their configuration, or the default network if not set.
+    fn network(&self, channel: &Channel) -> u8 {
+        channel.network().unwrap_or(self.config.network)
+    }
+
+    // Lib config flags, event buffering, event filters, volatile encryption keys, and SDU masks
+    // are lost when the ANT+ USB device is reset, so reapply any that were configured.
+    fn restore_config(&mut self) -> Result<()> {
+        let config: Vec<Message> = self
+            .lib_config
+            .iter()
+            .chain(&self.event_buffer)
+            .chain(&self.event_filter)
+            .chain(self.encryption_keys.values())
+            .chain(&self.encryption_id)
+            .chain(self.sdu_masks.values())
+            .cloned()
+            .collect();
+        for mesg in config {
+            self.write_message(&mesg)?;
+        }
         Ok(())
     }
+
+    fn get_ant_version(&mut self) -> Result<()> {
+        self.write_message(&message::get_ant_version())?;
+        Ok(())
+    }
+
+    fn get_capabilities(&mut self) -> Result<()> {
+        self.write_message(&message::get_capabilities())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transport::{self, MockTransport};
+    use std::thread::{self, JoinHandle};
+
+    const RECV_TIMEOUT: Duration = Duration::from_secs(2);
+    const EVENT_TRANSFER_TX_COMPLETED: u8 = 0x05;
+    const EVENT_TRANSFER_TX_FAILED: u8 = 0x06;
+
+    // Starts the run loop on the MockTransport.
+    fn run_mock(mock: &MockTransport) -> (Sender<Request>, Receiver<Response>, JoinHandle<()>) {
+        run_mock_with_config(mock, test_config())
+    }
+
+    fn run_mock_with_config(
+        mock: &MockTransport,
+        config: RunConfig,
+    ) -> (Sender<Request>, Receiver<Response>, JoinHandle<()>) {
+        let (request_tx, request_rx) = crossbeam_channel::unbounded();
+        let (message_tx, message_rx) = crossbeam_channel::unbounded();
+        let mock = mock.clone();
+        let handle = thread::spawn(move || run_transport(mock, request_rx, message_tx, config));
+        (request_tx, message_rx, handle)
+    }
+
+    // RunConfig with short timeouts so tests don't wait on the ANT+ stick.
+    fn test_config() -> RunConfig {
+        RunConfig::new()
+            .read_timeout(Duration::from_millis(10))
+            .reset_delay(Duration::from_millis(10))
+    }
+
+    // Opens a channel and waits for it to be open.
+    fn open_channel(requests: &Sender<Request>, responses: &Receiver<Response>, number: u8) {
+        let config = Config::new().device_type(0x78).frequency(57).period(8070);
+        requests.send(Request::OpenChannel(number, config)).unwrap();
+        recv_until(
+            responses,
+            |r| matches!(r, Response::ChannelOpened(n) if *n == number),
+        );
+    }
+
+    // MockTransport::stick that replies to acknowledged data with the transfer events returned
+    // by events for each acknowledged data message written, numbered from 1.
+    fn transfer_stick<F>(mut events: F) -> MockTransport
+    where
+        F: FnMut(usize) -> Option<u8> + Send + 'static,
+    {
+        let mut written = 0;
+        MockTransport::new().respond_with(move |mesg| {
+            if mesg.message_id() != MessageId::AcknowledgeData {
+                return transport::stick_replies(8, mesg);
+            }
+            written += 1;
+            events(written)
+                .map(|code| {
+                    let data = [mesg.data()[0], MessageId::Event.into(), code];
+                    Message::with_payload(MessageId::ResponseEvent.into(), &data)
+                })
+                .into_iter()
+                .collect()
+        })
+    }
+
+    // Acknowledged data messages written to the MockTransport.
+    fn acknowledged(mock: &MockTransport) -> Vec<Message> {
+        mock.written()
+            .into_iter()
+            .filter(|mesg| mesg.message_id() == MessageId::AcknowledgeData)
+            .collect()
+    }
+
+    // Receives responses until one matches.
+    fn recv_until<F: Fn(&Response) -> bool>(responses: &Receiver<Response>, f: F) -> Response {
+        loop {
+            let response = responses.recv_timeout(RECV_TIMEOUT).unwrap();
+            if f(&response) {
+                return response;
+            }
+        }
+    }
+
+    #[test]
+    fn run_loop_opens_channel() {
+        let mock = MockTransport::stick(8);
+        let (requests, responses, handle) = run_mock(&mock);
+        let config = Config::new().device_type(0x78).frequency(57).period(8070);
+        requests.send(Request::OpenChannel(0, config)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::ChannelOpened(0)));
+
+        let written: Vec<MessageId> = mock.written().iter().map(Message::message_id).collect();
+        assert_eq!(
+            written,
+            [
+                MessageId::Reset,
+                MessageId::NetworkKey,
+                MessageId::Request,
+                MessageId::Request,
+                MessageId::AssignChannel,
+                MessageId::ChannelId,
+                MessageId::ChannelSearchTimeout,
+                MessageId::ChannelPeriod,
+                MessageId::ChannelRadioFrequency,
+                MessageId::OpenChannel,
+            ]
+        );
+
+        mock.push(&BroadcastDataMessage::new(0, &[0x04, 0, 0, 0, 0, 0, 0, 72]).to_message());
+        match recv_until(&responses, |r| matches!(r, Response::BroadcastData(_))) {
+            Response::BroadcastData(mesg) => assert_eq!(mesg.data()[7], 72),
+            _ => unreachable!(),
+        }
+
+        requests.send(Request::CloseChannel(0)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::ChannelClosed(0)));
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_sets_network_keys() {
+        let key = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mock = MockTransport::stick(8);
+        let config = test_config().network_key(0, key).network(0);
+        let (requests, responses, handle) = run_mock_with_config(&mock, config);
+        open_channel(&requests, &responses, 0);
+        let config = Config::new()
+            .device_type(0x78)
+            .frequency(57)
+            .period(8070)
+            .network(ANT_NETWORK);
+        requests.send(Request::OpenChannel(1, config)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::ChannelOpened(1)));
+
+        // Keys are set in order of network before capabilities are requested.
+        let written = mock.written();
+        let ids: Vec<MessageId> = written.iter().take(4).map(Message::message_id).collect();
+        assert_eq!(
+            ids,
+            [
+                MessageId::Reset,
+                MessageId::NetworkKey,
+                MessageId::NetworkKey,
+                MessageId::Request,
+            ]
+        );
+        assert_eq!(written[1].data(), [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(written[2].data()[0], ANT_NETWORK);
+        assert_eq!(written[2].data()[1..], ANT_NETWORK_KEY);
+        let networks: Vec<(u8, u8)> = written
+            .iter()
+            .filter(|mesg| mesg.message_id() == MessageId::AssignChannel)
+            .map(|mesg| (mesg.data()[0], mesg.data()[2]))
+            .collect();
+        assert_eq!(networks, [(0, 0), (1, ANT_NETWORK)]);
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_without_network_keys() {
+        let mock = MockTransport::stick(8);
+        let config = test_config().clear_network_keys();
+        let (requests, responses, handle) = run_mock_with_config(&mock, config);
+        open_channel(&requests, &responses, 0);
+        let written: Vec<MessageId> = mock.written().iter().map(Message::message_id).collect();
+        assert_eq!(written[..2], [MessageId::Reset, MessageId::Request]);
+        assert!(!written.contains(&MessageId::NetworkKey));
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_network_key_reset() {
+        // The ANT+ stick resets while the second key is being set, so the keys are set again.
+        let mut resets = 0;
+        let mut keys = 0;
+        let mock = MockTransport::new().respond_with(move |mesg| match mesg.message_id() {
+            MessageId::Reset => {
+                resets += 1;
+                transport::stick_replies(8, mesg)
+            }
+            MessageId::NetworkKey => {
+                keys += 1;
+                if keys == 2 && resets == 1 {
+                    vec![Message::with_payload(MessageId::Startup.into(), &[0x00])]
+                } else {
+                    transport::stick_replies(8, mesg)
+                }
+            }
+            _ => transport::stick_replies(8, mesg),
+        });
+        let config = test_config().network_key(0, [1, 2, 3, 4, 5, 6, 7, 8]);
+        let (requests, responses, handle) = run_mock_with_config(&mock, config);
+        open_channel(&requests, &responses, 0);
+        let written: Vec<MessageId> = mock.written().iter().map(Message::message_id).collect();
+        let keys = written
+            .iter()
+            .filter(|&&id| id == MessageId::NetworkKey)
+            .count();
+        assert_eq!(keys, 4);
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_capabilities_timeout() {
+        // The ANT+ stick ignores the first capabilities request.
+        let mut requested = false;
+        let mock = MockTransport::new().respond_with(move |mesg| {
+            if mesg.message_id() == MessageId::Request
+                && mesg.data()[1] == u8::from(MessageId::Capabilities)
+                && !requested
+            {
+                requested = true;
+                return Vec::new();
+            }
+            transport::stick_replies(8, mesg)
+        });
+        let (requests, responses, handle) = run_mock(&mock);
+        open_channel(&requests, &responses, 0);
+        let written: Vec<MessageId> = mock.written().iter().map(Message::message_id).collect();
+        let resets = written.iter().filter(|&&id| id == MessageId::Reset).count();
+        assert_eq!(resets, 2);
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_without_channels() {
+        let mock = MockTransport::stick(0);
+        let (requests, responses, handle) = run_mock(&mock);
+        requests
+            .send(Request::OpenScanMode(Config::new().frequency(57)))
+            .unwrap();
+        recv_until(&responses, |r| {
+            matches!(r, Response::Error(AntError::InvalidChannel(0)))
+        });
+        mock.push(&BroadcastDataMessage::new(0, &[0x04, 0, 0, 0, 0, 0, 0, 72]).to_message());
+        requests.send(Request::GetMetrics).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::Metrics(_)));
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_read_errors() {
+        let mock = MockTransport::stick(8);
+        let (_requests, responses, handle) = run_mock(&mock);
+        mock.push_error(rusb::Error::Interrupted);
+        recv_until(&responses, |r| {
+            matches!(
+                r,
+                Response::Error(AntError::UsbDeviceError(rusb::Error::Interrupted))
+            )
+        });
+        mock.disconnect();
+        recv_until(&responses, |r| matches!(r, Response::StickDisconnected));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_reconnects() {
+        let first = MockTransport::stick(8);
+        let second = MockTransport::stick(8);
+        let mut sticks = vec![Ok(second.clone()), Ok(first.clone())];
+        let (requests, request_rx) = crossbeam_channel::unbounded();
+        let (message_tx, responses) = crossbeam_channel::unbounded();
+        let config = RunConfig::new()
+            .read_timeout(Duration::from_millis(10))
+            .reset_delay(Duration::from_millis(10))
+            .discovery(Backoff::new().interval(Duration::from_millis(10)));
+        let handle = thread::spawn(move || {
+            run_devices(
+                request_rx,
+                message_tx,
+                config,
+                &AtomicBool::new(false),
+                |_| {
+                    sticks
+                        .pop()
+                        .unwrap_or(Err(AntError::UsbDeviceError(rusb::Error::NoDevice)))
+                },
+            )
+        });
+        let config = Config::new().device_type(0x78).frequency(57).period(8070);
+        requests.send(Request::OpenChannel(0, config)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::ChannelOpened(0)));
+
+        first.push_error(rusb::Error::NoDevice);
+        recv_until(&responses, |r| matches!(r, Response::StickDisconnected));
+        recv_until(&responses, |r| matches!(r, Response::StickReconnected));
+        requests.send(Request::GetMetrics).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::Metrics(_)));
+        let written: Vec<MessageId> = second.written().iter().map(Message::message_id).collect();
+        assert!(written.contains(&MessageId::AssignChannel));
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_watchdog() {
+        let mock = MockTransport::stick(8);
+        let (requests, request_rx) = crossbeam_channel::unbounded();
+        let (message_tx, responses) = crossbeam_channel::unbounded();
+        let config = RunConfig::new()
+            .read_timeout(Duration::from_millis(10))
+            .reset_delay(Duration::from_millis(10))
+            .watchdog(Duration::from_millis(250));
+        let stick = mock.clone();
+        let handle = thread::spawn(move || run_transport(stick, request_rx, message_tx, config));
+        let config = Config::new().device_type(0x78).frequency(57).period(8070);
+        requests.send(Request::OpenChannel(0, config)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::ChannelOpened(0)));
+
+        // The MockTransport only replies to commands, so the ANT+ stick goes silent once the
+        // channel is open.
+        recv_until(&responses, |r| matches!(r, Response::StickReset));
+        requests.send(Request::GetMetrics).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::Metrics(_)));
+        let written: Vec<MessageId> = mock.written().iter().map(Message::message_id).collect();
+        let resets = written.iter().filter(|&&id| id == MessageId::Reset).count();
+        let assigned = written
+            .iter()
+            .filter(|&&id| id == MessageId::AssignChannel)
+            .count();
+        assert_eq!((resets, assigned), (2, 2));
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_requests_page() {
+        let mock = MockTransport::stick(8);
+        let (requests, request_rx) = crossbeam_channel::unbounded();
+        let (message_tx, responses) = crossbeam_channel::unbounded();
+        let config = RunConfig::new()
+            .read_timeout(Duration::from_millis(10))
+            .reset_delay(Duration::from_millis(10))
+            .page_request_timeout(Duration::from_millis(200));
+        let stick = mock.clone();
+        let handle = thread::spawn(move || run_transport(stick, request_rx, message_tx, config));
+        let config = Config::new().device_type(0x78).frequency(57).period(8070);
+        requests.send(Request::OpenChannel(0, config)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::ChannelOpened(0)));
+
+        let request_page = |page, attempts| Request::RequestPage {
+            channel: 0,
+            page,
+            transmission: Transmission::Broadcast(1),
+            attempts,
+        };
+        requests.send(request_page(0x50, 3)).unwrap();
+        requests.send(Request::GetMetrics).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::Metrics(_)));
+        mock.push(&BroadcastDataMessage::new(0, &[0x50, 0xFF, 0xFF, 1, 1, 0, 1, 0]).to_message());
+        match recv_until(&responses, |r| {
+            matches!(r, Response::PageRequestResult { .. })
+        }) {
+            Response::PageRequestResult { channel, page, ok } => {
+                assert_eq!((channel, page, ok), (0, 0x50, true))
+            }
+            _ => unreachable!(),
+        }
+
+        // The page never arrives, so it is requested once for each attempt.
+        requests.send(request_page(0x51, 2)).unwrap();
+        match recv_until(&responses, |r| {
+            matches!(r, Response::PageRequestResult { .. })
+        }) {
+            Response::PageRequestResult { channel, page, ok } => {
+                assert_eq!((channel, page, ok), (0, 0x51, false))
+            }
+            _ => unreachable!(),
+        }
+        let requested: Vec<u8> = mock
+            .written()
+            .iter()
+            .filter(|mesg| mesg.message_id() == MessageId::AcknowledgeData)
+            .map(|mesg| mesg.data()[7])
+            .collect();
+        assert_eq!(requested, [0x50, 0x51, 0x51]);
+        // Page requests don't send transfer responses.
+        assert!(responses
+            .try_iter()
+            .all(|r| !matches!(r, Response::TransferCompleted(_))));
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_page_request_closed() {
+        let mock = MockTransport::stick(8);
+        let (requests, request_rx) = crossbeam_channel::unbounded();
+        let (message_tx, responses) = crossbeam_channel::unbounded();
+        let config = RunConfig::new()
+            .read_timeout(Duration::from_millis(10))
+            .reset_delay(Duration::from_millis(10))
+            .page_request_timeout(Duration::from_secs(10));
+        let stick = mock.clone();
+        let handle = thread::spawn(move || run_transport(stick, request_rx, message_tx, config));
+        let config = Config::new().device_type(0x78).frequency(57).period(8070);
+        requests.send(Request::OpenChannel(0, config)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::ChannelOpened(0)));
+
+        let request_page = |page| Request::RequestPage {
+            channel: 0,
+            page,
+            transmission: Transmission::Broadcast(1),
+            attempts: 3,
+        };
+        let result = |responses: &Receiver<Response>| match recv_until(responses, |r| {
+            matches!(r, Response::PageRequestResult { .. })
+        }) {
+            Response::PageRequestResult { channel, page, ok } => (channel, page, ok),
+            _ => unreachable!(),
+        };
+        // The page toggle bit of a heart rate monitor isn't part of the page number.
+        requests.send(request_page(0x50)).unwrap();
+        requests.send(Request::GetMetrics).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::Metrics(_)));
+        mock.push(&BroadcastDataMessage::new(0, &[0xD0, 0xFF, 0xFF, 1, 1, 0, 1, 0]).to_message());
+        assert_eq!(result(&responses), (0, 0x50, true));
+
+        requests.send(request_page(0x51)).unwrap();
+        requests.send(Request::CloseChannel(0)).unwrap();
+        assert_eq!(result(&responses), (0, 0x51, false));
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn disconnected_errors() {
+        assert!(disconnected_error(&AntError::UsbDeviceError(
+            rusb::Error::NoDevice
+        )));
+        let io = AntError::UsbDeviceError(rusb::Error::Io);
+        assert_eq!(disconnected_error(&io), cfg!(windows));
+        assert!(!disconnected_error(&AntError::UsbDeviceError(
+            rusb::Error::Pipe
+        )));
+    }
+
+    #[test]
+    fn run_loop_quits_before_ready() {
+        // The ANT+ stick never answers, so the run loop would keep resetting it.
+        let mock = MockTransport::new();
+        let (_requests, request_rx) = crossbeam_channel::unbounded();
+        let (message_tx, responses) = crossbeam_channel::unbounded();
+        let config = test_config()
+            .reset_commands(u8::MAX)
+            .reset_attempts(u8::MAX);
+        let quit = Arc::new(AtomicBool::new(false));
+        let run_quit = quit.clone();
+        let handle = thread::spawn(move || {
+            run_devices(request_rx, message_tx, config, &run_quit, |_| {
+                Ok(mock.clone())
+            })
+        });
+        thread::sleep(Duration::from_millis(50));
+        quit.store(true, Ordering::SeqCst);
+        // The run loop drops its Sender once it exits.
+        assert!(matches!(
+            responses.recv_timeout(RECV_TIMEOUT),
+            Err(crossbeam_channel::RecvTimeoutError::Disconnected)
+        ));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_receiver_dropped() {
+        let mock = MockTransport::stick(8);
+        let (requests, responses, handle) = run_mock(&mock);
+        open_channel(&requests, &responses, 0);
+        drop(responses);
+        mock.push(&BroadcastDataMessage::new(0, &[0x04, 0, 0, 0, 0, 0, 0, 72]).to_message());
+        handle.join().unwrap();
+        assert_eq!(
+            mock.written().last().map(Message::message_id),
+            Some(MessageId::Reset)
+        );
+    }
+
+    #[test]
+    fn run_loop_sends_queued_messages() {
+        let mock = MockTransport::stick(8);
+        let (requests, responses, handle) = run_mock(&mock);
+        let config = Config::new()
+            .device_type(0x78)
+            .timeout(0)
+            .low_priority_timeout(10);
+        requests.send(Request::OpenChannel(0, config)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::ChannelOpened(0)));
+
+        // The low priority search timeout is sent right after the search timeout.
+        let written: Vec<MessageId> = mock.written().iter().map(Message::message_id).collect();
+        let timeout = written
+            .iter()
+            .position(|id| *id == MessageId::ChannelSearchTimeout)
+            .unwrap();
+        assert_eq!(written[timeout + 1], MessageId::LowPrioritySearchTimeout);
+        assert_eq!(written[timeout + 2], MessageId::ChannelPeriod);
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_unknown_channel() {
+        let mock = MockTransport::stick(8);
+        let (requests, responses, handle) = run_mock(&mock);
+        open_channel(&requests, &responses, 0);
+
+        // Channel closed event for a channel the ANT+ stick doesn't support.
+        let data = [20, MessageId::Event.into(), 0x07];
+        mock.push(&Message::with_payload(
+            MessageId::ResponseEvent.into(),
+            &data,
+        ));
+        requests.send(Request::GetMetrics).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::Metrics(_)));
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_retries_transfer() {
+        // Only the third attempt completes.
+        let mock = transfer_stick(|written| match written {
+            3 => Some(EVENT_TRANSFER_TX_COMPLETED),
+            _ => Some(EVENT_TRANSFER_TX_FAILED),
+        });
+        let (requests, responses, handle) = run_mock(&mock);
+        open_channel(&requests, &responses, 0);
+
+        let mesg = AcknowledgeDataMessage::new(0, &[0x01; 8]);
+        requests
+            .send(Request::SendAcknowledged(mesg.clone(), 2))
+            .unwrap();
+        recv_until(&responses, |r| matches!(r, Response::TransferCompleted(0)));
+        assert_eq!(acknowledged(&mock).len(), 3);
+
+        // The retries are used up before the transfer completes.
+        requests.send(Request::SendAcknowledged(mesg, 1)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::TransferFailed(0)));
+        assert_eq!(acknowledged(&mock).len(), 5);
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_close_fails_transfer() {
+        // The transfer never finishes.
+        let mock = transfer_stick(|_| None);
+        let (requests, responses, handle) = run_mock(&mock);
+        open_channel(&requests, &responses, 0);
+
+        let mesg = AcknowledgeDataMessage::new(0, &[0x01; 8]);
+        requests.send(Request::SendAcknowledged(mesg, 3)).unwrap();
+        requests.send(Request::CloseChannel(0)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::TransferFailed(0)));
+        recv_until(&responses, |r| matches!(r, Response::ChannelClosed(0)));
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_transfer_token() {
+        // Only the second transfer completes.
+        let mock = transfer_stick(|written| match written {
+            2 => Some(EVENT_TRANSFER_TX_COMPLETED),
+            _ => Some(EVENT_TRANSFER_TX_FAILED),
+        });
+        let (requests, responses, handle) = run_mock(&mock);
+        open_channel(&requests, &responses, 0);
+
+        let send = |token| Request::SendAcknowledgedWithToken {
+            mesg: AcknowledgeDataMessage::new(0, &[0x01; 8]),
+            retries: 0,
+            token,
+        };
+        requests.send(send(7)).unwrap();
+        requests.send(send(8)).unwrap();
+        let mut results = Vec::new();
+        while results.len() < 2 {
+            match responses.recv_timeout(RECV_TIMEOUT).unwrap() {
+                Response::TransferResult { token, ok } => results.push((token, ok)),
+                Response::TransferCompleted(_) | Response::TransferFailed(_) => {
+                    panic!("Expected TransferResult")
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(results, [(7, false), (8, true)]);
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_queues_transfers() {
+        // Transfers only finish with the events pushed below.
+        let mock = transfer_stick(|_| None);
+        let (requests, responses, handle) = run_mock(&mock);
+        open_channel(&requests, &responses, 0);
+
+        for data in 1..=3 {
+            let mesg = AcknowledgeDataMessage::new(0, &[data; 8]);
+            requests.send(Request::SendAcknowledged(mesg, 0)).unwrap();
+        }
+        requests.send(Request::GetMetrics).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::Metrics(_)));
+        assert_eq!(acknowledged(&mock).len(), 1);
+
+        // The next transfer is sent once the one in progress completes.
+        let completed = [0, MessageId::Event.into(), EVENT_TRANSFER_TX_COMPLETED];
+        mock.push(&Message::with_payload(
+            MessageId::ResponseEvent.into(),
+            &completed,
+        ));
+        recv_until(&responses, |r| matches!(r, Response::TransferCompleted(0)));
+        requests.send(Request::GetMetrics).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::Metrics(_)));
+        let sent: Vec<u8> = acknowledged(&mock)
+            .iter()
+            .map(|mesg| mesg.data()[1])
+            .collect();
+        assert_eq!(sent, [1, 2]);
+
+        // Transfers in progress and queued are failed when the channel is closed.
+        requests.send(Request::CloseChannel(0)).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::TransferFailed(0)));
+        recv_until(&responses, |r| matches!(r, Response::TransferFailed(0)));
+        recv_until(&responses, |r| matches!(r, Response::ChannelClosed(0)));
+        assert_eq!(acknowledged(&mock).len(), 2);
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_flushes_event_buffer() {
+        let mock = MockTransport::stick(8);
+        let (requests, responses, handle) = run_mock(&mock);
+        requests.send(Request::FlushEventBuffer).unwrap();
+        recv_until(&responses, |r| {
+            matches!(r, Response::Error(AntError::EventBufferNotConfigured))
+        });
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+
+        let mock = MockTransport::stick(8);
+        let config = test_config().event_buffer(EventBufferConfig::AllEvents, 256, 10);
+        let (requests, responses, handle) = run_mock_with_config(&mock, config);
+        open_channel(&requests, &responses, 0);
+        requests.send(Request::FlushEventBuffer).unwrap();
+        requests.send(Request::GetMetrics).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::Metrics(_)));
+        // Set once the ANT+ stick is ready and again to flush.
+        let expected = message::set_event_buffer_config(EventBufferConfig::AllEvents, 256, 10);
+        let written = mock.written();
+        let configured: Vec<&Message> = written
+            .iter()
+            .filter(|mesg| mesg.message_id() == MessageId::EventBufferingConfig)
+            .collect();
+        assert_eq!(configured, [&expected, &expected]);
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_tap() {
+        let mock = MockTransport::stick(8);
+        let (tap_tx, tap) = crossbeam_channel::unbounded();
+        let config = test_config().tap(tap_tx);
+        let (requests, responses, handle) = run_mock_with_config(&mock, config);
+        open_channel(&requests, &responses, 0);
+        let data = BroadcastDataMessage::new(0, &[0x04, 0, 0, 0, 0, 0, 0, 72]).to_message();
+        mock.push(&data);
+        recv_until(&responses, |r| matches!(r, Response::BroadcastData(_)));
+        requests.send(Request::GetMetrics).unwrap();
+        recv_until(&responses, |r| matches!(r, Response::Metrics(_)));
+
+        // Every message written is tapped in order, along with the data read.
+        let frames: Vec<RawFrame> = tap.try_iter().collect();
+        let written: Vec<RawFrame> = mock
+            .written()
+            .iter()
+            .map(|mesg| RawFrame::Tx(mesg.encode()))
+            .collect();
+        let tx: Vec<RawFrame> = frames
+            .iter()
+            .filter(|frame| matches!(frame, RawFrame::Tx(_)))
+            .cloned()
+            .collect();
+        assert_eq!(tx, written);
+        assert!(frames.contains(&RawFrame::Rx(data.encode())));
+
+        // The run loop keeps running once the tap is dropped.
+        drop(tap);
+        mock.push(&data);
+        recv_until(&responses, |r| matches!(r, Response::BroadcastData(_)));
+
+        requests.send(Request::Quit).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn run_loop_reset_fails() {
+        let mock = MockTransport::new();
+        let (_requests, responses, handle) = run_mock(&mock);
+        recv_until(&responses, |r| {
+            matches!(r, Response::Error(AntError::Reset))
+        });
+        handle.join().unwrap();
+        assert_eq!(mock.written().len(), RESET_COMMANDS as usize);
+    }
 }
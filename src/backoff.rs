@@ -0,0 +1,89 @@
+/// The backoff module provides Backoff, which spaces out attempts that keep failing, such as
+/// looking for an ANT+ USB device that is not plugged in or reopening a channel that keeps
+/// closing.
+use std::time::Duration;
+
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// Backoff controls how long to wait between attempts, such as how often the run loop looks for
+/// the ANT+ USB device while it is not plugged in. The wait starts at interval and doubles after
+/// each attempt up to max_interval. If max_attempts is set, no more attempts are made once they
+/// are used up, so the run loop gives up and sends a NoDevice error.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Backoff {
+    interval: Duration,
+    max_interval: Duration,
+    max_attempts: Option<u32>,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self {
+            interval: INTERVAL,
+            max_interval: INTERVAL,
+            max_attempts: None,
+        }
+    }
+
+    /// Sets the wait after the first attempt.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the longest wait between attempts.
+    pub fn max_interval(mut self, max_interval: Duration) -> Self {
+        self.max_interval = max_interval;
+        self
+    }
+
+    /// Gives up after the number of attempts.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    // Wait after the attempt, or None if there are no attempts left. Attempts start at 1.
+    pub(crate) fn delay(&self, attempt: u32) -> Option<Duration> {
+        match self.max_attempts {
+            Some(max) if attempt >= max => None,
+            _ => {
+                let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+                let delay = self
+                    .interval
+                    .checked_mul(factor)
+                    .unwrap_or(self.max_interval);
+                Some(delay.min(self.max_interval.max(self.interval)))
+            }
+        }
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn backoff_delay() {
+        let backoff = Backoff::new();
+        assert_eq!(backoff.delay(1), Some(Duration::from_secs(1)));
+        assert_eq!(backoff.delay(100), Some(Duration::from_secs(1)));
+
+        let backoff = Backoff::new()
+            .interval(Duration::from_millis(100))
+            .max_interval(Duration::from_millis(500))
+            .max_attempts(5);
+        assert_eq!(backoff.delay(1), Some(Duration::from_millis(100)));
+        assert_eq!(backoff.delay(2), Some(Duration::from_millis(200)));
+        assert_eq!(backoff.delay(3), Some(Duration::from_millis(400)));
+        assert_eq!(backoff.delay(4), Some(Duration::from_millis(500)));
+        assert_eq!(backoff.delay(5), None);
+    }
+}
@@ -3,9 +3,21 @@
 /// gets mapped to a single device. Even if multiple devices are sending data, the first device
 /// learned by the channel will have its data routed through the configured channel. If multiple
 /// devices of the same type are to be used, multiple channels need to be opened.
-use crate::message::{self, ChannelResponseMessage, Message};
+use std::time::Instant;
+
+use crate::backoff::Backoff;
+use crate::device::common;
+use crate::message::{self, ChannelResponseMessage, EncryptionMode, Message, MessageId};
+
+/// Bidirectional slave channel. The channel receives data from a master.
+pub const CHANNEL_TYPE_SLAVE: u8 = 0x00;
+/// Bidirectional master channel. The channel transmits data to any slaves.
+pub const CHANNEL_TYPE_MASTER: u8 = 0x10;
+/// Low priority search timeout that keeps the channel searching until a device is found.
+pub const SEARCH_TIMEOUT_INFINITE: u8 = 0xFF;
 
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
     device_id: u16,
     device_type: u8,
@@ -13,13 +25,20 @@ pub struct Config {
     frequency: u8,
     period: u16,
     timeout: u8,
+    low_priority_timeout: Option<u8>,
     transmission_type: u8,
+    scan: bool,
+    frequency_agility: Option<[u8; 3]>,
+    encryption: Option<u8>,
+    sdu_mask: Option<u8>,
+    network: Option<u8>,
+    reopen: ReopenPolicy,
 }
 
 impl Config {
     pub fn new() -> Self {
         Config {
-            channel_type: 0x00,
+            channel_type: CHANNEL_TYPE_SLAVE,
             timeout: 30,
             ..Default::default()
         }
@@ -40,11 +59,65 @@ impl Config {
         self
     }
 
+    /// Configures the channel as a master channel so the ANT+ USB device transmits data as the
+    /// device instead of receiving data from a device. Master channels require a non-zero
+    /// device ID and transmission type.
+    pub fn master(mut self) -> Self {
+        self.channel_type = CHANNEL_TYPE_MASTER;
+        self
+    }
+
+    /// Returns true if the channel type is one of the master channel types.
+    pub fn is_master(&self) -> bool {
+        self.channel_type & CHANNEL_TYPE_MASTER == CHANNEL_TYPE_MASTER
+    }
+
+    /// Opens the channel in continuous scan mode so the ANT+ USB device receives data from
+    /// every device on the frequency instead of a single device. Scan mode uses channel 0 and
+    /// requires all other channels to be closed.
+    pub fn scan(mut self) -> Self {
+        self.scan = true;
+        self
+    }
+
+    pub fn is_scan(&self) -> bool {
+        self.scan
+    }
+
+    /// Assigns the channel to a network other than the run loop's default network. The network
+    /// key needs to be set in the RunConfig.
+    pub fn network(mut self, network: u8) -> Self {
+        self.network = Some(network);
+        self
+    }
+
     pub fn frequency(mut self, frequency: u8) -> Self {
         self.frequency = frequency;
         self
     }
 
+    /// Enables frequency agility so the channel hops between the three radio frequencies when
+    /// the current frequency has interference. The frequencies are in MHz offset from 2400MHz.
+    pub fn frequency_agility(mut self, frequencies: [u8; 3]) -> Self {
+        self.frequency_agility = Some(frequencies);
+        self
+    }
+
+    /// Enables AES-128 encryption on the channel using the key stored in the volatile key index
+    /// of the ANT+ USB device. The key needs to be set before the channel is opened.
+    pub fn encryption(mut self, key_index: u8) -> Self {
+        self.encryption = Some(key_index);
+        self
+    }
+
+    /// Only forwards broadcast data when the bytes selected by the SDU mask stored in the mask
+    /// index of the ANT+ USB device change. The mask needs to be set before the channel is
+    /// opened.
+    pub fn selective_data_update(mut self, mask_index: u8) -> Self {
+        self.sdu_mask = Some(mask_index);
+        self
+    }
+
     pub fn period(mut self, period: u16) -> Self {
         self.period = period;
         self
@@ -55,21 +128,58 @@ impl Config {
         self
     }
 
+    /// Sets the low priority search timeout in 2.5s increments. Low priority search does not
+    /// interrupt other open channels, so pairing the channel with a high priority timeout of 0
+    /// lets it search indefinitely with SEARCH_TIMEOUT_INFINITE without blocking other channels.
+    pub fn low_priority_timeout(mut self, timeout: u8) -> Self {
+        self.low_priority_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets what the run loop does when the channel closes without being requested to, such as
+    /// after a search timeout. Defaults to ReopenPolicy::Always.
+    pub fn reopen_policy(mut self, policy: ReopenPolicy) -> Self {
+        self.reopen = policy;
+        self
+    }
+
     pub fn transmission_type(mut self, transmission_type: u8) -> Self {
         self.transmission_type = transmission_type;
         self
     }
 }
 
+/// ReopenPolicy decides if a channel that closed without being requested to is reopened.
+/// Never lets the channel close, Always reopens it right away, and Backoff waits before
+/// reopening it, giving up once the channel has closed the backoff's max attempts times without
+/// receiving data.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReopenPolicy {
+    Never,
+    #[default]
+    Always,
+    Backoff(Backoff),
+}
+
+/// State of a channel as seen by the library. The Set states are steps configuring the channel
+/// before it is opened. Open and Reopen are waiting on the ANT+ USB device to open the channel,
+/// and Ready is an open channel that is searching for or tracking a device.
 #[derive(Debug, PartialEq, Clone)]
-enum State {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum State {
     Assign,
     Unassign,
     SetDeviceId,
     SetTimeout,
+    SetLowPriorityTimeout,
     SetFrequency,
+    SetFrequencyAgility,
+    SetEncryption,
+    SetSelectiveDataUpdate,
     SetPeriod,
     Open,
+    Reopen,
     Closed,
     Ready,
 }
@@ -77,10 +187,21 @@ enum State {
 /// Channel maintains the channel number, state of the channel, and the device
 /// for the channel configuration parameters.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Channel {
     state: State,
     number: u8,
     device: Config,
+    // Set once the channel ID has been requested for a channel paired with a wildcard device ID.
+    channel_id_requested: bool,
+    // Times the channel has closed without receiving data, and when to reopen it.
+    closed_count: u32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    reopen_at: Option<Instant>,
+    // Messages to send after the message returned by route(), for configuration steps that
+    // need more than one message sent at a time.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    queued: Vec<Message>,
 }
 
 impl Channel {
@@ -89,6 +210,10 @@ impl Channel {
             state: State::Assign,
             number,
             device,
+            channel_id_requested: false,
+            closed_count: 0,
+            reopen_at: None,
+            queued: Vec::new(),
         }
     }
 
@@ -96,16 +221,38 @@ impl Channel {
         self.number
     }
 
+    pub fn is_master(&self) -> bool {
+        self.device.is_master()
+    }
+
+    pub fn is_scan(&self) -> bool {
+        self.device.is_scan()
+    }
+
+    pub fn network(&self) -> Option<u8> {
+        self.device.network
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Configuration the channel was opened with.
+    pub fn config(&self) -> &Config {
+        &self.device
+    }
+
     // TODO: Happy path for now, we only route messages that are
     // ReponseNoError. We'll just check to verify the message received
     // is what we expect in the current state, then transition the state or
     // log the error.
     /// Routes messages for the channel when opening the channel for the specified
-    /// device type.
+    /// device type. Any messages queued while routing are sent after the returned message and
+    /// are taken with take_queued().
     pub fn route(&mut self, mesg: &ChannelResponseMessage) -> Option<Message> {
         match self.state {
             State::Assign => {
-                if mesg.message_id() == message::MESG_ASSIGN_CHANNEL_ID {
+                if mesg.message_id() == MessageId::AssignChannel {
                     log::debug!(
                         "Setting channel state to SetDeviceId. Sending set_channel_id message"
                     );
@@ -117,15 +264,39 @@ impl Channel {
                 None
             }
             State::SetDeviceId => {
-                if mesg.message_id() == message::MESG_CHANNEL_ID_ID {
+                // Master channels do not search for a device, so skip setting the search
+                // timeout.
+                if mesg.message_id() == MessageId::ChannelId && self.is_master() {
+                    log::debug!("Setting channel state to SetPeriod. Sending set_period message");
+                    self.state = State::SetPeriod;
+                    return Some(self.set_period());
+                }
+                if mesg.message_id() == MessageId::ChannelId {
                     log::debug!("Setting channel state to SetTimeout. Sending set_timeout message");
                     self.state = State::SetTimeout;
+                    // The low priority search timeout is sent along with the search timeout.
+                    if let Some(mesg) = self.set_lp_search_timeout() {
+                        self.queue(mesg);
+                    }
                     return Some(self.set_hp_search_timeout());
                 }
                 None
             }
             State::SetTimeout => {
-                if mesg.message_id() == message::MESG_CHANNEL_SEARCH_TIMEOUT_ID {
+                if mesg.message_id() == MessageId::ChannelSearchTimeout {
+                    if self.device.low_priority_timeout.is_some() {
+                        log::debug!("Setting channel state to SetLowPriorityTimeout");
+                        self.state = State::SetLowPriorityTimeout;
+                        return None;
+                    }
+                    log::debug!("Setting channel state to SetPeriod. Sending set_period message");
+                    self.state = State::SetPeriod;
+                    return Some(self.set_period());
+                }
+                None
+            }
+            State::SetLowPriorityTimeout => {
+                if mesg.message_id() == MessageId::LowPrioritySearchTimeout {
                     log::debug!("Setting channel state to SetPeriod. Sending set_period message");
                     self.state = State::SetPeriod;
                     return Some(self.set_period());
@@ -133,7 +304,7 @@ impl Channel {
                 None
             }
             State::SetPeriod => {
-                if mesg.message_id() == message::MESG_CHANNEL_MESG_PERIOD_ID {
+                if mesg.message_id() == MessageId::ChannelPeriod {
                     log::debug!(
                         "Setting channel state to SetFrequency. Sending set_frequency message"
                     );
@@ -143,29 +314,174 @@ impl Channel {
                 None
             }
             State::SetFrequency => {
-                if mesg.message_id() == message::MESG_CHANNEL_RADIO_FREQ_ID {
+                if mesg.message_id() == MessageId::ChannelRadioFrequency
+                    && self.device.frequency_agility.is_some()
+                {
+                    log::debug!("Setting channel state to SetFrequencyAgility");
+                    self.state = State::SetFrequencyAgility;
+                    return self.set_frequency_agility();
+                }
+                if mesg.message_id() == MessageId::ChannelRadioFrequency {
+                    return Some(self.encrypt_or_open());
+                }
+                None
+            }
+            State::SetFrequencyAgility => {
+                if mesg.message_id() == MessageId::AutoFrequencyConfig {
+                    return Some(self.encrypt_or_open());
+                }
+                None
+            }
+            State::SetEncryption => {
+                if mesg.message_id() == MessageId::EnableEncryption {
+                    return Some(self.sdu_or_open());
+                }
+                None
+            }
+            State::SetSelectiveDataUpdate => {
+                if mesg.message_id() == MessageId::SduConfig {
                     log::debug!("Setting channel state to Open. Sending open_channel message");
                     self.state = State::Open;
                     return Some(self.open());
                 }
                 None
             }
-            State::Open => {
-                if mesg.message_id() == message::MESG_OPEN_CHANNEL_ID {
+            State::Open | State::Reopen => {
+                if mesg.message_id() == MessageId::OpenChannel
+                    || mesg.message_id() == MessageId::OpenRxScan
+                {
                     log::info!("Channel {:?} is open", self.number);
+                    self.state = State::Ready;
                     return None;
                 }
                 None
             }
             _ => {
                 log::debug!("Unsupported channel message in current state: {:x?}", mesg);
-                unimplemented!()
+                None
+            }
+        }
+    }
+
+    // Encryption is enabled as the last step before opening the channel if configured.
+    fn encrypt_or_open(&mut self) -> Message {
+        if let Some(mesg) = self.enable_encryption() {
+            log::debug!("Setting channel state to SetEncryption");
+            self.state = State::SetEncryption;
+            return mesg;
+        }
+        self.sdu_or_open()
+    }
+
+    // Selective data updates are configured after encryption if configured.
+    fn sdu_or_open(&mut self) -> Message {
+        if let Some(mesg) = self.set_selective_data_update() {
+            log::debug!("Setting channel state to SetSelectiveDataUpdate");
+            self.state = State::SetSelectiveDataUpdate;
+            return mesg;
+        }
+        log::debug!("Setting channel state to Open. Sending open_channel message");
+        self.state = State::Open;
+        self.open()
+    }
+
+    /// Returns true once the ANT+ USB device has opened the channel.
+    pub fn is_open(&self) -> bool {
+        self.state == State::Ready
+    }
+
+    /// Returns true while waiting on the ANT+ USB device to reopen a channel that closed without
+    /// being requested to.
+    pub fn is_reopening(&self) -> bool {
+        self.state == State::Reopen
+    }
+
+    /// Reopens a channel that closed without being requested to.
+    pub fn reopen(&mut self) -> Message {
+        self.state = State::Reopen;
+        self.open()
+    }
+
+    /// Schedules the channel to be reopened after it closed without being requested to.
+    /// Returns false if the reopen policy gives up on the channel.
+    pub fn closed(&mut self, now: Instant) -> bool {
+        self.state = State::Closed;
+        self.closed_count += 1;
+        let delay = match self.device.reopen {
+            ReopenPolicy::Never => None,
+            ReopenPolicy::Always => Some(Default::default()),
+            ReopenPolicy::Backoff(backoff) => backoff.delay(self.closed_count),
+        };
+        self.reopen_at = delay.map(|delay| now + delay);
+        self.reopen_at.is_some()
+    }
+
+    /// Reopens the channel once the wait set when it closed has passed.
+    pub fn poll_reopen(&mut self, now: Instant) -> Option<Message> {
+        match self.reopen_at {
+            Some(at) if at <= now => {
+                self.reopen_at = None;
+                Some(self.reopen())
             }
+            _ => None,
         }
     }
 
+    /// Data was received on the channel, so the reopen policy starts over the next time the
+    /// channel closes.
+    pub fn received(&mut self) {
+        self.closed_count = 0;
+    }
+
+    /// Resets the channel so it can be assigned again after the ANT+ USB device is reset.
+    pub fn reset(&mut self) {
+        self.state = State::Assign;
+        self.channel_id_requested = false;
+        self.reopen_at = None;
+        self.queued.clear();
+    }
+
+    // Queues a message to be sent after the message returned by route().
+    fn queue(&mut self, mesg: Message) {
+        self.queued.push(mesg);
+    }
+
+    /// Takes the messages queued while routing a message, in the order they are to be sent
+    /// after the message returned by route().
+    pub fn take_queued(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.queued)
+    }
+
+    /// Page number of data received on the channel, taking the page toggle bit of the
+    /// configured device type into account.
+    pub fn page_number(&self, data: &[u8]) -> u8 {
+        common::page_number(self.device.device_type, data)
+    }
+
+    /// Requests the channel ID the first time data is received on a slave channel configured
+    /// with a wildcard device ID of 0 so the device the channel paired with can be learned.
+    pub fn request_channel_id(&mut self) -> Option<Message> {
+        if self.channel_id_requested
+            || self.device.device_id != 0
+            || self.is_master()
+            || self.is_scan()
+        {
+            return None;
+        }
+        self.channel_id_requested = true;
+        Some(message::get_channel_id(self.number))
+    }
+
     /// Assigns a channel to the specified network.
     pub fn assign(&self, network: u8) -> Message {
+        if self.device.frequency_agility.is_some() {
+            return message::assign_channel_extended(
+                self.number,
+                self.device.channel_type,
+                network,
+                message::EXT_ASSIGN_FREQUENCY_AGILITY,
+            );
+        }
         message::assign_channel(self.number, self.device.channel_type, network)
     }
 
@@ -184,6 +500,13 @@ impl Channel {
         message::set_hp_search_timeout(self.number, self.device.timeout)
     }
 
+    /// Sets the low priority search timeout if configured.
+    pub fn set_lp_search_timeout(&self) -> Option<Message> {
+        self.device
+            .low_priority_timeout
+            .map(|timeout| message::set_lp_search_timeout(self.number, timeout))
+    }
+
     /// Sets the period for the channel for how often a message is expected.
     pub fn set_period(&self) -> Message {
         message::set_channel_period(self.number, self.device.period)
@@ -194,15 +517,49 @@ impl Channel {
         message::set_channel_frequency(self.number, self.device.frequency)
     }
 
-    /// Open the channel to start receiving broadcast data from the device.
+    /// Sets the frequencies used for frequency agility if configured.
+    pub fn set_frequency_agility(&self) -> Option<Message> {
+        self.device
+            .frequency_agility
+            .map(|frequencies| message::set_frequency_agility(self.number, frequencies))
+    }
+
+    /// Enables encryption on the channel if configured. Master channels send every encrypted
+    /// message to slaves.
+    pub fn enable_encryption(&self) -> Option<Message> {
+        self.device.encryption.map(|key_index| {
+            message::enable_channel_encryption(self.number, EncryptionMode::Enabled, key_index, 1)
+        })
+    }
+
+    /// Configures selective data updates on the channel if configured.
+    pub fn set_selective_data_update(&self) -> Option<Message> {
+        self.device
+            .sdu_mask
+            .map(|mask_index| message::config_selective_data_update(self.number, mask_index))
+    }
+
+    /// Open the channel to start receiving broadcast data from the device, or from all devices
+    /// if the channel is configured for scan mode.
     pub fn open(&self) -> Message {
+        if self.device.scan {
+            return message::open_rx_scan_mode();
+        }
         message::open_channel(self.number)
     }
+
+    /// Broadcast data from a master channel. The ANT+ USB device will continue to transmit
+    /// the data each channel period until new data is sent.
+    pub fn broadcast(&self, data: &[u8; 8]) -> Message {
+        message::BroadcastDataMessage::new(self.number, data).to_message()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::convert::TryFrom;
+    use std::time::Duration;
 
     #[test]
     fn new() {
@@ -212,4 +569,196 @@ mod test {
         assert_eq!(channel.number, 0);
         assert_eq!(channel.device, Config::new());
     }
+
+    #[test]
+    fn master() {
+        let config = Config::new().master();
+        assert!(config.is_master());
+        assert!(!Config::new().is_master());
+        assert!(Config::new().channel_type(0x50).is_master());
+    }
+
+    #[test]
+    fn scan_opens_rx_scan_mode() {
+        let mut channel = Channel::new(0, Config::new().scan());
+        assert!(channel.open() == message::open_rx_scan_mode());
+        channel.state = State::Open;
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_OPEN_RX_SCAN_ID, 0][..]).unwrap();
+        assert!(channel.route(&mesg).is_none());
+        assert!(Channel::new(0, Config::new()).open() == message::open_channel(0));
+    }
+
+    #[test]
+    fn low_priority_search_timeout() {
+        let config = Config::new()
+            .timeout(0)
+            .low_priority_timeout(SEARCH_TIMEOUT_INFINITE);
+        let mut channel = Channel::new(0, config);
+        channel.state = State::SetDeviceId;
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_CHANNEL_ID_ID, 0][..]).unwrap();
+        assert!(channel.route(&mesg) == Some(message::set_hp_search_timeout(0, 0)));
+        assert!(channel.take_queued() == vec![message::set_lp_search_timeout(0, 0xFF)]);
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_CHANNEL_SEARCH_TIMEOUT_ID, 0][..])
+                .unwrap();
+        assert!(channel.route(&mesg).is_none());
+        assert_eq!(channel.state, State::SetLowPriorityTimeout);
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_SET_LP_SEARCH_TIMEOUT_ID, 0][..])
+                .unwrap();
+        assert!(channel.route(&mesg) == Some(channel.set_period()));
+
+        // Without a low priority timeout the channel goes straight to setting the period.
+        let mut channel = Channel::new(0, Config::new());
+        channel.state = State::SetTimeout;
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_CHANNEL_SEARCH_TIMEOUT_ID, 0][..])
+                .unwrap();
+        assert!(channel.route(&mesg) == Some(channel.set_period()));
+    }
+
+    #[test]
+    fn frequency_agility() {
+        let config = Config::new().frequency(3).frequency_agility([3, 39, 75]);
+        let mut channel = Channel::new(0, config);
+        assert!(
+            channel.assign(1)
+                == message::assign_channel_extended(0, 0, 1, message::EXT_ASSIGN_FREQUENCY_AGILITY)
+        );
+        channel.state = State::SetFrequency;
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_CHANNEL_RADIO_FREQ_ID, 0][..])
+                .unwrap();
+        assert!(channel.route(&mesg) == Some(message::set_frequency_agility(0, [3, 39, 75])));
+        let mesg = ChannelResponseMessage::try_from(&[0, message::MESG_AUTO_FREQ_CONFIG_ID, 0][..])
+            .unwrap();
+        assert!(channel.route(&mesg) == Some(channel.open()));
+    }
+
+    #[test]
+    fn request_channel_id() {
+        let mut channel = Channel::new(1, Config::new());
+        assert!(channel.request_channel_id() == Some(message::get_channel_id(1)));
+        assert!(channel.request_channel_id().is_none());
+        assert!(Channel::new(1, Config::new().device_id(100))
+            .request_channel_id()
+            .is_none());
+    }
+
+    #[test]
+    fn encryption() {
+        let mut channel = Channel::new(0, Config::new().encryption(0));
+        channel.state = State::SetFrequency;
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_CHANNEL_RADIO_FREQ_ID, 0][..])
+                .unwrap();
+        assert!(
+            channel.route(&mesg)
+                == Some(message::enable_channel_encryption(
+                    0,
+                    EncryptionMode::Enabled,
+                    0,
+                    1
+                ))
+        );
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_ENABLE_ENCRYPTION_ID, 0][..])
+                .unwrap();
+        assert!(channel.route(&mesg) == Some(channel.open()));
+        assert_eq!(channel.state, State::Open);
+    }
+
+    #[test]
+    fn selective_data_update() {
+        let mut channel = Channel::new(0, Config::new().encryption(0).selective_data_update(1));
+        channel.state = State::SetEncryption;
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_ENABLE_ENCRYPTION_ID, 0][..])
+                .unwrap();
+        assert!(channel.route(&mesg) == Some(message::config_selective_data_update(0, 1)));
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_SDU_CONFIG_ID, 0][..]).unwrap();
+        assert!(channel.route(&mesg) == Some(channel.open()));
+    }
+
+    #[test]
+    fn reset() {
+        let mut channel = Channel::new(0, Config::new());
+        channel.state = State::Open;
+        assert!(channel.request_channel_id().is_some());
+        channel.reset();
+        assert_eq!(channel.state, State::Assign);
+        assert!(channel.request_channel_id().is_some());
+    }
+
+    #[test]
+    fn open_and_reopen() {
+        let mut channel = Channel::new(0, Config::new());
+        channel.state = State::Open;
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_OPEN_CHANNEL_ID, 0][..]).unwrap();
+        assert!(channel.route(&mesg).is_none());
+        assert!(channel.is_open());
+        assert!(channel.reopen() == message::open_channel(0));
+        assert!(channel.is_reopening());
+        assert!(!channel.is_open());
+        assert!(channel.route(&mesg).is_none());
+        assert!(channel.is_open());
+        assert_eq!(channel.state(), &State::Ready);
+    }
+
+    #[test]
+    fn reopen_policy() {
+        let now = Instant::now();
+        let mut channel = Channel::new(0, Config::new());
+        assert!(channel.closed(now));
+        assert!(channel.poll_reopen(now) == Some(message::open_channel(0)));
+        assert!(channel.is_reopening());
+
+        let mut channel = Channel::new(0, Config::new().reopen_policy(ReopenPolicy::Never));
+        assert!(!channel.closed(now));
+        assert!(channel.poll_reopen(now).is_none());
+
+        let backoff = Backoff::new()
+            .interval(Duration::from_secs(1))
+            .max_interval(Duration::from_secs(4))
+            .max_attempts(2);
+        let mut channel = Channel::new(
+            0,
+            Config::new().reopen_policy(ReopenPolicy::Backoff(backoff)),
+        );
+        assert!(channel.closed(now));
+        assert!(channel.poll_reopen(now).is_none());
+        assert!(channel.poll_reopen(now + Duration::from_secs(1)).is_some());
+        assert!(!channel.closed(now));
+        channel.received();
+        assert!(channel.closed(now));
+    }
+
+    #[test]
+    fn master_route_skips_search_timeout() {
+        let mut channel = Channel::new(0, Config::new().device_id(1).master());
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_ASSIGN_CHANNEL_ID, 0][..]).unwrap();
+        assert!(channel.route(&mesg) == Some(channel.set_channel_id()));
+        let mesg =
+            ChannelResponseMessage::try_from(&[0, message::MESG_CHANNEL_ID_ID, 0][..]).unwrap();
+        assert!(channel.route(&mesg) == Some(channel.set_period()));
+        assert_eq!(channel.state, State::SetPeriod);
+    }
+
+    #[test]
+    fn queued_messages() {
+        let mut channel = Channel::new(0, Config::new());
+        assert!(channel.take_queued().is_empty());
+        channel.queue(channel.set_period());
+        channel.queue(channel.open());
+        assert!(channel.take_queued() == vec![channel.set_period(), channel.open()]);
+        assert!(channel.take_queued().is_empty());
+        channel.queue(channel.open());
+        channel.reset();
+        assert!(channel.take_queued().is_empty());
+    }
 }
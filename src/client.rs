@@ -0,0 +1,258 @@
+/// The client module provides AntClient, a handle to the run loop that wraps the request and
+/// response channels so applications can open and close channels without tracking channel
+/// numbers themselves.
+use std::collections::BTreeSet;
+
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+
+use crate::{
+    ant::{Request, Response},
+    channel::Config,
+    error::AntError,
+    Result,
+};
+
+#[cfg(feature = "futures")]
+use crate::stream::{ResponseStream, Router};
+
+/// Handle to a channel opened through AntClient. Responses for the channel carry the channel
+/// number returned by number().
+#[derive(Debug, PartialEq, Eq)]
+pub struct ChannelHandle(u8);
+
+impl ChannelHandle {
+    pub fn number(&self) -> u8 {
+        self.0
+    }
+}
+
+/// AntClient wraps the request Sender and response Receiver passed to ant::run.
+///
+/// let (request_tx, request_rx) = libant::unbounded();
+/// let (message_tx, message_rx) = libant::unbounded();
+/// std::thread::spawn(move || libant::ant::run(request_rx, message_tx, RunConfig::new()));
+///
+/// let mut client = AntClient::new(request_tx, message_rx);
+/// let hrm = client.open_channel(HeartRateMonitor::channel_config())?;
+pub struct AntClient {
+    requests: Sender<Request>,
+    responses: Receiver<Response>,
+    channels: BTreeSet<u8>,
+    #[cfg(feature = "futures")]
+    router: Option<Router>,
+}
+
+impl AntClient {
+    pub fn new(requests: Sender<Request>, responses: Receiver<Response>) -> Self {
+        Self {
+            requests,
+            responses,
+            channels: BTreeSet::new(),
+            #[cfg(feature = "futures")]
+            router: None,
+        }
+    }
+
+    /// Opens a channel on the lowest channel number not already opened by the client. If the
+    /// ANT+ USB device does not support the channel number, an InvalidChannel error is received
+    /// as a response.
+    pub fn open_channel(&mut self, config: Config) -> Result<ChannelHandle> {
+        let number = self.next_channel()?;
+        self.send(Request::OpenChannel(number, config))?;
+        self.channels.insert(number);
+        Ok(ChannelHandle(number))
+    }
+
+    /// Opens a channel like open_channel, but responses for the channel are sent to the given
+    /// Sender instead of being received through the client.
+    pub fn open_channel_with_sender(
+        &mut self,
+        config: Config,
+        responses: Sender<Response>,
+    ) -> Result<ChannelHandle> {
+        let number = self.next_channel()?;
+        self.send(Request::OpenChannelWithSender(number, config, responses))?;
+        self.channels.insert(number);
+        Ok(ChannelHandle(number))
+    }
+
+    /// Closes a channel opened by the client.
+    pub fn close(&mut self, handle: ChannelHandle) -> Result<()> {
+        self.send(Request::CloseChannel(handle.0))?;
+        self.channels.remove(&handle.0);
+        Ok(())
+    }
+
+    /// Sends a request to the run loop.
+    pub fn send(&self, request: Request) -> Result<()> {
+        self.requests
+            .send(request)
+            .map_err(|_| AntError::RunLoopDisconnected)
+    }
+
+    /// Blocks until a response is received from the run loop.
+    pub fn recv(&self) -> Result<Response> {
+        self.responses
+            .recv()
+            .map_err(|_| AntError::RunLoopDisconnected)
+    }
+
+    /// Returns a response if one is waiting without blocking.
+    pub fn try_recv(&self) -> Result<Option<Response>> {
+        match self.responses.try_recv() {
+            Ok(response) => Ok(Some(response)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(e) => Err(AntError::MessageTryRecvError(e)),
+        }
+    }
+
+    /// Returns a stream of the responses from the run loop not taken by a channel stream,
+    /// ending any stream already returned by stream(). Responses are only received through
+    /// recv() and try_recv() while no stream takes them, such as once the stream is dropped.
+    #[cfg(feature = "futures")]
+    pub fn stream(&mut self) -> ResponseStream {
+        self.router().stream()
+    }
+
+    /// Returns a stream of the responses for a channel opened by the client, ending any stream
+    /// already returned for the channel.
+    #[cfg(feature = "futures")]
+    pub fn channel_stream(&mut self, handle: &ChannelHandle) -> ResponseStream {
+        self.router().channel_stream(handle.number())
+    }
+
+    /// Stops the run loop, closing all open channels.
+    pub fn quit(self) -> Result<()> {
+        self.send(Request::Quit)
+    }
+
+    // Lowest channel number not already opened by the client.
+    fn next_channel(&self) -> Result<u8> {
+        (0..=u8::MAX)
+            .find(|number| !self.channels.contains(number))
+            .ok_or(AntError::InvalidChannel(u8::MAX))
+    }
+
+    // Routes responses from the run loop to streams once one is requested, so responses are read
+    // in one place and each is delivered to a single stream or the client.
+    #[cfg(feature = "futures")]
+    fn router(&mut self) -> &Router {
+        if self.router.is_none() {
+            let (router, responses) = Router::spawn(self.responses.clone());
+            self.responses = responses;
+            self.router = Some(router);
+        }
+        self.router.as_ref().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ant::{run_transport, RunConfig};
+    use crate::message::BroadcastDataMessage;
+    use crate::transport::MockTransport;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn open_and_close_channels() {
+        let (request_tx, request_rx) = crossbeam_channel::unbounded();
+        let (_response_tx, response_rx) = crossbeam_channel::unbounded();
+        let mut client = AntClient::new(request_tx, response_rx);
+
+        let first = client.open_channel(Config::new()).unwrap();
+        let second = client.open_channel(Config::new()).unwrap();
+        assert_eq!(first.number(), 0);
+        assert_eq!(second.number(), 1);
+        assert!(matches!(
+            request_rx.try_recv(),
+            Ok(Request::OpenChannel(0, _))
+        ));
+        assert!(matches!(
+            request_rx.try_recv(),
+            Ok(Request::OpenChannel(1, _))
+        ));
+
+        client.close(first).unwrap();
+        assert!(matches!(
+            request_rx.try_recv(),
+            Ok(Request::CloseChannel(0))
+        ));
+        assert_eq!(client.open_channel(Config::new()).unwrap().number(), 0);
+        assert!(client.try_recv().unwrap().is_none());
+    }
+
+    #[test]
+    fn open_channel_with_sender() {
+        let mock = MockTransport::stick(8);
+        let (request_tx, request_rx) = crossbeam_channel::unbounded();
+        let (response_tx, response_rx) = crossbeam_channel::unbounded();
+        let config = RunConfig::new()
+            .read_timeout(Duration::from_millis(10))
+            .reset_delay(Duration::from_millis(10));
+        let stick = mock.clone();
+        let handle = thread::spawn(move || run_transport(stick, request_rx, response_tx, config));
+        let mut client = AntClient::new(request_tx, response_rx);
+        let (channel_tx, channel_rx) = crossbeam_channel::unbounded();
+        let config = Config::new().device_type(0x78).frequency(57).period(8070);
+        let hrm = client.open_channel_with_sender(config, channel_tx).unwrap();
+        let recv = || channel_rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert!(matches!(recv(), Response::ChannelOpened(0)));
+
+        let data = BroadcastDataMessage::new(hrm.number(), &[0x04, 0, 0, 0, 0, 0, 0, 72]);
+        mock.push(&data.to_message());
+        assert!(matches!(recv(), Response::BroadcastData(_)));
+        client.send(Request::GetMetrics).unwrap();
+        assert!(matches!(client.recv(), Ok(Response::Metrics(_))));
+
+        // Responses fall back to the client once the channel's Receiver is dropped.
+        drop(channel_rx);
+        mock.push(&data.to_message());
+        assert!(matches!(client.recv(), Ok(Response::BroadcastData(_))));
+
+        client.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn streams_and_recv() {
+        use futures::executor::block_on;
+        use futures::stream::StreamExt;
+
+        let (request_tx, _request_rx) = crossbeam_channel::unbounded();
+        let (response_tx, response_rx) = crossbeam_channel::unbounded();
+        let mut client = AntClient::new(request_tx, response_rx);
+        let first = client.open_channel(Config::new()).unwrap();
+        let second = client.open_channel(Config::new()).unwrap();
+        let mut first = client.channel_stream(&first);
+        let mut second = client.channel_stream(&second);
+
+        response_tx.send(Response::ChannelOpened(0)).unwrap();
+        response_tx.send(Response::ChannelOpened(1)).unwrap();
+        response_tx.send(Response::ChannelOpened(2)).unwrap();
+        response_tx.send(Response::SearchTimeout(0)).unwrap();
+        assert!(matches!(
+            block_on(first.next()),
+            Some(Response::ChannelOpened(0))
+        ));
+        assert!(matches!(
+            block_on(second.next()),
+            Some(Response::ChannelOpened(1))
+        ));
+        assert!(matches!(client.recv(), Ok(Response::ChannelOpened(2))));
+        assert!(matches!(
+            block_on(first.next()),
+            Some(Response::SearchTimeout(0))
+        ));
+
+        // Responses for a dropped stream are received by the client.
+        drop(second);
+        response_tx.send(Response::SearchTimeout(1)).unwrap();
+        assert!(matches!(client.recv(), Ok(Response::SearchTimeout(1))));
+        drop(response_tx);
+        assert!(block_on(first.next()).is_none());
+        assert!(client.recv().is_err());
+    }
+}
@@ -1,14 +1,33 @@
 /// Device enum for passing in the type of device when opening a channel. As new devices
 /// are added to the library, the enum will be extended for each type of device.
+pub mod activity;
+pub mod cadence;
+pub mod cgm;
+pub mod common;
+pub mod controls;
+pub mod environment;
+pub mod fec;
 pub mod hrm;
+pub mod lev;
+pub mod lights;
+mod manufacturers;
 pub mod powermeter;
+pub mod radar;
+pub mod shifting;
+pub mod speed;
+pub mod speed_cadence;
+pub mod suspension;
+pub mod tpms;
 pub mod weightscale;
 
+use std::collections::BTreeMap;
+
 use crate::message::{bytes_to_u16, bytes_to_u32};
 
 // Common data pages across device types.
 // Page 0x50 - Manufacturer Information
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Page0x50([u8; 8]);
 
 impl Page0x50 {
@@ -25,7 +44,8 @@ impl Page0x50 {
     }
 }
 // Page 0x51 - Product Information
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Page0x51([u8; 8]);
 
 impl Page0x51 {
@@ -43,7 +63,8 @@ impl Page0x51 {
     }
 }
 // Page 0x52 - Battery Status
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Page0x52([u8; 8]);
 
 impl Page0x52 {
@@ -58,6 +79,23 @@ impl Page0x52 {
         Some(self.coarse_voltage() as f32 + (self.0[6] as f32 / 256_f32))
     }
 
+    /// Number of batteries in the device, or None if the device doesn't identify its batteries.
+    pub fn battery_count(&self) -> Option<u8> {
+        match self.0[2] {
+            0xFF => None,
+            value => Some(value & 0x0F),
+        }
+    }
+
+    /// Index of the battery the page is for, or None if the device doesn't identify its
+    /// batteries.
+    pub fn battery_index(&self) -> Option<u8> {
+        match self.0[2] {
+            0xFF => None,
+            value => Some(value >> 4),
+        }
+    }
+
     // Operating time in hours
     pub fn operating_time(&self) -> f32 {
         (bytes_to_u32(&self.0[3..6]) * self.time_resolution() as u32) as f32 / 3600_f32
@@ -81,7 +119,463 @@ impl Page0x52 {
     }
 }
 
+// Page 0x53 - Time and Date
+/// Time and date reported by devices with a clock, such as watches and weight scales, or sent
+/// to a device to set its clock. Times are UTC or local time as agreed by the devices.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page0x53([u8; 8]);
+
+impl Page0x53 {
+    /// Time and date to send to a device. Years from 2000 to 2255 can be sent. The day of the
+    /// week is sent as invalid if not known.
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        day_of_week: Option<DayOfWeek>,
+        hours: u8,
+        minutes: u8,
+        seconds: u8,
+    ) -> Self {
+        let day_of_week = day_of_week.map_or(0x07, |day| day as u8);
+        Self([
+            0x53,
+            0xFF,
+            seconds,
+            minutes,
+            hours,
+            (day_of_week << 5) | (day & 0x1F),
+            month,
+            year.saturating_sub(2000).min(255) as u8,
+        ])
+    }
+
+    pub fn seconds(&self) -> u8 {
+        self.0[2]
+    }
+
+    pub fn minutes(&self) -> u8 {
+        self.0[3]
+    }
+
+    pub fn hours(&self) -> u8 {
+        self.0[4]
+    }
+
+    /// Day of the month, from 1 to 31.
+    pub fn day(&self) -> u8 {
+        self.0[5] & 0x1F
+    }
+
+    pub fn day_of_week(&self) -> Option<DayOfWeek> {
+        DayOfWeek::from(self.0[5] >> 5)
+    }
+
+    /// Month, from 1 to 12.
+    pub fn month(&self) -> u8 {
+        self.0[6]
+    }
+
+    pub fn year(&self) -> u16 {
+        2000 + self.0[7] as u16
+    }
+
+    /// Data page to send to a device to set its clock.
+    pub fn encode(&self) -> [u8; 8] {
+        self.0
+    }
+}
+
+// Page 0x54 - Subfield Data
+// [2] - Subpage of the first data field, 0xFF if not used
+// [3] - Subpage of the second data field, 0xFF if not used
+// [4..6] - First data field
+// [6..8] - Second data field
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page0x54([u8; 8]);
+
+impl Page0x54 {
+    /// The subfields carried by the page, without unused subpages.
+    pub fn subfields(&self) -> Vec<Subfield> {
+        [(self.0[2], &self.0[4..6]), (self.0[3], &self.0[6..])]
+            .iter()
+            .filter(|(subpage, _)| *subpage != 0xFF)
+            .map(|(subpage, value)| Subfield::from(*subpage, bytes_to_u16(value)))
+            .collect()
+    }
+}
+
+/// A subfield of common page 0x54, used by devices to send environmental readings and other
+/// data alongside their main data pages.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Subfield {
+    /// Temperature in degrees Celsius.
+    Temperature(f32),
+    /// Barometric pressure in kPa.
+    BarometricPressure(f32),
+    /// Relative humidity as a percentage.
+    Humidity(f32),
+    /// Wind speed in meters per second.
+    WindSpeed(f32),
+    /// Wind direction in degrees.
+    WindDirection(f32),
+    ChargingCycles(u16),
+    /// Minimum operating temperature in degrees Celsius.
+    MinOperatingTemperature(f32),
+    /// Maximum operating temperature in degrees Celsius.
+    MaxOperatingTemperature(f32),
+    Unknown(u8, u16),
+}
+
+impl Subfield {
+    fn from(subpage: u8, value: u16) -> Self {
+        match subpage {
+            1 => Self::Temperature(value as i16 as f32 / 100_f32),
+            2 => Self::BarometricPressure(value as f32 / 100_f32),
+            3 => Self::Humidity(value as f32 / 100_f32),
+            4 => Self::WindSpeed(value as f32 / 100_f32),
+            5 => Self::WindDirection(value as f32 / 20_f32),
+            6 => Self::ChargingCycles(value),
+            7 => Self::MinOperatingTemperature(value as i16 as f32 / 100_f32),
+            8 => Self::MaxOperatingTemperature(value as i16 as f32 / 100_f32),
+            _ => Self::Unknown(subpage, value),
+        }
+    }
+}
+
+/// Latest value of each subfield received in common page 0x54.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Subfields {
+    temperature: Option<f32>,
+    barometric_pressure: Option<f32>,
+    humidity: Option<f32>,
+    wind_speed: Option<f32>,
+    wind_direction: Option<f32>,
+    charging_cycles: Option<u16>,
+    min_operating_temperature: Option<f32>,
+    max_operating_temperature: Option<f32>,
+}
+
+impl Subfields {
+    pub fn update(&mut self, page: &Page0x54) {
+        for subfield in page.subfields() {
+            match subfield {
+                Subfield::Temperature(value) => self.temperature = Some(value),
+                Subfield::BarometricPressure(value) => self.barometric_pressure = Some(value),
+                Subfield::Humidity(value) => self.humidity = Some(value),
+                Subfield::WindSpeed(value) => self.wind_speed = Some(value),
+                Subfield::WindDirection(value) => self.wind_direction = Some(value),
+                Subfield::ChargingCycles(value) => self.charging_cycles = Some(value),
+                Subfield::MinOperatingTemperature(value) => {
+                    self.min_operating_temperature = Some(value)
+                }
+                Subfield::MaxOperatingTemperature(value) => {
+                    self.max_operating_temperature = Some(value)
+                }
+                Subfield::Unknown(..) => {}
+            }
+        }
+    }
+
+    /// Temperature in degrees Celsius.
+    pub fn temperature(&self) -> Option<f32> {
+        self.temperature
+    }
+
+    /// Barometric pressure in kPa.
+    pub fn barometric_pressure(&self) -> Option<f32> {
+        self.barometric_pressure
+    }
+
+    /// Relative humidity as a percentage.
+    pub fn humidity(&self) -> Option<f32> {
+        self.humidity
+    }
+
+    /// Wind speed in meters per second.
+    pub fn wind_speed(&self) -> Option<f32> {
+        self.wind_speed
+    }
+
+    /// Wind direction in degrees.
+    pub fn wind_direction(&self) -> Option<f32> {
+        self.wind_direction
+    }
+
+    pub fn charging_cycles(&self) -> Option<u16> {
+        self.charging_cycles
+    }
+
+    /// Minimum operating temperature in degrees Celsius.
+    pub fn min_operating_temperature(&self) -> Option<f32> {
+        self.min_operating_temperature
+    }
+
+    /// Maximum operating temperature in degrees Celsius.
+    pub fn max_operating_temperature(&self) -> Option<f32> {
+        self.max_operating_temperature
+    }
+}
+
+// Page 0x55 - Memory Level
+// [4] - Percent used in 0.5%, 0xFF if invalid
+// [5..7] - Total size in 0.1 units
+// [7] - Bit 7 set for bytes, clear for bits. Bits 0-6 for the unit prefix.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page0x55([u8; 8]);
+
+impl Page0x55 {
+    /// Percentage of the device's memory used.
+    pub fn percent_used(&self) -> Option<f32> {
+        match self.0[4] {
+            0xFF => None,
+            value => Some(value as f32 / 2_f32),
+        }
+    }
+
+    /// Total size of the device's memory in the unit given by size_unit.
+    pub fn total_size(&self) -> f32 {
+        bytes_to_u16(&self.0[5..7]) as f32 / 10_f32
+    }
+
+    pub fn size_unit(&self) -> MemoryUnit {
+        MemoryUnit::from(self.0[7])
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MemoryUnit {
+    Bits,
+    Kilobits,
+    Megabits,
+    Terabits,
+    Bytes,
+    Kilobytes,
+    Megabytes,
+    Terabytes,
+    Unknown(u8),
+}
+
+impl MemoryUnit {
+    fn from(value: u8) -> Self {
+        match (value & 0x80 == 0x80, value & 0x7F) {
+            (false, 0x00) => Self::Bits,
+            (false, 0x01) => Self::Kilobits,
+            (false, 0x02) => Self::Megabits,
+            (false, 0x03) => Self::Terabits,
+            (true, 0x00) => Self::Bytes,
+            (true, 0x01) => Self::Kilobytes,
+            (true, 0x02) => Self::Megabytes,
+            (true, 0x03) => Self::Terabytes,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+// Page 0x56 - Paired Devices
+// [1] - Index of the peripheral device
+// [2] - Total number of peripheral devices
+// [3] - Bit 7 set if paired. Bits 3-6 for the connection state, bits 0-2 for the network key.
+// [4..6] - Device number of the peripheral device
+// [6] - Transmission type of the peripheral device
+// [7] - Device type of the peripheral device
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page0x56([u8; 8]);
+
+impl Page0x56 {
+    pub fn index(&self) -> u8 {
+        self.0[1]
+    }
+
+    /// Total number of peripheral devices the master device is connected to.
+    pub fn total(&self) -> u8 {
+        self.0[2]
+    }
+
+    pub fn paired_device(&self) -> PairedDevice {
+        PairedDevice {
+            paired: self.0[3] & 0x80 == 0x80,
+            connection_state: ConnectionState::from((self.0[3] >> 3) & 0x0F),
+            network_key: NetworkKey::from(self.0[3] & 0x07),
+            device_id: bytes_to_u16(&self.0[4..6]),
+            transmission_type: self.0[6],
+            device_type: self.0[7],
+        }
+    }
+}
+
+/// A peripheral device a master device is connected to, from common page 0x56.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PairedDevice {
+    pub paired: bool,
+    pub connection_state: ConnectionState,
+    pub network_key: NetworkKey,
+    pub device_id: u16,
+    pub transmission_type: u8,
+    pub device_type: u8,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConnectionState {
+    Closed,
+    Searching,
+    Tracking,
+    Unknown(u8),
+}
+
+impl ConnectionState {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Closed,
+            1 => Self::Searching,
+            2 => Self::Tracking,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NetworkKey {
+    Public,
+    Private,
+    AntPlus,
+    AntFs,
+    Unknown(u8),
+}
+
+impl NetworkKey {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Public,
+            1 => Self::Private,
+            2 => Self::AntPlus,
+            3 => Self::AntFs,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+/// Peripheral devices reported by a master device in common page 0x56, by index. A master
+/// device sends one page for each peripheral device in turn.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PairedDevices {
+    total: u8,
+    devices: BTreeMap<u8, PairedDevice>,
+}
+
+impl PairedDevices {
+    pub fn update(&mut self, page: &Page0x56) {
+        self.total = page.total();
+        // Peripheral devices at indexes past the total have been removed.
+        let total = self.total;
+        self.devices.retain(|index, _| *index < total);
+        if page.index() < total {
+            self.devices.insert(page.index(), page.paired_device());
+        }
+    }
+
+    /// Total number of peripheral devices the master device last reported.
+    pub fn total(&self) -> u8 {
+        self.total
+    }
+
+    /// Peripheral devices received so far, in index order.
+    pub fn devices(&self) -> Vec<PairedDevice> {
+        self.devices.values().copied().collect()
+    }
+}
+
+// Page 0x57 - Error Description
+// [2] - Bits 6-7 for the error level, bits 0-3 for the index of the system component
+// [3] - Profile specific error code, 0xFF if not used
+// [4..8] - Manufacturer specific error code
+/// Error reported by a device, such as a trainer that is over temperature.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page0x57([u8; 8]);
+
+impl Page0x57 {
+    pub fn error_level(&self) -> ErrorLevel {
+        ErrorLevel::from(self.0[2] >> 6)
+    }
+
+    /// Index of the component with the error for devices made up of several components.
+    pub fn component_index(&self) -> u8 {
+        self.0[2] & 0x0F
+    }
+
+    /// Error code defined by the device profile.
+    pub fn profile_error(&self) -> Option<u8> {
+        match self.0[3] {
+            0xFF => None,
+            code => Some(code),
+        }
+    }
+
+    /// Error code defined by the manufacturer.
+    pub fn manufacturer_error(&self) -> u32 {
+        bytes_to_u32(&self.0[4..])
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ErrorLevel {
+    Warning,
+    Critical,
+    Unknown(u8),
+}
+
+impl ErrorLevel {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Warning,
+            2 => Self::Critical,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DayOfWeek {
+    Sunday,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+}
+
+impl DayOfWeek {
+    fn from(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Sunday),
+            1 => Some(Self::Monday),
+            2 => Some(Self::Tuesday),
+            3 => Some(Self::Wednesday),
+            4 => Some(Self::Thursday),
+            5 => Some(Self::Friday),
+            6 => Some(Self::Saturday),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BatteryStatus {
     New,
     Good,
@@ -128,7 +622,10 @@ impl std::fmt::Display for BatteryStatus {
 }
 
 // Sent as a u16. Matching Manufacturer to value can be found in a spreadsheet in the SDK.
-#[derive(Debug, Clone)]
+/// Manufacturers without a variant are Other with their ID, and are displayed with their name
+/// from the full list of ANT+ manufacturers if the ID is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Manufacturer {
     Garmin,
     SRM,
@@ -141,7 +638,7 @@ pub enum Manufacturer {
     Campagnolo,
     Favero,
     SRAM,
-    Undefined,
+    Other(u16),
 }
 
 impl Manufacturer {
@@ -158,14 +655,31 @@ impl Manufacturer {
             100 => Self::Campagnolo,
             263 => Self::Favero,
             268 => Self::SRAM,
-            _ => Self::Undefined,
+            _ => Self::Other(value),
         }
     }
-}
 
-impl std::fmt::Display for Manufacturer {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let manufacturer = match *self {
+    /// Manufacturer ID sent by the device.
+    pub fn id(&self) -> u16 {
+        match *self {
+            Self::Garmin => 1,
+            Self::SRM => 6,
+            Self::Quarq => 7,
+            Self::Saxonar => 29,
+            Self::WahooFitness => 32,
+            Self::Shimano => 41,
+            Self::Rotor => 60,
+            Self::StagesCycling => 69,
+            Self::Campagnolo => 100,
+            Self::Favero => 263,
+            Self::SRAM => 268,
+            Self::Other(id) => id,
+        }
+    }
+
+    /// Name of the manufacturer, or None if the ID is not in the list of ANT+ manufacturers.
+    pub fn name(&self) -> Option<&'static str> {
+        let name = match *self {
             Self::Garmin => "Garmin",
             Self::SRM => "SRM",
             Self::Quarq => "Quarq",
@@ -177,8 +691,196 @@ impl std::fmt::Display for Manufacturer {
             Self::Campagnolo => "Campagnolo",
             Self::Favero => "Favero",
             Self::SRAM => "SRAM",
-            Self::Undefined => "Undefined",
+            Self::Other(id) => {
+                let index = manufacturers::MANUFACTURERS
+                    .binary_search_by_key(&id, |(id, _)| *id)
+                    .ok()?;
+                manufacturers::MANUFACTURERS[index].1
+            }
         };
-        write!(f, "{}", manufacturer)
+        Some(name)
+    }
+}
+
+impl std::fmt::Display for Manufacturer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "Unknown ({})", self.id()),
+        }
+    }
+}
+
+/// PageScheduler decides when a master profile should interleave background pages, such as the
+/// common pages 0x50, 0x51, and 0x52, with its main data pages. After every interval main data
+/// pages, the next background page in the rotation is sent repeat times in a row.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageScheduler {
+    interval: u32,
+    repeat: u32,
+    pages: Vec<u8>,
+    position: u32,
+    index: usize,
+}
+
+impl PageScheduler {
+    pub fn new(interval: u32, pages: &[u8]) -> Self {
+        Self {
+            interval,
+            repeat: 1,
+            pages: pages.to_vec(),
+            position: 0,
+            index: 0,
+        }
+    }
+
+    /// Number of times each background page is sent in a row. Defaults to 1.
+    pub fn repeat(mut self, repeat: u32) -> Self {
+        self.repeat = repeat.max(1);
+        self
+    }
+
+    /// Returns the background page to send for the next message, or None if a main data page
+    /// should be sent.
+    pub fn next_page(&mut self) -> Option<u8> {
+        if self.pages.is_empty() {
+            return None;
+        }
+        let position = self.position;
+        self.position = (self.position + 1) % (self.interval + self.repeat);
+        if position < self.interval {
+            return None;
+        }
+        let page = self.pages[self.index];
+        if position == self.interval + self.repeat - 1 {
+            self.index = (self.index + 1) % self.pages.len();
+        }
+        Some(page)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_page_0x53() {
+        let page = Page0x53([0x53, 0xFF, 30, 15, 13, 0x88, 10, 26]);
+        assert_eq!(page.seconds(), 30);
+        assert_eq!(page.minutes(), 15);
+        assert_eq!(page.hours(), 13);
+        assert_eq!(page.day(), 8);
+        assert_eq!(page.day_of_week(), Some(DayOfWeek::Thursday));
+        assert_eq!(page.month(), 10);
+        assert_eq!(page.year(), 2026);
+
+        let page = Page0x53::new(2026, 10, 8, Some(DayOfWeek::Thursday), 13, 15, 30);
+        assert_eq!(page.encode(), [0x53, 0xFF, 30, 15, 13, 0x88, 10, 26]);
+        let page = Page0x53::new(2026, 10, 8, None, 13, 15, 30);
+        assert_eq!(page.day_of_week(), None);
+        assert_eq!(page.day(), 8);
+    }
+
+    #[test]
+    fn test_page_0x54() {
+        // Temperature of -5.5C and humidity of 45.25%.
+        let page = Page0x54([0x54, 0xFF, 0x01, 0x03, 0xDA, 0xFD, 0xAD, 0x11]);
+        assert_eq!(
+            page.subfields(),
+            [Subfield::Temperature(-5.5), Subfield::Humidity(45.25)]
+        );
+        let mut subfields = Subfields::default();
+        subfields.update(&page);
+        // Wind direction of 90 degrees, with the second subpage unused.
+        subfields.update(&Page0x54([0x54, 0xFF, 0x05, 0xFF, 0x08, 0x07, 0xFF, 0xFF]));
+        assert_eq!(subfields.temperature(), Some(-5.5));
+        assert_eq!(subfields.humidity(), Some(45.25));
+        assert_eq!(subfields.wind_direction(), Some(90.0));
+        assert_eq!(subfields.wind_speed(), None);
+    }
+
+    #[test]
+    fn test_page_0x55() {
+        // 92.5% of 4 megabytes used.
+        let page = Page0x55([0x55, 0xFF, 0xFF, 0xFF, 185, 40, 0, 0x82]);
+        assert_eq!(page.percent_used(), Some(92.5));
+        assert_eq!(page.total_size(), 4.0);
+        assert_eq!(page.size_unit(), MemoryUnit::Megabytes);
+        let page = Page0x55([0x55, 0xFF, 0xFF, 0xFF, 0xFF, 40, 0, 0x04]);
+        assert_eq!(page.percent_used(), None);
+        assert_eq!(page.size_unit(), MemoryUnit::Unknown(0x04));
+    }
+
+    #[test]
+    fn test_page_0x56() {
+        // Heart rate monitor 0x3039 on the ANT+ network, paired and tracking.
+        let page = Page0x56([0x56, 0x01, 0x02, 0x92, 0x39, 0x30, 0x01, 0x78]);
+        assert_eq!(page.index(), 1);
+        assert_eq!(page.total(), 2);
+        let device = page.paired_device();
+        assert!(device.paired);
+        assert_eq!(device.connection_state, ConnectionState::Tracking);
+        assert_eq!(device.network_key, NetworkKey::AntPlus);
+        assert_eq!(device.device_id, 0x3039);
+        assert_eq!(device.transmission_type, 0x01);
+        assert_eq!(device.device_type, 0x78);
+
+        let mut devices = PairedDevices::default();
+        devices.update(&Page0x56([0x56, 0x00, 0x02, 0x88, 0x01, 0x00, 0x01, 0x0B]));
+        devices.update(&page);
+        assert_eq!(devices.devices().len(), 2);
+        // Down to one peripheral device.
+        devices.update(&Page0x56([0x56, 0x00, 0x01, 0x88, 0x01, 0x00, 0x01, 0x0B]));
+        assert_eq!(devices.total(), 1);
+        assert_eq!(devices.devices()[0].device_type, 0x0B);
+        assert_eq!(devices.devices().len(), 1);
+    }
+
+    #[test]
+    fn test_page_0x57() {
+        let page = Page0x57([0x57, 0xFF, 0x82, 0xFF, 0x2A, 0x00, 0x00, 0x00]);
+        assert_eq!(page.error_level(), ErrorLevel::Critical);
+        assert_eq!(page.component_index(), 2);
+        assert_eq!(page.profile_error(), None);
+        assert_eq!(page.manufacturer_error(), 42);
+        let page = Page0x57([0x57, 0xFF, 0x40, 0x01, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(page.error_level(), ErrorLevel::Warning);
+        assert_eq!(page.profile_error(), Some(1));
+    }
+
+    #[test]
+    fn test_manufacturer() {
+        assert_eq!(Manufacturer::from(32), Manufacturer::WahooFitness);
+        assert_eq!(Manufacturer::from(32).id(), 32);
+        assert_eq!(Manufacturer::from(123), Manufacturer::Other(123));
+        assert_eq!(Manufacturer::from(123).to_string(), "Polar Electro");
+        assert_eq!(Manufacturer::from(1000).to_string(), "Unknown (1000)");
+        assert!(manufacturers::MANUFACTURERS
+            .windows(2)
+            .all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_page_scheduler() {
+        let mut scheduler = PageScheduler::new(2, &[0x50, 0x51]).repeat(2);
+        let pages: Vec<Option<u8>> = (0..8).map(|_| scheduler.next_page()).collect();
+        assert_eq!(
+            pages,
+            [
+                None,
+                None,
+                Some(0x50),
+                Some(0x50),
+                None,
+                None,
+                Some(0x51),
+                Some(0x51)
+            ]
+        );
+        let mut scheduler = PageScheduler::new(2, &[]);
+        assert_eq!(scheduler.next_page(), None);
+        assert_eq!(scheduler.next_page(), None);
+        assert_eq!(scheduler.next_page(), None);
     }
 }
@@ -0,0 +1,177 @@
+use super::{BatteryStatus, Manufacturer, MemoryUnit, Page0x50, Page0x51, Page0x52, Page0x55};
+use crate::channel::Config;
+use crate::message::bytes_to_u32;
+
+// Constant values for the activity monitor channel.
+const ACTIVITY_DEVICE_TYPE: u8 = 0x0F;
+const ACTIVITY_FREQUENCY: u8 = 0x39;
+const ACTIVITY_PERIOD: u16 = 8192;
+
+// ActivityMonitor provides a way to decode the daily totals broadcast by an activity monitor such
+// as a fitness tracker worn on the wrist.
+// Page 0x01 -> Step Count
+// Page 0x02 -> Calories
+// Page 0x03 -> Activity Time
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+// Page 0x52 -> Battery Voltage
+// Page 0x55 -> Memory Level
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActivityMonitor {
+    steps: Option<u32>,
+    calories: Option<u32>,
+    activity_time: Option<u32>,
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+    page_0x55: Option<Page0x55>,
+}
+
+impl ActivityMonitor {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(ACTIVITY_DEVICE_TYPE)
+            .frequency(ACTIVITY_FREQUENCY)
+            .period(ACTIVITY_PERIOD)
+    }
+
+    /// Cumulative step count for the day.
+    pub fn steps(&self) -> Option<u32> {
+        self.steps
+    }
+
+    /// Cumulative calories burned for the day in kcal.
+    pub fn calories(&self) -> Option<u32> {
+        self.calories
+    }
+
+    /// Cumulative time spent active for the day in minutes.
+    pub fn activity_time(&self) -> Option<u32> {
+        self.activity_time
+    }
+
+    /// Percentage of the activity monitor's memory used, to warn before it is full and
+    /// activities are no longer recorded until downloaded.
+    pub fn memory_used(&self) -> Option<f32> {
+        self.page_0x55.and_then(|page| page.percent_used())
+    }
+
+    /// Total size of the activity monitor's memory and its unit.
+    pub fn memory_size(&self) -> Option<(f32, MemoryUnit)> {
+        self.page_0x55
+            .map(|page| (page.total_size(), page.size_unit()))
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.battery_status());
+        }
+        None
+    }
+
+    pub fn battery_voltage(&self) -> Option<f32> {
+        if let Some(page) = &self.page_0x52 {
+            return page.battery_voltage();
+        }
+        None
+    }
+
+    /// Operating time in hours.
+    pub fn operating_time(&self) -> Option<f32> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.operating_time());
+        }
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.serial_number());
+        }
+        None
+    }
+
+    pub fn software_version(&self) -> Option<f32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.software_version());
+        }
+        None
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.manufacturer());
+        }
+        None
+    }
+
+    pub fn model_number(&self) -> Option<u16> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.model_number());
+        }
+        None
+    }
+
+    pub fn hardware_revision(&self) -> Option<u8> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.hardware_revision());
+        }
+        None
+    }
+
+    // Each activity page carries its cumulative value in bytes 4-7.
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] {
+            0x01 => self.steps = Some(bytes_to_u32(&data[4..])),
+            0x02 => self.calories = Some(bytes_to_u32(&data[4..])),
+            0x03 => self.activity_time = Some(bytes_to_u32(&data[4..])),
+            0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(data)),
+            0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(data)),
+            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            0x55 => self.page_0x55 = Some(Page0x55(data)),
+            _ => {} // Do nothing with rest of pages for now.
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_activity_decode() {
+        let mut monitor = ActivityMonitor::new();
+        assert_eq!(monitor.steps(), None);
+        monitor.decode([0x01, 0xFF, 0xFF, 0xFF, 0x10, 0x27, 0x00, 0x00]);
+        monitor.decode([0x02, 0xFF, 0xFF, 0xFF, 0xC4, 0x09, 0x00, 0x00]);
+        monitor.decode([0x03, 0xFF, 0xFF, 0xFF, 0x5A, 0x00, 0x00, 0x00]);
+        assert_eq!(monitor.steps(), Some(10000));
+        assert_eq!(monitor.calories(), Some(2500));
+        assert_eq!(monitor.activity_time(), Some(90));
+    }
+
+    #[test]
+    fn test_activity_common_pages() {
+        let mut monitor = ActivityMonitor::new();
+        monitor.decode([0x50, 0xFF, 0xFF, 0x02, 0x01, 0x00, 0x2A, 0x00]);
+        monitor.decode([0x51, 0xFF, 0xFF, 0x0C, 0x39, 0x30, 0x00, 0x00]);
+        // Only the first manufacturer and product pages are kept.
+        monitor.decode([0x50, 0xFF, 0xFF, 0x03, 0x01, 0x00, 0x2B, 0x00]);
+        assert!(matches!(monitor.manufacturer(), Some(Manufacturer::Garmin)));
+        assert_eq!(monitor.model_number(), Some(42));
+        assert_eq!(monitor.hardware_revision(), Some(2));
+        assert_eq!(monitor.serial_number(), Some(12345));
+        assert_eq!(monitor.software_version(), Some(1.2));
+        // 3.5V battery with operating time in 2 second units.
+        monitor.decode([0x52, 0xFF, 0xFF, 0x08, 0x07, 0x00, 0x80, 0x23]);
+        assert_eq!(monitor.battery_voltage(), Some(3.5));
+        assert_eq!(monitor.operating_time(), Some(1.0));
+    }
+}
@@ -0,0 +1,176 @@
+use super::{BatteryStatus, Manufacturer};
+use crate::channel::Config;
+/// Bicycle cadence sensor. Every data page includes the cadence event time and the cumulative
+/// crank revolution count. The MSB of the page number toggles every four pages for devices that
+/// support background pages.
+use crate::message::{bytes_to_u16, bytes_to_u32};
+
+const CADENCE_DEVICE_TYPE: u8 = 0x7A;
+const CADENCE_FREQUENCY: u8 = 0x39;
+const CADENCE_PERIOD: u16 = 8102;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CadenceSensor {
+    cadence: u8,
+    revolutions: u32,
+    last_event_time: Option<u16>,
+    last_revolution_count: Option<u16>,
+    operating_time: u32,
+    manufacturer_id: u8,
+    serial_number: u16,
+    hardware_version: u8,
+    software_version: u8,
+    model_number: u8,
+    fractional_battery_voltage: u8,
+    descriptive_bit_field: u8,
+    stopped: bool,
+}
+
+impl CadenceSensor {
+    pub fn new() -> Self {
+        CadenceSensor {
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(CADENCE_DEVICE_TYPE)
+            .frequency(CADENCE_FREQUENCY)
+            .period(CADENCE_PERIOD)
+    }
+
+    /// Cadence in RPM calculated from the last two events.
+    pub fn cadence(&self) -> u8 {
+        self.cadence
+    }
+
+    /// Cumulative crank revolution count as reported by the sensor. Rolls over at 65536.
+    pub fn revolution_count(&self) -> u16 {
+        self.last_revolution_count.unwrap_or(0)
+    }
+
+    /// Crank revolutions counted since the first page was received.
+    pub fn revolutions(&self) -> u32 {
+        self.revolutions
+    }
+
+    /// Cumulative operating time in seconds.
+    pub fn operating_time(&self) -> u32 {
+        self.operating_time
+    }
+
+    /// Manufacturer of the hardware device
+    pub fn manufacturer(&self) -> Manufacturer {
+        Manufacturer::from(self.manufacturer_id as u16)
+    }
+
+    /// Serial number of device, typically the ANT+ ID
+    pub fn serial_number(&self) -> u16 {
+        self.serial_number
+    }
+
+    /// Hardware version of the device
+    pub fn hardware_version(&self) -> u8 {
+        self.hardware_version
+    }
+
+    /// Software version of the device
+    pub fn software_version(&self) -> u8 {
+        self.software_version
+    }
+
+    /// Model number of the device
+    pub fn model_number(&self) -> u8 {
+        self.model_number
+    }
+
+    /// Battery voltage in volts. Returns None if the voltage is invalid.
+    pub fn battery_voltage(&self) -> Option<f32> {
+        let coarse = self.descriptive_bit_field & 0x0F;
+        if coarse == 0x0F {
+            return None;
+        }
+        Some(coarse as f32 + self.fractional_battery_voltage as f32 / 256_f32)
+    }
+
+    /// Battery status from the data provided by the device.
+    pub fn battery_status(&self) -> BatteryStatus {
+        BatteryStatus::from(self.descriptive_bit_field)
+    }
+
+    /// Whether the sensor has signaled the crank is stopped.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Decode broadcast data received from the cadence sensor.
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] & 0x7F {
+            // Data page 0 Default data page
+            0x00 => {}
+            // Data page 1 Cumulative Operating Time
+            0x01 => self.operating_time = bytes_to_u32(&data[1..4]) * 2,
+            // Data page 2 Manufacturer Information
+            0x02 => {
+                self.manufacturer_id = data[1];
+                self.serial_number = bytes_to_u16(&data[2..4]);
+            }
+            // Data page 3 Product Information
+            0x03 => {
+                self.hardware_version = data[1];
+                self.software_version = data[2];
+                self.model_number = data[3];
+            }
+            // Data page 4 Battery Status
+            0x04 => {
+                self.fractional_battery_voltage = data[2];
+                self.descriptive_bit_field = data[3];
+            }
+            // Data page 5 Motion
+            0x05 => self.stopped = data[1] & 0x01 == 0x01,
+            _ => return, // Drop message if none of these pages
+        }
+        self.update_cadence(bytes_to_u16(&data[4..6]), bytes_to_u16(&data[6..]));
+    }
+
+    // Cadence is calculated from the change in revolution count over the change in event time
+    // since the last page. Both fields roll over at 65536.
+    fn update_cadence(&mut self, event_time: u16, revolution_count: u16) {
+        if let (Some(last_event_time), Some(last_revolution_count)) =
+            (self.last_event_time, self.last_revolution_count)
+        {
+            let time_delta = event_time.wrapping_sub(last_event_time);
+            let rev_delta = revolution_count.wrapping_sub(last_revolution_count);
+            self.revolutions += rev_delta as u32;
+            if self.stopped {
+                self.cadence = 0;
+            } else if time_delta != 0 {
+                self.cadence =
+                    (60_f32 * rev_delta as f32 / (time_delta as f32 / 1024_f32)).round() as u8;
+            }
+        }
+        self.last_event_time = Some(event_time);
+        self.last_revolution_count = Some(revolution_count);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cadence_decode() {
+        let mut sensor = CadenceSensor::new();
+        sensor.decode([0x00, 0xFF, 0xFF, 0xFF, 0x00, 0xFE, 0xFF, 0xFF]);
+        assert_eq!(sensor.cadence(), 0);
+        // Event time and revolution count both roll over. 0x0200 ticks is half a second.
+        sensor.decode([0x80, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(sensor.revolutions(), 1);
+        assert_eq!(sensor.cadence(), 120);
+        // Stop signaled on page 5.
+        sensor.decode([0x05, 0x01, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(sensor.cadence(), 0);
+    }
+}
@@ -0,0 +1,191 @@
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52};
+use crate::channel::Config;
+/// Continuous glucose monitor. The monitor broadcasts the most recent glucose measurement along
+/// with the trend and how long ago the measurement was taken. Background pages carry the common
+/// manufacturer, product, and battery information.
+use crate::message::bytes_to_u16;
+
+const CGM_DEVICE_TYPE: u8 = 0x24;
+const CGM_FREQUENCY: u8 = 0x39;
+const CGM_PERIOD: u16 = 8192;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlucoseMonitor {
+    glucose: Option<u16>,
+    trend: Option<i8>,
+    time_offset: u16,
+    sequence_number: u8,
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+}
+
+impl GlucoseMonitor {
+    pub fn new() -> Self {
+        GlucoseMonitor {
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(CGM_DEVICE_TYPE)
+            .frequency(CGM_FREQUENCY)
+            .period(CGM_PERIOD)
+    }
+
+    /// Glucose concentration in mg/dL. None until a valid measurement is received.
+    pub fn glucose(&self) -> Option<u16> {
+        self.glucose
+    }
+
+    /// Glucose concentration in mmol/L.
+    pub fn glucose_in_mmol(&self) -> Option<f32> {
+        self.glucose.map(|mg| mg as f32 / 18.0)
+    }
+
+    /// Rate of change of glucose in mg/dL per minute.
+    pub fn trend(&self) -> Option<f32> {
+        self.trend.map(|trend| trend as f32 / 10_f32)
+    }
+
+    /// Direction glucose is trending based on the rate of change.
+    pub fn trend_direction(&self) -> Option<TrendDirection> {
+        self.trend().map(TrendDirection::from)
+    }
+
+    /// Minutes since the glucose measurement was taken.
+    pub fn time_offset(&self) -> u16 {
+        self.time_offset
+    }
+
+    /// Sequence number of the measurement. Increments with each new measurement.
+    pub fn sequence_number(&self) -> u8 {
+        self.sequence_number
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        self.page_0x52.map(|page| page.battery_status())
+    }
+
+    pub fn battery_voltage(&self) -> Option<f32> {
+        self.page_0x52.and_then(|page| page.battery_voltage())
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        self.page_0x51.map(|page| page.serial_number())
+    }
+
+    pub fn software_version(&self) -> Option<f32> {
+        self.page_0x51.map(|page| page.software_version())
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        self.page_0x50.map(|page| page.manufacturer())
+    }
+
+    pub fn model_number(&self) -> Option<u16> {
+        self.page_0x50.map(|page| page.model_number())
+    }
+
+    /// Decode broadcast data received from the glucose monitor.
+    pub fn decode_broadcast_data(&mut self, data: &[u8]) {
+        // Check length of slice. Discard if not 8.
+        if data.len() == 8 {
+            let mut page = [0; 8];
+            page.copy_from_slice(data);
+            match data[0] {
+                // Data page 1 Glucose Measurement
+                // [1] - Sequence number
+                // [2..4] - Glucose concentration in mg/dL, 0xFFFF if invalid
+                // [4] - Trend in 0.1 mg/dL/min, 0x7F if invalid
+                // [6..8] - Minutes since the measurement was taken
+                0x01 => {
+                    self.sequence_number = data[1];
+                    self.glucose = match bytes_to_u16(&data[2..4]) {
+                        0xFFFF => None,
+                        glucose => Some(glucose),
+                    };
+                    self.trend = match data[4] as i8 {
+                        0x7F => None,
+                        trend => Some(trend),
+                    };
+                    self.time_offset = bytes_to_u16(&data[6..]);
+                }
+                0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(page)),
+                0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(page)),
+                0x52 => self.page_0x52 = Some(Page0x52(page)),
+                _ => {} //Drop message if none of these pages
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrendDirection {
+    FallingQuickly,
+    Falling,
+    Steady,
+    Rising,
+    RisingQuickly,
+}
+
+impl TrendDirection {
+    // Thresholds in mg/dL/min commonly used by CGM displays.
+    fn from(rate: f32) -> Self {
+        if rate <= -2.0 {
+            Self::FallingQuickly
+        } else if rate <= -1.0 {
+            Self::Falling
+        } else if rate < 1.0 {
+            Self::Steady
+        } else if rate < 2.0 {
+            Self::Rising
+        } else {
+            Self::RisingQuickly
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cgm_decode() {
+        let mut cgm = GlucoseMonitor::new();
+        assert_eq!(cgm.glucose(), None);
+        // 126 mg/dL falling 1.5 mg/dL/min, measured 3 minutes ago.
+        cgm.decode_broadcast_data(&[0x01, 0x05, 0x7E, 0x00, 0xF1, 0xFF, 0x03, 0x00]);
+        assert_eq!(cgm.sequence_number(), 5);
+        assert_eq!(cgm.glucose(), Some(126));
+        assert_eq!(cgm.glucose_in_mmol(), Some(7.0));
+        assert_eq!(cgm.trend(), Some(-1.5));
+        assert_eq!(cgm.trend_direction(), Some(TrendDirection::Falling));
+        assert_eq!(cgm.time_offset(), 3);
+        // Invalid measurement and trend.
+        cgm.decode_broadcast_data(&[0x01, 0x06, 0xFF, 0xFF, 0x7F, 0xFF, 0x00, 0x00]);
+        assert_eq!(cgm.glucose(), None);
+        assert_eq!(cgm.trend(), None);
+    }
+
+    #[test]
+    fn test_cgm_common_pages() {
+        let mut cgm = GlucoseMonitor::new();
+        assert!(cgm.manufacturer().is_none());
+        cgm.decode_broadcast_data(&[0x50, 0xFF, 0xFF, 0x01, 0x01, 0x00, 0x2A, 0x00]);
+        cgm.decode_broadcast_data(&[0x51, 0xFF, 0xFF, 0x0C, 0x39, 0x30, 0x00, 0x00]);
+        cgm.decode_broadcast_data(&[0x52, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00, 0x13]);
+        assert!(matches!(cgm.manufacturer(), Some(Manufacturer::Garmin)));
+        assert_eq!(cgm.model_number(), Some(42));
+        assert_eq!(cgm.serial_number(), Some(12345));
+        assert_eq!(cgm.software_version(), Some(1.2));
+        assert_eq!(cgm.battery_voltage(), Some(3.0));
+        assert!(matches!(cgm.battery_status(), Some(BatteryStatus::New)));
+        // Only the first manufacturer and product pages are kept.
+        cgm.decode_broadcast_data(&[0x50, 0xFF, 0xFF, 0x01, 0x01, 0x00, 0x2B, 0x00]);
+        assert_eq!(cgm.model_number(), Some(42));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::message::AcknowledgeDataMessage;
+
+const COMMON_DATA_PAGE_70: u8 = 0x46;
+const REQUEST_DATA_PAGE: u8 = 0x01;
+// Heart rate monitors, bike cadence sensors, and bike speed sensors.
+const PAGE_TOGGLE_DEVICE_TYPES: [u8; 3] = [0x78, 0x7A, 0x7B];
+
+/// How a device sends back a page requested with common page 70.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Transmission {
+    /// Broadcast the page the number of times, from 1 to 127.
+    Broadcast(u8),
+    /// Send the page as acknowledged data the number of times, from 1 to 127.
+    Acknowledged(u8),
+    /// Send the page as acknowledged data until it is acknowledged.
+    UntilAcknowledged,
+}
+
+impl Transmission {
+    fn encode(&self) -> u8 {
+        match *self {
+            Self::Broadcast(times) => times.clamp(1, 0x7F),
+            Self::Acknowledged(times) => 0x80 | times.clamp(1, 0x7F),
+            Self::UntilAcknowledged => 0x80,
+        }
+    }
+}
+
+/// Common page 70 requesting a data page from a device, such as the manufacturer information
+/// or a page the device only sends on request. Sent as acknowledged data. Send it with
+/// Request::RequestPage to have the run loop retry until the page arrives.
+pub fn request_data_page(
+    channel_number: u8,
+    page_number: u8,
+    transmission: Transmission,
+) -> AcknowledgeDataMessage {
+    AcknowledgeDataMessage::new(
+        channel_number,
+        &[
+            COMMON_DATA_PAGE_70,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            transmission.encode(),
+            page_number,
+            REQUEST_DATA_PAGE,
+        ],
+    )
+}
+
+/// Page number of the data sent by a device of the device type. Heart rate monitors and bike
+/// speed and cadence sensors flip the top bit of the page number every four pages.
+pub fn page_number(device_type: u8, data: &[u8]) -> u8 {
+    if PAGE_TOGGLE_DEVICE_TYPES.contains(&device_type) {
+        data[0] & 0x7F
+    } else {
+        data[0]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_request_data_page() {
+        let mesg = request_data_page(2, 0x50, Transmission::Broadcast(4));
+        assert_eq!(mesg.channel(), 2);
+        assert_eq!(
+            mesg.data(),
+            [0x46, 0xFF, 0xFF, 0xFF, 0xFF, 0x04, 0x50, 0x01]
+        );
+        let mesg = request_data_page(2, 0x50, Transmission::Acknowledged(2));
+        assert_eq!(mesg.data()[5], 0x82);
+        let mesg = request_data_page(2, 0x50, Transmission::UntilAcknowledged);
+        assert_eq!(mesg.data()[5], 0x80);
+    }
+
+    #[test]
+    fn test_page_number() {
+        assert_eq!(page_number(0x78, &[0x84, 0xFF]), 0x04);
+        assert_eq!(page_number(0x7B, &[0x05, 0xFF]), 0x05);
+        assert_eq!(page_number(0x11, &[0x80, 0xFF]), 0x80);
+    }
+}
@@ -0,0 +1,547 @@
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52, Page0x56, PairedDevices};
+use crate::channel::Config;
+use crate::message::{bytes_to_u16, AcknowledgeDataMessage};
+
+// Constant values for the controls channel.
+const CONTROLS_DEVICE_TYPE: u8 = 0x10;
+const CONTROLS_FREQUENCY: u8 = 0x39;
+const CONTROLS_PERIOD: u16 = 8192;
+const AUDIO_COMMAND_PAGE: u8 = 0x10;
+const CONTROL_DEVICE_AVAILABILITY_PAGE: u8 = 0x02;
+const GENERIC_COMMAND_PAGE: u8 = 0x49;
+
+// AudioControls provides a way to decode the audio state broadcast by a controllable audio
+// device, such as a phone playing music, and to build the command pages a remote sends back to
+// control it.
+// Page 0x01 -> Audio Update Data
+// Page 0x10 -> Audio Command (command)
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+// Page 0x52 -> Battery Voltage
+// Page 0x56 -> Paired Devices
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AudioControls {
+    serial_number: u16,
+    manufacturer_id: u16,
+    sequence_number: u8,
+    page_0x01: Option<Page0x01>,
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+    paired_devices: PairedDevices,
+}
+
+impl AudioControls {
+    /// Create a new audio remote. The serial number and manufacturer ID of the remote are sent
+    /// with every command so the audio device can identify the remote.
+    pub fn new(serial_number: u16, manufacturer_id: u16) -> Self {
+        Self {
+            serial_number,
+            manufacturer_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(CONTROLS_DEVICE_TYPE)
+            .frequency(CONTROLS_FREQUENCY)
+            .period(CONTROLS_PERIOD)
+    }
+
+    /// Volume as a percentage. None if the audio device doesn't report volume.
+    pub fn volume(&self) -> Option<u8> {
+        match self.page_0x01 {
+            Some(page) if page.volume() <= 100 => Some(page.volume()),
+            _ => None,
+        }
+    }
+
+    /// Total time of the current track in seconds.
+    pub fn total_track_time(&self) -> Option<u16> {
+        match self.page_0x01 {
+            Some(page) if page.total_track_time() != 0xFFFF => Some(page.total_track_time()),
+            _ => None,
+        }
+    }
+
+    /// Elapsed time of the current track in seconds.
+    pub fn current_track_time(&self) -> Option<u16> {
+        match self.page_0x01 {
+            Some(page) if page.current_track_time() != 0xFFFF => Some(page.current_track_time()),
+            _ => None,
+        }
+    }
+
+    /// Current state of the audio device.
+    pub fn state(&self) -> Option<AudioState> {
+        self.page_0x01.map(|page| page.state())
+    }
+
+    /// Current repeat mode of the audio device.
+    pub fn repeat(&self) -> Option<RepeatMode> {
+        self.page_0x01.map(|page| page.repeat())
+    }
+
+    /// Current shuffle mode of the audio device.
+    pub fn shuffle(&self) -> Option<ShuffleMode> {
+        self.page_0x01.map(|page| page.shuffle())
+    }
+
+    /// Remotes and other peripheral devices the audio device is connected to.
+    pub fn paired_devices(&self) -> &PairedDevices {
+        &self.paired_devices
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.battery_status());
+        }
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.serial_number());
+        }
+        None
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.manufacturer());
+        }
+        None
+    }
+
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] {
+            0x01 => self.page_0x01 = Some(Page0x01(data)),
+            0x56 => self.paired_devices.update(&Page0x56(data)),
+            0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(data)),
+            0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(data)),
+            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            _ => {} // Do nothing with rest of pages for now.
+        }
+    }
+
+    pub fn play(&mut self, channel_number: u8) -> AcknowledgeDataMessage {
+        self.command(channel_number, AudioCommand::Play)
+    }
+
+    pub fn pause(&mut self, channel_number: u8) -> AcknowledgeDataMessage {
+        self.command(channel_number, AudioCommand::Pause)
+    }
+
+    pub fn volume_up(&mut self, channel_number: u8) -> AcknowledgeDataMessage {
+        self.command(channel_number, AudioCommand::VolumeUp)
+    }
+
+    pub fn volume_down(&mut self, channel_number: u8) -> AcknowledgeDataMessage {
+        self.command(channel_number, AudioCommand::VolumeDown)
+    }
+
+    pub fn next_track(&mut self, channel_number: u8) -> AcknowledgeDataMessage {
+        self.command(channel_number, AudioCommand::Ahead)
+    }
+
+    pub fn previous_track(&mut self, channel_number: u8) -> AcknowledgeDataMessage {
+        self.command(channel_number, AudioCommand::Back)
+    }
+
+    /// Sends an Acknowledge data page to the audio device with the specified command. The
+    /// sequence number is incremented with every command sent.
+    pub fn command(&mut self, channel_number: u8, command: AudioCommand) -> AcknowledgeDataMessage {
+        self.sequence_number = self.sequence_number.wrapping_add(1);
+        let command: u16 = command.into();
+        AcknowledgeDataMessage::new(
+            channel_number,
+            &[
+                AUDIO_COMMAND_PAGE,
+                (self.serial_number & 0xFF) as u8,
+                ((self.serial_number >> 8) & 0xFF) as u8,
+                (self.manufacturer_id & 0xFF) as u8,
+                ((self.manufacturer_id >> 8) & 0xFF) as u8,
+                self.sequence_number,
+                (command & 0xFF) as u8,
+                ((command >> 8) & 0xFF) as u8,
+            ],
+        )
+    }
+}
+
+// GenericControl allows an application to act as the controllable device for generic remotes
+// such as the Garmin Edge Remote. The controllable device broadcasts its availability and the
+// remote sends generic command pages back to it.
+// Page 0x02 -> Control Device Availability (broadcast by the controllable device)
+// Page 0x49 -> Generic Command (sent by the remote)
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenericControl {
+    last_page_0x49: Option<Page0x49>,
+    pending_command: Option<GenericCommand>,
+}
+
+impl GenericControl {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        AudioControls::channel_config()
+    }
+
+    /// Serial number of the remote that sent the last command.
+    pub fn remote_serial_number(&self) -> Option<u16> {
+        self.last_page_0x49.map(|page| page.serial_number())
+    }
+
+    /// Manufacturer of the remote that sent the last command.
+    pub fn remote_manufacturer(&self) -> Option<Manufacturer> {
+        self.last_page_0x49
+            .map(|page| Manufacturer::from(page.manufacturer_id()))
+    }
+
+    /// Last command received from the remote.
+    pub fn last_command(&self) -> Option<GenericCommand> {
+        self.last_page_0x49.map(|page| page.command())
+    }
+
+    /// Takes the command received from the remote that hasn't been handled yet. Remotes resend a
+    /// command until it has been acknowledged, so a command is only returned once per key press.
+    pub fn take_command(&mut self) -> Option<GenericCommand> {
+        self.pending_command.take()
+    }
+
+    pub fn decode(&mut self, data: [u8; 8]) {
+        if data[0] == GENERIC_COMMAND_PAGE {
+            let p = Page0x49(data);
+            // A new key press is signaled by a change in sequence number.
+            let is_new = match &self.last_page_0x49 {
+                Some(last_page) => last_page.sequence_number() != p.sequence_number(),
+                None => true,
+            };
+            if is_new && p.command() != GenericCommand::NoCommand {
+                self.pending_command = Some(p.command());
+            }
+            self.last_page_0x49 = Some(p);
+        }
+    }
+
+    /// The control device availability page to broadcast so remotes know generic commands are
+    /// supported.
+    pub fn availability_page(&self) -> [u8; 8] {
+        [
+            CONTROL_DEVICE_AVAILABILITY_PAGE,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            // Bit 4 signals generic control is supported.
+            0x10,
+        ]
+    }
+}
+
+/// Builds the generic command page sent from a remote to a controllable device. Useful for
+/// simulating a remote.
+pub fn generic_command(
+    channel: u8,
+    serial_number: u16,
+    manufacturer_id: u16,
+    sequence_number: u8,
+    command: GenericCommand,
+) -> AcknowledgeDataMessage {
+    let command: u16 = command.into();
+    AcknowledgeDataMessage::new(
+        channel,
+        &[
+            GENERIC_COMMAND_PAGE,
+            (serial_number & 0xFF) as u8,
+            ((serial_number >> 8) & 0xFF) as u8,
+            (manufacturer_id & 0xFF) as u8,
+            ((manufacturer_id >> 8) & 0xFF) as u8,
+            sequence_number,
+            (command & 0xFF) as u8,
+            ((command >> 8) & 0xFF) as u8,
+        ],
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GenericCommand {
+    MenuUp,
+    MenuDown,
+    MenuSelect,
+    MenuBack,
+    Home,
+    Start,
+    Stop,
+    Reset,
+    Length,
+    Lap,
+    Custom(u16),
+    NoCommand,
+}
+
+impl GenericCommand {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => Self::MenuUp,
+            1 => Self::MenuDown,
+            2 => Self::MenuSelect,
+            3 => Self::MenuBack,
+            4 => Self::Home,
+            5 => Self::Start,
+            6 => Self::Stop,
+            7 => Self::Reset,
+            8 => Self::Length,
+            9 => Self::Lap,
+            0xFFFF => Self::NoCommand,
+            v => Self::Custom(v),
+        }
+    }
+}
+
+impl From<GenericCommand> for u16 {
+    fn from(command: GenericCommand) -> u16 {
+        match command {
+            GenericCommand::MenuUp => 0,
+            GenericCommand::MenuDown => 1,
+            GenericCommand::MenuSelect => 2,
+            GenericCommand::MenuBack => 3,
+            GenericCommand::Home => 4,
+            GenericCommand::Start => 5,
+            GenericCommand::Stop => 6,
+            GenericCommand::Reset => 7,
+            GenericCommand::Length => 8,
+            GenericCommand::Lap => 9,
+            GenericCommand::Custom(v) => v,
+            GenericCommand::NoCommand => 0xFFFF,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AudioCommand {
+    Play,
+    Pause,
+    Stop,
+    VolumeUp,
+    VolumeDown,
+    MuteUnmute,
+    Ahead,
+    Back,
+    RepeatCurrentTrack,
+    RepeatAll,
+    RepeatOff,
+    ShuffleSongs,
+    ShuffleAlbums,
+    ShuffleOff,
+    FastForward,
+    Rewind,
+}
+
+impl From<AudioCommand> for u16 {
+    fn from(command: AudioCommand) -> u16 {
+        match command {
+            AudioCommand::Play => 0,
+            AudioCommand::Pause => 1,
+            AudioCommand::Stop => 2,
+            AudioCommand::VolumeUp => 3,
+            AudioCommand::VolumeDown => 4,
+            AudioCommand::MuteUnmute => 5,
+            AudioCommand::Ahead => 6,
+            AudioCommand::Back => 7,
+            AudioCommand::RepeatCurrentTrack => 8,
+            AudioCommand::RepeatAll => 9,
+            AudioCommand::RepeatOff => 10,
+            AudioCommand::ShuffleSongs => 11,
+            AudioCommand::ShuffleAlbums => 12,
+            AudioCommand::ShuffleOff => 13,
+            AudioCommand::FastForward => 14,
+            AudioCommand::Rewind => 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AudioState {
+    Off,
+    Play,
+    Pause,
+    Stop,
+    Busy,
+    Unknown,
+}
+
+impl AudioState {
+    fn from(value: u8) -> Self {
+        match value & 0x0F {
+            0 => Self::Off,
+            1 => Self::Play,
+            2 => Self::Pause,
+            3 => Self::Stop,
+            4 => Self::Busy,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RepeatMode {
+    Off,
+    CurrentTrack,
+    AllSongs,
+    Custom,
+}
+
+impl RepeatMode {
+    fn from(value: u8) -> Self {
+        match (value >> 4) & 0x03 {
+            0 => Self::Off,
+            1 => Self::CurrentTrack,
+            2 => Self::AllSongs,
+            _ => Self::Custom,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ShuffleMode {
+    Off,
+    Songs,
+    Albums,
+    Custom,
+}
+
+impl ShuffleMode {
+    fn from(value: u8) -> Self {
+        match (value >> 6) & 0x03 {
+            0 => Self::Off,
+            1 => Self::Songs,
+            2 => Self::Albums,
+            _ => Self::Custom,
+        }
+    }
+}
+
+// Audio Update Data Page
+// [1] - Volume, 0xFF if invalid
+// [2..4] - Total track time in seconds
+// [4..6] - Current track time in seconds
+// [6] - Audio state in bits 0-3, repeat in bits 4-5, shuffle in bits 6-7
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x01([u8; 8]);
+
+impl Page0x01 {
+    fn volume(&self) -> u8 {
+        self.0[1]
+    }
+
+    fn total_track_time(&self) -> u16 {
+        bytes_to_u16(&self.0[2..4])
+    }
+
+    fn current_track_time(&self) -> u16 {
+        bytes_to_u16(&self.0[4..6])
+    }
+
+    fn state(&self) -> AudioState {
+        AudioState::from(self.0[6])
+    }
+
+    fn repeat(&self) -> RepeatMode {
+        RepeatMode::from(self.0[6])
+    }
+
+    fn shuffle(&self) -> ShuffleMode {
+        ShuffleMode::from(self.0[6])
+    }
+}
+
+// Generic Command Page
+// [1..3] - Serial number of the remote
+// [3..5] - Manufacturer ID of the remote
+// [5] - Sequence number, incremented for each new command
+// [6..8] - Command number
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x49([u8; 8]);
+
+impl Page0x49 {
+    fn serial_number(&self) -> u16 {
+        bytes_to_u16(&self.0[1..3])
+    }
+
+    fn manufacturer_id(&self) -> u16 {
+        bytes_to_u16(&self.0[3..5])
+    }
+
+    fn sequence_number(&self) -> u8 {
+        self.0[5]
+    }
+
+    fn command(&self) -> GenericCommand {
+        GenericCommand::from(bytes_to_u16(&self.0[6..]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_audio_command() {
+        let mut audio = AudioControls::new(0x1234, 1);
+        let mesg = audio.play(2);
+        assert_eq!(mesg.channel(), 2);
+        assert_eq!(
+            mesg.data(),
+            [0x10, 0x34, 0x12, 0x01, 0x00, 0x01, 0x00, 0x00]
+        );
+        let mesg = audio.next_track(2);
+        assert_eq!(
+            mesg.data(),
+            [0x10, 0x34, 0x12, 0x01, 0x00, 0x02, 0x06, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_audio_decode_page0x01() {
+        let mut audio = AudioControls::new(0, 0);
+        audio.decode([0x01, 0x32, 0xB4, 0x00, 0x3C, 0x00, 0x61, 0xFF]);
+        assert_eq!(audio.volume(), Some(50));
+        assert_eq!(audio.total_track_time(), Some(180));
+        assert_eq!(audio.current_track_time(), Some(60));
+        assert_eq!(audio.state(), Some(AudioState::Play));
+        assert_eq!(audio.repeat(), Some(RepeatMode::AllSongs));
+        assert_eq!(audio.shuffle(), Some(ShuffleMode::Songs));
+    }
+
+    #[test]
+    fn test_generic_control_decode() {
+        let mut control = GenericControl::new();
+        let mesg = generic_command(0, 0x1234, 1, 1, GenericCommand::Lap);
+        let mut data = [0; 8];
+        data.copy_from_slice(mesg.data());
+        control.decode(data);
+        assert_eq!(control.remote_serial_number(), Some(0x1234));
+        assert_eq!(control.take_command(), Some(GenericCommand::Lap));
+        // A retransmission with the same sequence number is not a new key press.
+        control.decode(data);
+        assert_eq!(control.take_command(), None);
+        assert_eq!(control.last_command(), Some(GenericCommand::Lap));
+    }
+}
@@ -0,0 +1,160 @@
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52, Page0x54, Subfields};
+use crate::channel::Config;
+use crate::message::bytes_to_u16;
+
+// Constant values for the environment sensor channel. Sensors broadcast at 0.5Hz by default to
+// save power and can be asked to broadcast at 4Hz.
+const ENV_DEVICE_TYPE: u8 = 0x19;
+const ENV_FREQUENCY: u8 = 0x39;
+const ENV_HALF_HZ: u16 = 65535;
+const ENV_FOUR_HZ: u16 = 8192;
+
+// Environment provides a way to decode the temperature data sent from ANT+ environment sensors
+// such as the Garmin tempe.
+// Page 0x00 -> General Information
+// Page 0x01 -> Temperature
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+// Page 0x52 -> Battery Voltage
+// Page 0x54 -> Subfield Data
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Environment {
+    page_0x01: Option<Page0x01>,
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+    subfields: Subfields,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    /// Channel config using the low power 0.5Hz channel period.
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(ENV_DEVICE_TYPE)
+            .frequency(ENV_FREQUENCY)
+            .period(ENV_HALF_HZ)
+    }
+
+    /// Channel config using the 4Hz channel period for sensors configured to broadcast faster.
+    pub fn channel_config_4hz() -> Config {
+        Self::channel_config().period(ENV_FOUR_HZ)
+    }
+
+    /// Current temperature in degrees Celsius.
+    pub fn temperature(&self) -> Option<f32> {
+        self.page_0x01.map(|page| page.current_temperature())
+    }
+
+    /// Lowest temperature in the last 24 hours in degrees Celsius.
+    pub fn low_temperature(&self) -> Option<f32> {
+        self.page_0x01.and_then(|page| page.low_temperature())
+    }
+
+    /// Highest temperature in the last 24 hours in degrees Celsius.
+    pub fn high_temperature(&self) -> Option<f32> {
+        self.page_0x01.and_then(|page| page.high_temperature())
+    }
+
+    /// Temperature event count. Increments with each temperature measurement.
+    pub fn event_count(&self) -> Option<u8> {
+        self.page_0x01.map(|page| page.event_count())
+    }
+
+    /// Readings such as barometric pressure and humidity sent by sensors with more than a
+    /// temperature sensor.
+    pub fn subfields(&self) -> &Subfields {
+        &self.subfields
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.battery_status());
+        }
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.serial_number());
+        }
+        None
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.manufacturer());
+        }
+        None
+    }
+
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] {
+            0x01 => self.page_0x01 = Some(Page0x01(data)),
+            0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(data)),
+            0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(data)),
+            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            0x54 => self.subfields.update(&Page0x54(data)),
+            _ => {} // Do nothing with rest of pages for now.
+        }
+    }
+}
+
+// Temperature Page
+// [2] - Event count
+// [3..5] - 24 hour low, 12 bit signed in 0.1C
+// [4..6] - 24 hour high, 12 bit signed in 0.1C
+// [6..8] - Current temperature, signed in 0.01C
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x01([u8; 8]);
+
+impl Page0x01 {
+    fn event_count(&self) -> u8 {
+        self.0[2]
+    }
+
+    fn low_temperature(&self) -> Option<f32> {
+        let raw = bytes_to_u16(&self.0[3..5]) & 0x0FFF;
+        temperature_from_12_bits(raw)
+    }
+
+    fn high_temperature(&self) -> Option<f32> {
+        let raw = bytes_to_u16(&self.0[4..6]) >> 4;
+        temperature_from_12_bits(raw)
+    }
+
+    fn current_temperature(&self) -> f32 {
+        bytes_to_u16(&self.0[6..]) as i16 as f32 / 100_f32
+    }
+}
+
+// 0x800 signals an invalid value. Otherwise, sign extend the 12 bit value.
+fn temperature_from_12_bits(raw: u16) -> Option<f32> {
+    if raw == 0x800 {
+        return None;
+    }
+    Some(((raw << 4) as i16 >> 4) as f32 / 10_f32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_environment_decode_page0x01() {
+        let mut env = Environment::new();
+        // Low of -5.0C, high of 25.0C, current of 21.53C.
+        env.decode([0x01, 0xFF, 0x03, 0xCE, 0xAF, 0x0F, 0x69, 0x08]);
+        assert_eq!(env.event_count(), Some(3));
+        assert_eq!(env.low_temperature(), Some(-5.0));
+        assert_eq!(env.high_temperature(), Some(25.0));
+        assert_eq!(env.temperature(), Some(21.53));
+    }
+}
@@ -0,0 +1,881 @@
+use super::common::{self, Transmission};
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52, Page0x57};
+use crate::channel::Config;
+use crate::error::AntError;
+use crate::message::{bytes_to_u16, AcknowledgeDataMessage};
+use crate::Result;
+
+// Constant values for the Fitness Equipment channel.
+const FE_DEVICE_TYPE: u8 = 0x11;
+const FE_FREQUENCY: u8 = 0x39;
+const FE_PERIOD: u16 = 8192;
+const FE_BASIC_RESISTANCE_PAGE: u8 = 0x30;
+const FE_TARGET_POWER_PAGE: u8 = 0x31;
+const FE_WIND_RESISTANCE_PAGE: u8 = 0x32;
+const FE_TRACK_RESISTANCE_PAGE: u8 = 0x33;
+const FE_USER_CONFIGURATION_PAGE: u8 = 0x37;
+
+// FitnessEquipment provides a way to decode and use the broadcast data sent from fitness
+// equipment supporting FE-C such as smart trainers.
+// Page 0x10 -> General FE Data
+// Page 0x11 -> General Settings
+// Page 0x15 -> Stationary Bike Data
+// Page 0x19 -> Specific Trainer/Stationary Bike Data
+// Page 0x1A -> Specific Trainer Torque Data
+// Page 0x30 -> Basic Resistance (command)
+// Page 0x31 -> Target Power (command)
+// Page 0x32 -> Wind Resistance (command)
+// Page 0x33 -> Track Resistance (command)
+// Page 0x36 -> FE Capabilities
+// Page 0x37 -> User Configuration (command)
+// Page 0x46 -> Request Data Page (command)
+// Page 0x47 -> Command Status
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+// Page 0x52 -> Battery Voltage
+// Page 0x57 -> Error Description
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FitnessEquipment {
+    cadence: u8,
+    power: u16,
+    page_0x10: Option<Page0x10>,
+    page_0x11: Option<Page0x11>,
+    last_page_0x19: Option<Page0x19>,
+    page_0x1a: Option<Page0x1A>,
+    page_0x36: Option<Page0x36>,
+    page_0x47: Option<Page0x47>,
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+    page_0x57: Option<Page0x57>,
+}
+
+impl FitnessEquipment {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(FE_DEVICE_TYPE)
+            .frequency(FE_FREQUENCY)
+            .period(FE_PERIOD)
+    }
+
+    /// Type of fitness equipment reported on the general FE data page.
+    pub fn equipment_type(&self) -> Option<EquipmentType> {
+        self.page_0x10.map(|page| page.equipment_type())
+    }
+
+    /// Instantaneous speed in meters per second.
+    pub fn speed(&self) -> Option<f32> {
+        self.page_0x10.map(|page| page.speed() as f32 / 1000_f32)
+    }
+
+    /// Elapsed time in seconds. Rolls over every 64 seconds.
+    pub fn elapsed_time(&self) -> Option<f32> {
+        self.page_0x10
+            .map(|page| page.elapsed_time() as f32 / 4_f32)
+    }
+
+    /// Distance traveled in meters, if supported by the equipment. Rolls over every 256 meters.
+    pub fn distance(&self) -> Option<u8> {
+        match self.page_0x10 {
+            Some(page) if page.distance_enabled() => Some(page.distance()),
+            _ => None,
+        }
+    }
+
+    /// Heart rate reported by the equipment, if a heart rate source is available.
+    pub fn heartrate(&self) -> Option<u8> {
+        match self.page_0x10 {
+            Some(page) if page.heartrate() != 0xFF => Some(page.heartrate()),
+            _ => None,
+        }
+    }
+
+    /// Incline as a percentage, if supported by the equipment.
+    pub fn incline(&self) -> Option<f32> {
+        match self.page_0x11 {
+            Some(page) if page.incline() != 0x7FFF => Some(page.incline() as f32 / 100_f32),
+            _ => None,
+        }
+    }
+
+    /// Resistance level as a percentage of maximum resistance.
+    pub fn resistance(&self) -> Option<f32> {
+        self.page_0x11
+            .map(|page| page.resistance_level() as f32 / 2_f32)
+    }
+
+    /// Instantaneous cadence from page 0x19 or page 0x15.
+    pub fn cadence(&self) -> u8 {
+        self.cadence
+    }
+
+    /// Power is calculated from the previous and current 0x19 pages. Instantaneous power is
+    /// used until two pages have been received.
+    pub fn power(&self) -> u16 {
+        self.power
+    }
+
+    /// Trainer status bits from page 0x19 signaling if calibration or user configuration is
+    /// required.
+    pub fn trainer_status(&self) -> Option<TrainerStatus> {
+        self.last_page_0x19.map(|page| page.trainer_status())
+    }
+
+    /// Whether the trainer is operating at the requested target power.
+    pub fn target_power_status(&self) -> Option<TargetPowerStatus> {
+        self.last_page_0x19.map(|page| page.target_power_status())
+    }
+
+    /// Wheel period in seconds from the trainer torque data page.
+    pub fn wheel_period(&self) -> Option<f32> {
+        self.page_0x1a
+            .map(|page| page.wheel_period() as f32 / 2048_f32)
+    }
+
+    /// Accumulated torque in Nm from the trainer torque data page. Rolls over every 2048 Nm.
+    pub fn accumulated_torque(&self) -> Option<f32> {
+        self.page_0x1a
+            .map(|page| page.accumulated_torque() as f32 / 32_f32)
+    }
+
+    /// State of the fitness equipment sent on every FE specific page.
+    pub fn state(&self) -> Option<FeState> {
+        self.page_0x10.map(|page| FeState::from(page.0[7]))
+    }
+
+    /// Capabilities of the trainer. Not all trainers broadcast the capabilities page, so it may
+    /// need to be requested with request_capabilities().
+    pub fn capabilities(&self) -> Option<Capabilities> {
+        self.page_0x36.map(|page| page.capabilities())
+    }
+
+    /// Page number of the last control command received by the equipment.
+    pub fn last_command(&self) -> Option<u8> {
+        match self.page_0x47 {
+            Some(page) if page.last_command() != 0xFF => Some(page.last_command()),
+            _ => None,
+        }
+    }
+
+    /// Sequence number of the last control command received by the equipment.
+    pub fn command_sequence(&self) -> Option<u8> {
+        match self.page_0x47 {
+            Some(page) if page.sequence_number() != 0xFF => Some(page.sequence_number()),
+            _ => None,
+        }
+    }
+
+    /// Status of the last control command received by the equipment.
+    pub fn command_status(&self) -> Option<CommandStatus> {
+        self.page_0x47.map(|page| page.status())
+    }
+
+    /// Response data for the last control command. This is the data the equipment is using for
+    /// the command, i.e. the last four bytes of the target power page.
+    pub fn command_data(&self) -> Option<[u8; 4]> {
+        self.page_0x47.map(|page| page.data())
+    }
+
+    /// Last error reported by the equipment, such as the trainer overheating.
+    pub fn error(&self) -> Option<Page0x57> {
+        self.page_0x57
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.battery_status());
+        }
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.serial_number());
+        }
+        None
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.manufacturer());
+        }
+        None
+    }
+
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] {
+            0x10 => self.page_0x10 = Some(Page0x10(data)),
+            0x11 => self.page_0x11 = Some(Page0x11(data)),
+            0x15 => {
+                let p = Page0x15(data);
+                if p.cadence() != 0xFF {
+                    self.cadence = p.cadence();
+                }
+                if p.instantaneous_power() != 0xFFFF {
+                    self.power = p.instantaneous_power();
+                }
+            }
+            0x19 => {
+                let p = Page0x19(data);
+                if p.cadence() != 0xFF {
+                    self.cadence = p.cadence();
+                }
+                match &self.last_page_0x19 {
+                    // If there is a last page, then we can calculate average power from the
+                    // current page against the last page.
+                    Some(last_page) => {
+                        if *last_page == p {
+                            return;
+                        }
+                        let ec_delta = p.event_count().wrapping_sub(last_page.event_count());
+                        let accp_delta = p
+                            .accumulated_power()
+                            .wrapping_sub(last_page.accumulated_power());
+                        if ec_delta != 0 {
+                            self.power = (accp_delta as f32 / ec_delta as f32).round() as u16;
+                        }
+                    }
+                    None => {
+                        if p.instantaneous_power() != 0x0FFF {
+                            self.power = p.instantaneous_power();
+                        }
+                    }
+                }
+                self.last_page_0x19 = Some(p);
+            }
+            0x1A => self.page_0x1a = Some(Page0x1A(data)),
+            0x36 => self.page_0x36 = Some(Page0x36(data)),
+            0x47 => self.page_0x47 = Some(Page0x47(data)),
+            0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(data)),
+            0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(data)),
+            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            0x57 => self.page_0x57 = Some(Page0x57(data)),
+            _ => {} // Do nothing with rest of pages for now.
+        }
+    }
+
+    /// Sends an Acknowledge data page to the trainer setting the resistance as a percentage of
+    /// the maximum resistance.
+    pub fn set_basic_resistance(
+        &self,
+        channel_number: u8,
+        resistance: f32,
+    ) -> Result<AcknowledgeDataMessage> {
+        self.check_supported(FE_BASIC_RESISTANCE_PAGE, |c| c.basic_resistance)?;
+        let resistance = (resistance.clamp(0_f32, 100_f32) * 2_f32).round() as u8;
+        Ok(AcknowledgeDataMessage::new(
+            channel_number,
+            &[
+                FE_BASIC_RESISTANCE_PAGE,
+                0xFF,
+                0xFF,
+                0xFF,
+                0xFF,
+                0xFF,
+                0xFF,
+                resistance,
+            ],
+        ))
+    }
+
+    /// Sends an Acknowledge data page to the trainer setting the target power in watts for ERG
+    /// mode.
+    pub fn set_target_power(
+        &self,
+        channel_number: u8,
+        power: f32,
+    ) -> Result<AcknowledgeDataMessage> {
+        self.check_supported(FE_TARGET_POWER_PAGE, |c| c.target_power)?;
+        let power = (power.clamp(0_f32, 4000_f32) * 4_f32).round() as u16;
+        Ok(AcknowledgeDataMessage::new(
+            channel_number,
+            &[
+                FE_TARGET_POWER_PAGE,
+                0xFF,
+                0xFF,
+                0xFF,
+                0xFF,
+                0xFF,
+                (power & 0xFF) as u8,
+                (power >> 8) as u8,
+            ],
+        ))
+    }
+
+    /// Sends an Acknowledge data page to the trainer setting the wind resistance for simulation
+    /// mode. The wind resistance coefficient is in kg/m, wind speed is in km/h with negative
+    /// values being a tailwind, and the drafting factor is 0.0 - 1.0 with 1.0 being no drafting.
+    pub fn set_wind_resistance(
+        &self,
+        channel_number: u8,
+        coefficient: f32,
+        wind_speed: i8,
+        drafting_factor: f32,
+    ) -> Result<AcknowledgeDataMessage> {
+        self.check_supported(FE_WIND_RESISTANCE_PAGE, |c| c.simulation)?;
+        let coefficient = (coefficient.clamp(0_f32, 1.86_f32) * 100_f32).round() as u8;
+        let wind_speed = (wind_speed.max(-127) as i16 + 127) as u8;
+        let drafting_factor = (drafting_factor.clamp(0_f32, 1_f32) * 100_f32).round() as u8;
+        Ok(AcknowledgeDataMessage::new(
+            channel_number,
+            &[
+                FE_WIND_RESISTANCE_PAGE,
+                0xFF,
+                0xFF,
+                0xFF,
+                0xFF,
+                coefficient,
+                wind_speed,
+                drafting_factor,
+            ],
+        ))
+    }
+
+    /// Sends an Acknowledge data page to the trainer setting the grade as a percentage and the
+    /// coefficient of rolling resistance for simulation mode.
+    pub fn set_track_resistance(
+        &self,
+        channel_number: u8,
+        grade: f32,
+        rolling_resistance: f32,
+    ) -> Result<AcknowledgeDataMessage> {
+        self.check_supported(FE_TRACK_RESISTANCE_PAGE, |c| c.simulation)?;
+        // Grade is sent in 0.01% with an offset of -200%.
+        let grade = ((grade.clamp(-200_f32, 200_f32) + 200_f32) * 100_f32).round() as u16;
+        // Rolling resistance is sent in 5x10^-5 units.
+        let rolling_resistance =
+            (rolling_resistance.clamp(0_f32, 0.0127_f32) / 0.00005_f32).round() as u8;
+        Ok(AcknowledgeDataMessage::new(
+            channel_number,
+            &[
+                FE_TRACK_RESISTANCE_PAGE,
+                0xFF,
+                0xFF,
+                0xFF,
+                0xFF,
+                (grade & 0xFF) as u8,
+                (grade >> 8) as u8,
+                rolling_resistance,
+            ],
+        ))
+    }
+
+    /// Sends an Acknowledge data page to the trainer with the user configuration. Trainers use
+    /// the rider and bike weight along with the wheel size to calculate resistance in
+    /// simulation mode.
+    pub fn set_user_configuration(
+        &self,
+        channel_number: u8,
+        config: &UserConfiguration,
+    ) -> AcknowledgeDataMessage {
+        AcknowledgeDataMessage::new(channel_number, &config.encode())
+    }
+
+    /// Sends an Acknowledge data page to the equipment requesting the command status page to
+    /// confirm the last control command was received.
+    pub fn request_command_status(&self, channel_number: u8) -> AcknowledgeDataMessage {
+        common::request_data_page(channel_number, 0x47, Transmission::Broadcast(1))
+    }
+
+    /// Sends an Acknowledge data page to the equipment requesting the capabilities page.
+    pub fn request_capabilities(&self, channel_number: u8) -> AcknowledgeDataMessage {
+        common::request_data_page(channel_number, 0x36, Transmission::Broadcast(1))
+    }
+
+    // Control commands are rejected if the trainer has reported it does not support the mode
+    // for the command. If the capabilities are not known, the command is sent.
+    fn check_supported(&self, page_number: u8, supported: fn(&Capabilities) -> bool) -> Result<()> {
+        match self.capabilities() {
+            Some(capabilities) if !supported(&capabilities) => {
+                Err(AntError::UnsupportedCommand(page_number))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Capabilities reported by the trainer on the FE capabilities page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capabilities {
+    /// Maximum resistance in Newtons, if reported.
+    pub max_resistance: Option<u16>,
+    pub basic_resistance: bool,
+    pub target_power: bool,
+    pub simulation: bool,
+}
+
+/// User configuration sent to the trainer. Fields not set are sent as invalid so the trainer
+/// uses its own defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UserConfiguration {
+    user_weight: Option<f32>,
+    bike_weight: Option<f32>,
+    wheel_diameter: Option<f32>,
+    gear_ratio: Option<f32>,
+}
+
+impl UserConfiguration {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    /// User weight in kg.
+    pub fn user_weight(mut self, user_weight: f32) -> Self {
+        self.user_weight = Some(user_weight);
+        self
+    }
+
+    /// Bike weight in kg.
+    pub fn bike_weight(mut self, bike_weight: f32) -> Self {
+        self.bike_weight = Some(bike_weight);
+        self
+    }
+
+    /// Wheel diameter in meters.
+    pub fn wheel_diameter(mut self, wheel_diameter: f32) -> Self {
+        self.wheel_diameter = Some(wheel_diameter);
+        self
+    }
+
+    /// Gear ratio as front chainring teeth / rear cog teeth.
+    pub fn gear_ratio(mut self, gear_ratio: f32) -> Self {
+        self.gear_ratio = Some(gear_ratio);
+        self
+    }
+
+    // User Configuration Page
+    // [1..3] - User weight in 0.01kg, 0xFFFF if invalid
+    // [3] - Reserved
+    // [4] - Wheel diameter offset in mm in bits 0-3, 0xF if invalid. Bike weight LSN in
+    //       bits 4-7
+    // [5] - Bike weight MSB. Bike weight is 12 bits in 0.05kg, 0xFFF if invalid
+    // [6] - Wheel diameter in 0.01m, 0xFF if invalid
+    // [7] - Gear ratio in 0.03, 0x00 if invalid
+    fn encode(&self) -> [u8; 8] {
+        let user_weight = self
+            .user_weight
+            .map(|w| (w.clamp(0_f32, 655.34_f32) * 100_f32).round() as u16)
+            .unwrap_or(0xFFFF);
+        let bike_weight = self
+            .bike_weight
+            .map(|w| (w.clamp(0_f32, 50_f32) * 20_f32).round() as u16)
+            .unwrap_or(0xFFF);
+        let (wheel_diameter, wheel_offset) = match self.wheel_diameter {
+            Some(d) => {
+                let mm = (d.clamp(0_f32, 2.54_f32) * 1000_f32).round() as u16;
+                ((mm / 10) as u8, (mm % 10) as u8)
+            }
+            None => (0xFF, 0x0F),
+        };
+        let gear_ratio = self
+            .gear_ratio
+            .map(|r| (r.clamp(0.03_f32, 7.65_f32) / 0.03_f32).round() as u8)
+            .unwrap_or(0x00);
+        [
+            FE_USER_CONFIGURATION_PAGE,
+            (user_weight & 0xFF) as u8,
+            (user_weight >> 8) as u8,
+            0xFF,
+            wheel_offset | ((bike_weight & 0x0F) << 4) as u8,
+            (bike_weight >> 4) as u8,
+            wheel_diameter,
+            gear_ratio,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EquipmentType {
+    General,
+    Treadmill,
+    Elliptical,
+    Rower,
+    Climber,
+    NordicSkier,
+    Trainer,
+    Unknown(u8),
+}
+
+impl EquipmentType {
+    fn from(value: u8) -> Self {
+        match value & 0x1F {
+            16 => Self::General,
+            19 => Self::Treadmill,
+            20 => Self::Elliptical,
+            22 => Self::Rower,
+            23 => Self::Climber,
+            24 => Self::NordicSkier,
+            25 => Self::Trainer,
+            v => Self::Unknown(v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FeState {
+    Reserved,
+    AsleepOff,
+    Ready,
+    InUse,
+    Finished,
+}
+
+impl FeState {
+    // FE state is stored in bits 4-6 of the last byte of every FE specific page.
+    fn from(value: u8) -> Self {
+        match (value >> 4) & 0x07 {
+            1 => Self::AsleepOff,
+            2 => Self::Ready,
+            3 => Self::InUse,
+            4 => Self::Finished,
+            _ => Self::Reserved,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrainerStatus {
+    pub power_calibration_required: bool,
+    pub resistance_calibration_required: bool,
+    pub user_configuration_required: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TargetPowerStatus {
+    AtTarget,
+    SpeedTooLow,
+    SpeedTooHigh,
+    Undetermined,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CommandStatus {
+    Pass,
+    Fail,
+    NotSupported,
+    Rejected,
+    Pending,
+    Uninitialized,
+    Reserved(u8),
+}
+
+impl CommandStatus {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Pass,
+            1 => Self::Fail,
+            2 => Self::NotSupported,
+            3 => Self::Rejected,
+            4 => Self::Pending,
+            0xFF => Self::Uninitialized,
+            v => Self::Reserved(v),
+        }
+    }
+}
+
+// General FE Data Page
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x10([u8; 8]);
+
+impl Page0x10 {
+    fn equipment_type(&self) -> EquipmentType {
+        EquipmentType::from(self.0[1])
+    }
+
+    fn elapsed_time(&self) -> u8 {
+        self.0[2]
+    }
+
+    fn distance(&self) -> u8 {
+        self.0[3]
+    }
+
+    fn speed(&self) -> u16 {
+        bytes_to_u16(&self.0[4..6])
+    }
+
+    fn heartrate(&self) -> u8 {
+        self.0[6]
+    }
+
+    fn distance_enabled(&self) -> bool {
+        self.0[7] & 0x04 == 0x04
+    }
+}
+
+// General Settings Page
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x11([u8; 8]);
+
+impl Page0x11 {
+    fn cycle_length(&self) -> u8 {
+        self.0[3]
+    }
+
+    fn incline(&self) -> i16 {
+        bytes_to_u16(&self.0[4..6]) as i16
+    }
+
+    fn resistance_level(&self) -> u8 {
+        self.0[6]
+    }
+}
+
+// Stationary Bike Data Page
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x15([u8; 8]);
+
+impl Page0x15 {
+    fn cadence(&self) -> u8 {
+        self.0[4]
+    }
+
+    fn instantaneous_power(&self) -> u16 {
+        bytes_to_u16(&self.0[5..7])
+    }
+}
+
+// Specific Trainer/Stationary Bike Data Page
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x19([u8; 8]);
+
+impl Page0x19 {
+    fn event_count(&self) -> u8 {
+        self.0[1]
+    }
+
+    fn cadence(&self) -> u8 {
+        self.0[2]
+    }
+
+    fn accumulated_power(&self) -> u16 {
+        bytes_to_u16(&self.0[3..5])
+    }
+
+    // Instantaneous power is 1.5 bytes with the MSN of byte 6 being trainer status bits.
+    fn instantaneous_power(&self) -> u16 {
+        bytes_to_u16(&self.0[5..7]) & 0x0FFF
+    }
+
+    fn trainer_status(&self) -> TrainerStatus {
+        let bits = self.0[6] >> 4;
+        TrainerStatus {
+            power_calibration_required: bits & 0x01 == 0x01,
+            resistance_calibration_required: bits & 0x02 == 0x02,
+            user_configuration_required: bits & 0x04 == 0x04,
+        }
+    }
+
+    fn target_power_status(&self) -> TargetPowerStatus {
+        match self.0[7] & 0x03 {
+            0 => TargetPowerStatus::AtTarget,
+            1 => TargetPowerStatus::SpeedTooLow,
+            2 => TargetPowerStatus::SpeedTooHigh,
+            _ => TargetPowerStatus::Undetermined,
+        }
+    }
+}
+
+// Specific Trainer Torque Data Page
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x1A([u8; 8]);
+
+impl Page0x1A {
+    fn event_count(&self) -> u8 {
+        self.0[1]
+    }
+
+    fn wheel_ticks(&self) -> u8 {
+        self.0[2]
+    }
+
+    fn wheel_period(&self) -> u16 {
+        bytes_to_u16(&self.0[3..5])
+    }
+
+    fn accumulated_torque(&self) -> u16 {
+        bytes_to_u16(&self.0[5..7])
+    }
+}
+
+// FE Capabilities Page
+// [5..7] - Maximum resistance in Newtons, 0xFFFF if invalid
+// [7] - Capabilities bit field. Bit 0 basic resistance, bit 1 target power, bit 2 simulation
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x36([u8; 8]);
+
+impl Page0x36 {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_resistance: match bytes_to_u16(&self.0[5..7]) {
+                0xFFFF => None,
+                v => Some(v),
+            },
+            basic_resistance: self.0[7] & 0x01 == 0x01,
+            target_power: self.0[7] & 0x02 == 0x02,
+            simulation: self.0[7] & 0x04 == 0x04,
+        }
+    }
+}
+
+// Command Status Page
+// [1] - Last received command page, 0xFF if none received
+// [2] - Sequence number of the last received command, 0xFF if none received
+// [3] - Command status
+// [4..8] - Response data of the last received command
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x47([u8; 8]);
+
+impl Page0x47 {
+    fn last_command(&self) -> u8 {
+        self.0[1]
+    }
+
+    fn sequence_number(&self) -> u8 {
+        self.0[2]
+    }
+
+    fn status(&self) -> CommandStatus {
+        CommandStatus::from(self.0[3])
+    }
+
+    fn data(&self) -> [u8; 4] {
+        [self.0[4], self.0[5], self.0[6], self.0[7]]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fec_decode_page0x10() {
+        let mut fe = FitnessEquipment::new();
+        fe.decode([0x10, 0x19, 0x28, 0x64, 0x88, 0x13, 0x8c, 0x34]);
+        assert_eq!(fe.equipment_type(), Some(EquipmentType::Trainer));
+        assert_eq!(fe.elapsed_time(), Some(10.0));
+        assert_eq!(fe.distance(), Some(100));
+        assert_eq!(fe.speed(), Some(5.0));
+        assert_eq!(fe.heartrate(), Some(140));
+        assert_eq!(fe.state(), Some(FeState::InUse));
+    }
+
+    #[test]
+    fn test_fec_decode_page0x19() {
+        let mut fe = FitnessEquipment::new();
+        let page1: [u8; 8] = [0x19, 0x01, 0x5a, 0xc8, 0x00, 0xc8, 0x10, 0x30];
+        fe.decode(page1);
+        assert_eq!(fe.cadence(), 90);
+        assert_eq!(fe.power(), 200);
+        assert_eq!(
+            fe.trainer_status(),
+            Some(TrainerStatus {
+                power_calibration_required: true,
+                resistance_calibration_required: false,
+                user_configuration_required: false,
+            })
+        );
+        assert_eq!(fe.target_power_status(), Some(TargetPowerStatus::AtTarget));
+        let page2: [u8; 8] = [0x19, 0x03, 0x5b, 0x1c, 0x02, 0xd2, 0x00, 0x30];
+        fe.decode(page2);
+        assert_eq!(fe.cadence(), 91);
+        // (0x021c - 0x00c8) / 2 events
+        assert_eq!(fe.power(), 170);
+    }
+
+    #[test]
+    fn test_fec_decode_page0x47() {
+        let mut fe = FitnessEquipment::new();
+        assert_eq!(fe.command_status(), None);
+        fe.decode([0x47, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(fe.last_command(), None);
+        assert_eq!(fe.command_status(), Some(CommandStatus::Uninitialized));
+        fe.decode([0x47, 0x31, 0x05, 0x00, 0xFF, 0xFF, 0xe8, 0x03]);
+        assert_eq!(fe.last_command(), Some(0x31));
+        assert_eq!(fe.command_sequence(), Some(5));
+        assert_eq!(fe.command_status(), Some(CommandStatus::Pass));
+        assert_eq!(fe.command_data(), Some([0xFF, 0xFF, 0xe8, 0x03]));
+    }
+
+    #[test]
+    fn test_fec_control_pages() {
+        let fe = FitnessEquipment::new();
+        let mesg = fe.set_basic_resistance(1, 25.5).unwrap();
+        assert_eq!(mesg.channel(), 1);
+        assert_eq!(mesg.data(), [0x30, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 51]);
+        // 250W in 0.25W units
+        let mesg = fe.set_target_power(1, 250.0).unwrap();
+        assert_eq!(
+            mesg.data(),
+            [0x31, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xe8, 0x03]
+        );
+        let mesg = fe.set_wind_resistance(1, 0.51, -10, 1.0).unwrap();
+        assert_eq!(mesg.data(), [0x32, 0xFF, 0xFF, 0xFF, 0xFF, 51, 117, 100]);
+        // 5% grade is (5 + 200) / 0.01 = 20500
+        let mesg = fe.set_track_resistance(1, 5.0, 0.004).unwrap();
+        assert_eq!(mesg.data(), [0x33, 0xFF, 0xFF, 0xFF, 0xFF, 0x14, 0x50, 80]);
+    }
+
+    #[test]
+    fn test_fec_capabilities() {
+        let mut fe = FitnessEquipment::new();
+        // Target power and simulation only
+        fe.decode([0x36, 0xFF, 0xFF, 0xFF, 0xFF, 0xd0, 0x07, 0x06]);
+        assert_eq!(
+            fe.capabilities(),
+            Some(Capabilities {
+                max_resistance: Some(2000),
+                basic_resistance: false,
+                target_power: true,
+                simulation: true,
+            })
+        );
+        assert!(matches!(
+            fe.set_basic_resistance(1, 10.0),
+            Err(AntError::UnsupportedCommand(0x30))
+        ));
+        assert!(fe.set_target_power(1, 200.0).is_ok());
+        assert!(fe.set_track_resistance(1, 2.0, 0.004).is_ok());
+    }
+
+    #[test]
+    fn test_fec_user_configuration() {
+        let fe = FitnessEquipment::new();
+        let config = UserConfiguration::new()
+            .user_weight(75.0)
+            .bike_weight(9.0)
+            .wheel_diameter(0.622)
+            .gear_ratio(2.0);
+        let mesg = fe.set_user_configuration(1, &config);
+        // 7500 = 0x1d4c, 180 = 0x0b4, 622mm = 62 * 10 + 2, 2.0 / 0.03 = 67
+        assert_eq!(mesg.data(), [0x37, 0x4c, 0x1d, 0xFF, 0x42, 0x0b, 62, 67]);
+        let mesg = fe.set_user_configuration(1, &UserConfiguration::new());
+        assert_eq!(
+            mesg.data(),
+            [0x37, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00]
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use super::{BatteryStatus, Manufacturer};
+use std::collections::VecDeque;
+
+use super::common::{self, Transmission};
+use super::{BatteryStatus, Manufacturer, PageScheduler};
 use crate::channel::Config;
 /// Heartrate Monitor device. Each data page contains HR data. Legacy devices
 /// only have a data page 0. Newer devices have multiple pages with a MSB bit
@@ -11,14 +14,37 @@ const HRM_DEVICE_TYPE: u8 = 0x78;
 const HRM_FREQUENCY: u8 = 0x39;
 const HRM_PERIOD: u16 = 8070;
 const HRM_TIMEOUT: u8 = 10;
-const COMMON_DATA_PAGE_70: u8 = 0x46;
+const HRM_TRANSMISSION_TYPE: u8 = 0x01;
+// Heart beat event time is in 1/1024s and the channel period is in 1/32768s.
+const HRM_PERIOD_EVENT_TIME: f64 = HRM_PERIOD as f64 / 32.0;
+// Main data pages sent before the background pages are interleaved.
+const HRM_MAIN_PAGE_MESSAGES: u32 = 64;
+// Each page is sent for four messages before the toggle bit flips.
+const HRM_TOGGLE_MESSAGES: u32 = 4;
+// RR intervals kept for HRV, about five minutes of beats at rest.
+const HRM_RR_INTERVALS: usize = 512;
+// Messages received without the toggle bit flipping before a device is treated as legacy, twice
+// the toggle period to allow for missed messages.
+const HRM_LEGACY_MESSAGES: u32 = 2 * HRM_TOGGLE_MESSAGES;
 
 // TODO Split out channel config from device broadcast data
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeartRateMonitor {
     heartrate: u8,
-    last_heartbeat_event: f32,
+    // Toggle bit of the first message and the messages received with it until the device is
+    // known to be modern.
+    page_toggle: Option<u8>,
+    toggle_messages: u32,
+    legacy: Option<bool>,
+    // Messages received before the device is known to be legacy or not. Their data pages are
+    // decoded once the device is known to be modern.
+    undetected_pages: Vec<[u8; 8]>,
+    // Time of the last heart beat event in 1/1024s.
+    heartbeat_event_time: Option<u16>,
     heartbeat_count: u8,
+    // RR intervals in ms, oldest first.
+    rr_intervals: VecDeque<f32>,
     operating_time: u32,
     manufacturer_id: u8,
     serial_number: u16,
@@ -28,6 +54,10 @@ pub struct HeartRateMonitor {
     battery_level: u8,
     fractional_battery_voltage: u8,
     descriptive_bit_field: u8,
+    // Swim interval summary from data page 5.
+    interval_average_heartrate: u8,
+    interval_max_heartrate: u8,
+    session_average_heartrate: u8,
 }
 
 impl HeartRateMonitor {
@@ -50,6 +80,71 @@ impl HeartRateMonitor {
         self.heartrate
     }
 
+    /// Legacy devices only send data page 0 without the page toggle bit, so byte 0 is not a
+    /// page number. True once the toggle bit has not flipped over the first eight messages, until
+    /// the toggle bit flips. Data pages received before a device is known to be modern are
+    /// decoded once it is.
+    pub fn is_legacy(&self) -> bool {
+        self.legacy == Some(true)
+    }
+
+    /// RR intervals in ms between the heart beats received, oldest first. Intervals are only
+    /// known when consecutive beats are received, from the beat count incrementing by one or
+    /// from the previous heart beat event time in data page 4. The last 512 intervals are kept.
+    pub fn rr_intervals(&self) -> impl Iterator<Item = f32> + '_ {
+        self.rr_intervals.iter().copied()
+    }
+
+    /// Clears the RR intervals, such as at the start of a new HRV measurement.
+    pub fn clear_rr_intervals(&mut self) {
+        self.rr_intervals.clear();
+    }
+
+    /// Root mean square of successive differences between RR intervals in ms. None until
+    /// three RR intervals are received.
+    pub fn rmssd(&self) -> Option<f32> {
+        if self.rr_intervals.len() < 3 {
+            return None;
+        }
+        let intervals = self
+            .rr_intervals
+            .iter()
+            .zip(self.rr_intervals.iter().skip(1));
+        let sum: f32 = intervals.map(|(a, b)| (b - a) * (b - a)).sum();
+        Some((sum / (self.rr_intervals.len() - 1) as f32).sqrt())
+    }
+
+    /// Standard deviation of the RR intervals in ms. None until two RR intervals are received.
+    pub fn sdnn(&self) -> Option<f32> {
+        if self.rr_intervals.len() < 2 {
+            return None;
+        }
+        let count = self.rr_intervals.len() as f32;
+        let mean = self.rr_intervals.iter().sum::<f32>() / count;
+        let sum: f32 = self
+            .rr_intervals
+            .iter()
+            .map(|rr| (rr - mean) * (rr - mean))
+            .sum();
+        Some((sum / (count - 1.0)).sqrt())
+    }
+
+    /// Average heartrate over the current or last swim interval. None until data page 5 is
+    /// received or if the device has no interval.
+    pub fn interval_average_heartrate(&self) -> Option<u8> {
+        valid_heartrate(self.interval_average_heartrate)
+    }
+
+    /// Maximum heartrate over the current or last swim interval.
+    pub fn interval_max_heartrate(&self) -> Option<u8> {
+        valid_heartrate(self.interval_max_heartrate)
+    }
+
+    /// Average heartrate over the swim session.
+    pub fn session_average_heartrate(&self) -> Option<u8> {
+        valid_heartrate(self.session_average_heartrate)
+    }
+
     /// Manufacturer of the hardware device
     /// HRM devices use a u8 field while other devices use u16.
     pub fn manufacturer(&self) -> Manufacturer {
@@ -101,67 +196,370 @@ impl HeartRateMonitor {
     pub fn decode_broadcast_data(&mut self, data: &[u8]) {
         // Check length of slice. Discard for now if not 9.
         if data.len() == 8 {
-            match data[0] {
-                // Data page 0 Default or unknown data page (legacy)
-                0x00 | 0x80 => {}
-                // Data page 1 Cumulative Operating Time
-                0x01 | 0x81 => self.operating_time = bytes_to_u32(&data[1..4]),
-                // Data page 2 Manufacturer Information
-                0x02 | 0x82 => {
-                    self.manufacturer_id = data[1];
-                    self.serial_number = bytes_to_u16(&data[2..4]);
-                }
-                // Data page 3 Product Information
-                0x03 | 0x83 => {
-                    self.hardware_version = data[1];
-                    self.software_version = data[2];
-                    self.model_number = data[3];
+            self.detect_legacy(data[0] & 0x80);
+            let page = match self.legacy {
+                Some(false) => data[0] & 0x7F,
+                Some(true) => 0x00,
+                None => {
+                    let mut page = [0; 8];
+                    page.copy_from_slice(data);
+                    self.undetected_pages.push(page);
+                    0x00
                 }
-                // Data page 4 Previous Heart Beat
-                0x04 | 0x84 => {}
-                // Data page 5 Swim Interval Summary
-                0x05 | 0x85 => {}
-                // Data page 6 Capabilities
-                0x06 | 0x86 => {}
-                // Data page 7 Battery Status
-                0x07 | 0x87 => {
-                    self.battery_level = data[1];
-                    self.fractional_battery_voltage = data[2];
-                    self.descriptive_bit_field = data[3];
+            };
+            if !self.decode_page(page, data) {
+                return; //Drop message if none of these pages
+            }
+            let event_time = bytes_to_u16(&data[4..6]);
+            let beats = data[6].wrapping_sub(self.heartbeat_count);
+            if beats > 0 {
+                let previous_event_time = match page {
+                    0x04 => Some(bytes_to_u16(&data[2..4])),
+                    _ if beats == 1 => self.heartbeat_event_time,
+                    _ => None,
+                };
+                if let Some(previous_event_time) = previous_event_time {
+                    self.push_rr_interval(event_time.wrapping_sub(previous_event_time));
                 }
-                _ => return, //Drop message if none of these pages
             }
-            self.last_heartbeat_event = bytes_to_u16(&data[4..6]) as f32 / 1000_f32;
+            self.heartbeat_event_time = Some(event_time);
             self.heartbeat_count = data[6];
             self.heartrate = data[7];
         }
     }
 
+    // Decodes the data specific to a data page, returning false for an unknown data page.
+    fn decode_page(&mut self, page: u8, data: &[u8]) -> bool {
+        match page {
+            // Data page 0 Default or unknown data page (legacy)
+            0x00 => {}
+            // Data page 1 Cumulative Operating Time
+            0x01 => self.operating_time = bytes_to_u32(&data[1..4]),
+            // Data page 2 Manufacturer Information
+            0x02 => {
+                self.manufacturer_id = data[1];
+                self.serial_number = bytes_to_u16(&data[2..4]);
+            }
+            // Data page 3 Product Information
+            0x03 => {
+                self.hardware_version = data[1];
+                self.software_version = data[2];
+                self.model_number = data[3];
+            }
+            // Data page 4 Previous Heart Beat
+            0x04 => {}
+            // Data page 5 Swim Interval Summary
+            0x05 => {
+                self.interval_average_heartrate = data[1];
+                self.interval_max_heartrate = data[2];
+                self.session_average_heartrate = data[3];
+            }
+            // Data page 6 Capabilities
+            0x06 => {}
+            // Data page 7 Battery Status
+            0x07 => {
+                self.battery_level = data[1];
+                self.fractional_battery_voltage = data[2];
+                self.descriptive_bit_field = data[3];
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    // Modern devices flip the toggle bit every four messages. A device taken as legacy is
+    // taken as modern if the toggle bit flips later, such as after messages were missed.
+    fn detect_legacy(&mut self, toggle: u8) {
+        if self.legacy == Some(false) {
+            return;
+        }
+        match self.page_toggle {
+            Some(page_toggle) if page_toggle != toggle => {
+                self.legacy = Some(false);
+                for page in std::mem::take(&mut self.undetected_pages) {
+                    self.decode_page(page[0] & 0x7F, &page);
+                }
+            }
+            Some(_) if self.legacy.is_some() => {}
+            Some(_) => {
+                self.toggle_messages += 1;
+                if self.toggle_messages >= HRM_LEGACY_MESSAGES {
+                    self.legacy = Some(true);
+                    self.undetected_pages.clear();
+                }
+            }
+            None => {
+                self.page_toggle = Some(toggle);
+                self.toggle_messages = 1;
+            }
+        }
+    }
+
+    // Interval between heart beat events in 1/1024s.
+    fn push_rr_interval(&mut self, interval: u16) {
+        if interval == 0 {
+            return;
+        }
+        if self.rr_intervals.len() == HRM_RR_INTERVALS {
+            self.rr_intervals.pop_front();
+        }
+        self.rr_intervals
+            .push_back(interval as f32 * 1000.0 / 1024.0);
+    }
+
     /// Sends an Acknowledge data page to the heart rate monitor requesting
     /// the manufacturer information.
     pub fn request_manufacturer_info(&self, channel_number: u8) -> AcknowledgeDataMessage {
-        AcknowledgeDataMessage::new(channel_number, &self.request_data_page(0x02))
+        common::request_data_page(channel_number, 0x02, Transmission::Broadcast(1))
     }
 
     /// Send an Acknowledge data page to the heart rate monitor requesting
     /// the battery status for the heart rate monitor.
     pub fn request_battery_status(&self, channel_number: u8) -> AcknowledgeDataMessage {
-        AcknowledgeDataMessage::new(channel_number, &self.request_data_page(0x07))
-    }
-
-    /// The general acknowledge data page to send to the heart rate monitor
-    /// requesting a specific page to be sent back to the device.
-    fn request_data_page(&self, page_number: u8) -> [u8; 8] {
-        [
-            COMMON_DATA_PAGE_70,
-            0xFF,
-            0xFF,
-            0xFF,
-            0xFF,
-            0x01,
-            page_number,
-            0x01,
-        ]
+        common::request_data_page(channel_number, 0x07, Transmission::Broadcast(1))
+    }
+}
+
+/// Heartrate Monitor broadcaster. Encodes heart rate data pages from a heartrate supplied by the
+/// application so the data can be transmitted on a master channel, i.e. re-broadcasting a
+/// heartrate from another source to a head unit. Call next_page() each time an EventTx is
+/// received for the channel and send the page with Request::SendBroadcast.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeartRateBroadcaster {
+    device_id: u16,
+    heartrate: u8,
+    heartbeat_count: u8,
+    // Time of the last heart beat event and the elapsed time in 1/1024s.
+    heartbeat_event_time: f64,
+    elapsed_time: f64,
+    message_count: u32,
+    scheduler: PageScheduler,
+    manufacturer_id: u8,
+    serial_number: u16,
+    hardware_version: u8,
+    software_version: u8,
+    model_number: u8,
+}
+
+impl HeartRateBroadcaster {
+    pub fn new(device_id: u16) -> Self {
+        HeartRateBroadcaster {
+            device_id,
+            heartrate: 0,
+            heartbeat_count: 0,
+            heartbeat_event_time: 0.0,
+            elapsed_time: 0.0,
+            message_count: 0,
+            scheduler: PageScheduler::new(HRM_MAIN_PAGE_MESSAGES, &[0x01, 0x02, 0x03])
+                .repeat(HRM_TOGGLE_MESSAGES),
+            // Development manufacturer ID
+            manufacturer_id: 0xFF,
+            serial_number: device_id,
+            hardware_version: 1,
+            software_version: 1,
+            model_number: 1,
+        }
+    }
+
+    /// Channel configuration for a master channel transmitting as this heart rate monitor.
+    pub fn channel_config(&self) -> Config {
+        HeartRateMonitor::channel_config()
+            .master()
+            .device_id(self.device_id)
+            .transmission_type(HRM_TRANSMISSION_TYPE)
+    }
+
+    /// Sets the manufacturer information sent in data page 2.
+    pub fn manufacturer(mut self, manufacturer_id: u8, serial_number: u16) -> Self {
+        self.manufacturer_id = manufacturer_id;
+        self.serial_number = serial_number;
+        self
+    }
+
+    /// Sets the product information sent in data page 3.
+    pub fn product(mut self, hardware_version: u8, software_version: u8, model_number: u8) -> Self {
+        self.hardware_version = hardware_version;
+        self.software_version = software_version;
+        self.model_number = model_number;
+        self
+    }
+
+    /// Sets the heartrate in bpm to broadcast. A heartrate of 0 stops heart beat events from
+    /// accumulating.
+    pub fn set_heartrate(&mut self, heartrate: u8) {
+        self.heartrate = heartrate;
+    }
+
+    pub fn heartrate(&self) -> u8 {
+        self.heartrate
+    }
+
+    /// Encodes the next data page to transmit. Each call advances time by one channel period,
+    /// accumulating heart beat events at the current heartrate. Data page 4 is sent as the main
+    /// data page with data pages 1, 2, and 3 interleaved as background pages. The toggle bit
+    /// flips every four messages.
+    pub fn next_page(&mut self) -> [u8; 8] {
+        self.elapsed_time += HRM_PERIOD_EVENT_TIME;
+        let previous_event_time = self.heartbeat_event_time;
+        if self.heartrate > 0 {
+            let beat_interval = 60.0 * 1024.0 / self.heartrate as f64;
+            // Don't let beats build up from a period where the heartrate was 0.
+            if self.elapsed_time - self.heartbeat_event_time > 2.0 * beat_interval {
+                self.heartbeat_event_time = self.elapsed_time - beat_interval;
+            }
+            while self.heartbeat_event_time + beat_interval <= self.elapsed_time {
+                self.heartbeat_event_time += beat_interval;
+                self.heartbeat_count = self.heartbeat_count.wrapping_add(1);
+            }
+        }
+
+        let toggle = if (self.message_count / HRM_TOGGLE_MESSAGES) % 2 == 1 {
+            0x80
+        } else {
+            0x00
+        };
+        self.message_count = self.message_count.wrapping_add(1);
+
+        let mut page = [0; 8];
+        match self.scheduler.next_page() {
+            // Data page 1 Cumulative Operating Time in 2s units
+            Some(0x01) => {
+                page[0] = 0x01;
+                let operating_time = (self.elapsed_time / 2048.0) as u32;
+                page[1..4].copy_from_slice(&operating_time.to_le_bytes()[..3]);
+            }
+            // Data page 2 Manufacturer Information
+            Some(0x02) => {
+                page[0] = 0x02;
+                page[1] = self.manufacturer_id;
+                page[2..4].copy_from_slice(&self.serial_number.to_le_bytes());
+            }
+            // Data page 3 Product Information
+            Some(_) => {
+                page[0] = 0x03;
+                page[1] = self.hardware_version;
+                page[2] = self.software_version;
+                page[3] = self.model_number;
+            }
+            // Data page 4 Previous Heart Beat
+            None => {
+                page[0] = 0x04;
+                page[1] = 0xFF;
+                page[2..4].copy_from_slice(&event_time(previous_event_time).to_le_bytes());
+            }
+        }
+        page[0] |= toggle;
+        page[4..6].copy_from_slice(&event_time(self.heartbeat_event_time).to_le_bytes());
+        page[6] = self.heartbeat_count;
+        page[7] = self.heartrate;
+        page
+    }
+}
+
+// Swim interval heartrates of 0 are invalid.
+fn valid_heartrate(heartrate: u8) -> Option<u8> {
+    match heartrate {
+        0 => None,
+        _ => Some(heartrate),
+    }
+}
+
+// Heart beat event time rolls over every 64s.
+fn event_time(time: f64) -> u16 {
+    (time as u64 & 0xFFFF) as u16
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn broadcaster_round_trip() {
+        let mut broadcaster = HeartRateBroadcaster::new(1234);
+        broadcaster.set_heartrate(120);
+        let mut hrm = HeartRateMonitor::new();
+        // Two seconds at 120 bpm should result in four heart beats.
+        let messages = (2.0 * 1024.0 / HRM_PERIOD_EVENT_TIME).ceil() as usize;
+        for _ in 0..messages {
+            hrm.decode_broadcast_data(&broadcaster.next_page());
+        }
+        assert_eq!(hrm.heartrate(), 120);
+        assert_eq!(hrm.heartbeat_count, 4);
+    }
+
+    #[test]
+    fn rr_intervals() {
+        let mut hrm = HeartRateMonitor::new();
+        // Page 0 beats 1024, 2048, then page 4 with previous event 2048.
+        hrm.decode_broadcast_data(&[0x00, 0xFF, 0xFF, 0xFF, 0x00, 0x04, 0x01, 60]);
+        hrm.decode_broadcast_data(&[0x00, 0xFF, 0xFF, 0xFF, 0x00, 0x08, 0x02, 60]);
+        hrm.decode_broadcast_data(&[0x84, 0xFF, 0x00, 0x08, 0x00, 0x0A, 0x03, 60]);
+        // Missed beat without the previous event time.
+        hrm.decode_broadcast_data(&[0x80, 0xFF, 0xFF, 0xFF, 0x00, 0x12, 0x05, 60]);
+        // Event time rolls over.
+        hrm.decode_broadcast_data(&[0x84, 0xFF, 0x00, 0xFC, 0x00, 0x0C, 0x07, 60]);
+        let intervals: Vec<f32> = hrm.rr_intervals().collect();
+        assert_eq!(intervals, vec![1000.0, 500.0, 4000.0]);
+        assert_eq!(
+            hrm.rmssd(),
+            Some(((250000.0_f32 + 12250000.0) / 2.0).sqrt())
+        );
+        assert!((hrm.sdnn().unwrap() - 1892.969).abs() < 0.01);
+        hrm.clear_rr_intervals();
+        assert_eq!(hrm.sdnn(), None);
+    }
+
+    #[test]
+    fn swim_interval_summary() {
+        let mut hrm = HeartRateMonitor::new();
+        assert_eq!(hrm.interval_average_heartrate(), None);
+        hrm.decode_broadcast_data(&[0x05, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 150]);
+        hrm.decode_broadcast_data(&[0x85, 142, 168, 131, 0x00, 0x04, 0x01, 150]);
+        assert_eq!(hrm.interval_average_heartrate(), Some(142));
+        assert_eq!(hrm.interval_max_heartrate(), Some(168));
+        assert_eq!(hrm.session_average_heartrate(), Some(131));
+        assert_eq!(hrm.heartrate(), 150);
+    }
+
+    #[test]
+    fn legacy_detection() {
+        let mut hrm = HeartRateMonitor::new();
+        for _ in 0..8 {
+            assert!(!hrm.is_legacy());
+            // Legacy byte 0 looks like data page 5 but is not interpreted.
+            hrm.decode_broadcast_data(&[0x05, 90, 0x00, 0x00, 0x00, 0x04, 0x01, 72]);
+        }
+        assert!(hrm.is_legacy());
+        assert_eq!(hrm.interval_average_heartrate(), None);
+        assert_eq!(hrm.heartrate(), 72);
+        // The toggle bit flipping later shows the device is modern after all.
+        hrm.decode_broadcast_data(&[0x85, 90, 85, 80, 0x00, 0x04, 0x01, 72]);
+        assert!(!hrm.is_legacy());
+        assert_eq!(hrm.interval_average_heartrate(), Some(90));
+
+        // Data pages received before the toggle bit flips are decoded once it does.
+        let mut hrm = HeartRateMonitor::new();
+        for page in [0x02, 0x02, 0x02, 0x02].iter() {
+            hrm.decode_broadcast_data(&[*page, 0x01, 0x2E, 0x16, 0x00, 0x04, 0x01, 72]);
+        }
+        hrm.decode_broadcast_data(&[0x84, 0xFF, 0x00, 0x04, 0x00, 0x04, 0x01, 72]);
+        assert!(!hrm.is_legacy());
+        assert_eq!(hrm.serial_number(), 5678);
+    }
+
+    #[test]
+    fn broadcaster_page_rotation() {
+        let mut broadcaster = HeartRateBroadcaster::new(1234).manufacturer(1, 5678);
+        let pages: Vec<[u8; 8]> = (0..68).map(|_| broadcaster.next_page()).collect();
+        assert_eq!(pages[0][0], 0x04);
+        assert_eq!(pages[4][0], 0x84);
+        assert_eq!(pages[8][0], 0x04);
+        assert_eq!(pages[64][0], 0x01);
+        assert_eq!(pages[67][0], 0x01);
+        let pages: Vec<[u8; 8]> = (0..68).map(|_| broadcaster.next_page()).collect();
+        assert_eq!(pages[64][0] & 0x7F, 0x02);
+        assert_eq!(bytes_to_u16(&pages[64][2..4]), 5678);
     }
 }
 
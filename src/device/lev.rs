@@ -0,0 +1,350 @@
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52};
+use crate::channel::Config;
+use crate::message::{bytes_to_u16, bytes_to_u32, AcknowledgeDataMessage};
+
+// Constant values for the Light Electric Vehicle channel.
+const LEV_DEVICE_TYPE: u8 = 0x14;
+const LEV_FREQUENCY: u8 = 0x39;
+const LEV_PERIOD: u16 = 8192;
+const LEV_DISPLAY_DATA_PAGE: u8 = 0x10;
+// Default wheel circumference in millimeters sent on the display data page.
+const DEFAULT_WHEEL_CIRCUMFERENCE: u16 = 2096;
+
+// Lev provides a way to decode the broadcast data sent from a light electric vehicle such as an
+// e-bike, and to build the display data page used to control the assist level.
+// Page 0x01 -> Speed System Information 1
+// Page 0x02 -> Speed & Distance Information
+// Page 0x03 -> Speed System Information 2
+// Page 0x10 -> Display Data (command)
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+// Page 0x52 -> Battery Voltage
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lev {
+    wheel_circumference: u16,
+    speed: Option<f32>,
+    travel_mode: Option<TravelMode>,
+    page_0x01: Option<Page0x01>,
+    page_0x02: Option<Page0x02>,
+    page_0x03: Option<Page0x03>,
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+}
+
+impl Lev {
+    pub fn new() -> Self {
+        Self {
+            wheel_circumference: DEFAULT_WHEEL_CIRCUMFERENCE,
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(LEV_DEVICE_TYPE)
+            .frequency(LEV_FREQUENCY)
+            .period(LEV_PERIOD)
+    }
+
+    /// Set the wheel circumference in millimeters sent to the LEV on the display data page.
+    pub fn set_wheel_circumference(&mut self, circumference: u16) {
+        self.wheel_circumference = circumference & 0x0FFF;
+    }
+
+    /// Speed in kilometers per hour.
+    pub fn speed(&self) -> Option<f32> {
+        self.speed
+    }
+
+    /// Odometer in kilometers.
+    pub fn odometer(&self) -> Option<f32> {
+        self.page_0x02.map(|page| page.odometer() as f32 / 100_f32)
+    }
+
+    /// Remaining range in kilometers based on the current battery charge, if supported.
+    pub fn remaining_range(&self) -> Option<u16> {
+        match self.page_0x02 {
+            Some(page) if page.remaining_range() != 0 => Some(page.remaining_range()),
+            _ => None,
+        }
+    }
+
+    /// Battery state of charge as a percentage.
+    pub fn battery_soc(&self) -> Option<u8> {
+        self.page_0x03.map(|page| page.battery_soc())
+    }
+
+    /// Whether the LEV is signaling the battery is empty.
+    pub fn battery_empty(&self) -> Option<bool> {
+        self.page_0x03.map(|page| page.battery_empty())
+    }
+
+    /// Current assist percentage.
+    pub fn percent_assist(&self) -> Option<u8> {
+        self.page_0x03.map(|page| page.percent_assist())
+    }
+
+    /// Current travel mode including assist and regenerative levels.
+    pub fn travel_mode(&self) -> Option<TravelMode> {
+        self.travel_mode
+    }
+
+    /// Current assist level. 0 is assist off.
+    pub fn assist_level(&self) -> Option<u8> {
+        self.travel_mode.map(|mode| mode.assist_level)
+    }
+
+    /// Current gear if the LEV reports gear state.
+    pub fn gear(&self) -> Option<u8> {
+        self.page_0x01.map(|page| page.rear_gear())
+    }
+
+    /// Error code reported by the LEV. 0 is no error.
+    pub fn error(&self) -> Option<u8> {
+        self.page_0x01.map(|page| page.error())
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.battery_status());
+        }
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.serial_number());
+        }
+        None
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.manufacturer());
+        }
+        None
+    }
+
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] {
+            0x01 => {
+                let p = Page0x01(data);
+                self.speed = Some(p.speed());
+                self.travel_mode = Some(p.travel_mode());
+                self.page_0x01 = Some(p);
+            }
+            0x02 => {
+                let p = Page0x02(data);
+                self.speed = Some(p.speed());
+                self.page_0x02 = Some(p);
+            }
+            0x03 => {
+                let p = Page0x03(data);
+                self.speed = Some(p.speed());
+                self.travel_mode = Some(p.travel_mode());
+                self.page_0x03 = Some(p);
+            }
+            0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(data)),
+            0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(data)),
+            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            _ => {} // Do nothing with rest of pages for now.
+        }
+    }
+
+    /// Sends an Acknowledge data page to the LEV requesting a change of assist level. The
+    /// current regenerative level is preserved.
+    pub fn set_assist_level(&self, channel_number: u8, level: u8) -> AcknowledgeDataMessage {
+        let regenerative_level = self
+            .travel_mode
+            .map(|mode| mode.regenerative_level)
+            .unwrap_or(0);
+        AcknowledgeDataMessage::new(
+            channel_number,
+            &self.display_data(TravelMode {
+                assist_level: level & 0x07,
+                regenerative_level,
+            }),
+        )
+    }
+
+    /// Sends an Acknowledge data page to the LEV requesting a change of travel mode.
+    pub fn set_travel_mode(&self, channel_number: u8, mode: TravelMode) -> AcknowledgeDataMessage {
+        AcknowledgeDataMessage::new(channel_number, &self.display_data(mode))
+    }
+
+    // Byte layout of the display data page.
+    // [1..3] - Wheel circumference in mm (12 bits)
+    // [3] - Travel mode, assist level in bits 3-5 and regenerative level in bits 0-2
+    // [4] - Display command, not used
+    // [5] - Reserved
+    // [6..8] - Manufacturer ID of the display, 0xFFFF if not used
+    fn display_data(&self, mode: TravelMode) -> [u8; 8] {
+        [
+            LEV_DISPLAY_DATA_PAGE,
+            (self.wheel_circumference & 0xFF) as u8,
+            ((self.wheel_circumference >> 8) & 0x0F) as u8,
+            mode.into(),
+            0x00,
+            0xFF,
+            0xFF,
+            0xFF,
+        ]
+    }
+}
+
+/// Travel mode of the LEV. Assist level is 0 (off) to 7 and regenerative level is 0 (off) to 7.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TravelMode {
+    pub assist_level: u8,
+    pub regenerative_level: u8,
+}
+
+impl TravelMode {
+    fn from(value: u8) -> Self {
+        TravelMode {
+            assist_level: (value >> 3) & 0x07,
+            regenerative_level: value & 0x07,
+        }
+    }
+}
+
+impl From<TravelMode> for u8 {
+    fn from(mode: TravelMode) -> u8 {
+        ((mode.assist_level & 0x07) << 3) | (mode.regenerative_level & 0x07)
+    }
+}
+
+// Speed is sent as 12 bits in 0.1 km/h increments on pages 0x01, 0x02, and 0x03.
+fn lev_speed(b: &[u8]) -> f32 {
+    (bytes_to_u16(b) & 0x0FFF) as f32 / 10_f32
+}
+
+// Speed System Information 1 Page
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x01([u8; 8]);
+
+impl Page0x01 {
+    fn travel_mode(&self) -> TravelMode {
+        TravelMode::from(self.0[2])
+    }
+
+    fn rear_gear(&self) -> u8 {
+        self.0[4] & 0x0F
+    }
+
+    fn error(&self) -> u8 {
+        self.0[5]
+    }
+
+    fn speed(&self) -> f32 {
+        lev_speed(&self.0[6..])
+    }
+}
+
+// Speed & Distance Information Page
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x02([u8; 8]);
+
+impl Page0x02 {
+    fn odometer(&self) -> u32 {
+        bytes_to_u32(&self.0[1..4])
+    }
+
+    fn remaining_range(&self) -> u16 {
+        bytes_to_u16(&self.0[4..6]) & 0x0FFF
+    }
+
+    fn speed(&self) -> f32 {
+        lev_speed(&self.0[6..])
+    }
+}
+
+// Speed System Information 2 Page
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x03([u8; 8]);
+
+impl Page0x03 {
+    fn battery_soc(&self) -> u8 {
+        self.0[1] & 0x7F
+    }
+
+    fn battery_empty(&self) -> bool {
+        self.0[1] & 0x80 == 0x80
+    }
+
+    fn travel_mode(&self) -> TravelMode {
+        TravelMode::from(self.0[2])
+    }
+
+    fn percent_assist(&self) -> u8 {
+        self.0[5]
+    }
+
+    fn speed(&self) -> f32 {
+        lev_speed(&self.0[6..])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lev_decode() {
+        let mut lev = Lev::new();
+        assert_eq!(lev.battery_soc(), None);
+        // 25.6 km/h in assist level 2 and regenerative level 1, rear gear 5, no error.
+        lev.decode([0x01, 0x00, 0x11, 0x00, 0x05, 0x00, 0x00, 0x01]);
+        assert_eq!(lev.speed(), Some(25.6));
+        assert_eq!(lev.assist_level(), Some(2));
+        assert_eq!(lev.gear(), Some(5));
+        assert_eq!(lev.error(), Some(0));
+        // 1234.56 km odometer with 40 km range left.
+        lev.decode([0x02, 0x40, 0xE2, 0x01, 0x28, 0x00, 0xFA, 0x00]);
+        assert_eq!(lev.odometer(), Some(1234.56));
+        assert_eq!(lev.remaining_range(), Some(40));
+        assert_eq!(lev.speed(), Some(25.0));
+        // 80% battery, assist level 3, 150% assist.
+        lev.decode([0x03, 0x50, 0x18, 0x00, 0x05, 0x96, 0xF0, 0x00]);
+        assert_eq!(lev.battery_soc(), Some(80));
+        assert_eq!(lev.battery_empty(), Some(false));
+        assert_eq!(lev.percent_assist(), Some(150));
+        assert_eq!(
+            lev.travel_mode(),
+            Some(TravelMode {
+                assist_level: 3,
+                regenerative_level: 0
+            })
+        );
+        assert_eq!(lev.speed(), Some(24.0));
+        lev.decode([0x03, 0x80, 0x18, 0x00, 0x05, 0x96, 0xF0, 0x00]);
+        assert_eq!(lev.battery_soc(), Some(0));
+        assert_eq!(lev.battery_empty(), Some(true));
+    }
+
+    #[test]
+    fn test_lev_commands() {
+        let mut lev = Lev::new();
+        lev.decode([0x01, 0x00, 0x11, 0x00, 0x05, 0x00, 0x00, 0x01]);
+        // The regenerative level is kept when changing the assist level.
+        let mesg = lev.set_assist_level(1, 4);
+        assert_eq!(mesg.channel(), 1);
+        assert_eq!(
+            mesg.data(),
+            [0x10, 0x30, 0x08, 0x21, 0x00, 0xFF, 0xFF, 0xFF]
+        );
+        lev.set_wheel_circumference(2200);
+        let mode = TravelMode {
+            assist_level: 0,
+            regenerative_level: 3,
+        };
+        let mesg = lev.set_travel_mode(1, mode);
+        assert_eq!(&mesg.data()[1..4], [0x98, 0x08, 0x03]);
+    }
+}
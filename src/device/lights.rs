@@ -0,0 +1,234 @@
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52};
+use crate::channel::Config;
+use crate::message::AcknowledgeDataMessage;
+
+// Constant values for the bike lights channel.
+const LIGHTS_DEVICE_TYPE: u8 = 0x23;
+const LIGHTS_FREQUENCY: u8 = 0x39;
+const LIGHTS_PERIOD: u16 = 4096;
+const LIGHT_CONTROL_PAGE: u8 = 0x10;
+
+// BikeLight provides a way to decode the light state broadcast by ANT+ bike lights and to build
+// the command pages sent back to the light to control it.
+// Page 0x01 -> Light State
+// Page 0x10 -> Light Control (command)
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+// Page 0x52 -> Battery Voltage
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BikeLight {
+    page_0x01: Option<Page0x01>,
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+}
+
+impl BikeLight {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(LIGHTS_DEVICE_TYPE)
+            .frequency(LIGHTS_FREQUENCY)
+            .period(LIGHTS_PERIOD)
+    }
+
+    /// Index of the light when multiple lights are connected together.
+    pub fn light_index(&self) -> Option<u8> {
+        self.page_0x01.map(|page| page.light_index())
+    }
+
+    /// Current mode the light is operating in.
+    pub fn mode(&self) -> Option<LightMode> {
+        self.page_0x01.map(|page| page.mode())
+    }
+
+    /// Beam intensity as a percentage, if reported by the light.
+    pub fn beam_level(&self) -> Option<u8> {
+        match self.page_0x01 {
+            Some(page) if page.beam_level() <= 100 => Some(page.beam_level()),
+            _ => None,
+        }
+    }
+
+    /// Sequence number of the last command processed by the light.
+    pub fn sequence_number(&self) -> Option<u8> {
+        self.page_0x01.map(|page| page.sequence_number())
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.battery_status());
+        }
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.serial_number());
+        }
+        None
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.manufacturer());
+        }
+        None
+    }
+
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] {
+            0x01 => self.page_0x01 = Some(Page0x01(data)),
+            0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(data)),
+            0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(data)),
+            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            _ => {} // Do nothing with rest of pages for now.
+        }
+    }
+
+    /// Sends an Acknowledge data page to the light turning it on in steady mode.
+    pub fn turn_on(&self, channel_number: u8) -> AcknowledgeDataMessage {
+        self.set_mode(channel_number, LightMode::Steady)
+    }
+
+    /// Sends an Acknowledge data page to the light turning it off.
+    pub fn turn_off(&self, channel_number: u8) -> AcknowledgeDataMessage {
+        self.set_mode(channel_number, LightMode::Off)
+    }
+
+    /// Sends an Acknowledge data page to the light changing the light mode, e.g. a flash
+    /// pattern.
+    pub fn set_mode(&self, channel_number: u8, mode: LightMode) -> AcknowledgeDataMessage {
+        AcknowledgeDataMessage::new(channel_number, &self.light_control(mode.into(), 0xFF))
+    }
+
+    /// Sends an Acknowledge data page to the light setting the beam intensity as a percentage.
+    /// Values above 100 are capped at 100.
+    pub fn set_beam_level(&self, channel_number: u8, level: u8) -> AcknowledgeDataMessage {
+        AcknowledgeDataMessage::new(
+            channel_number,
+            &self.light_control(LightMode::Steady.into(), level.min(100)),
+        )
+    }
+
+    // The light control page sent to the light. The sequence number is incremented from the last
+    // processed command so the light can detect new commands.
+    fn light_control(&self, mode: u8, beam_level: u8) -> [u8; 8] {
+        [
+            LIGHT_CONTROL_PAGE,
+            self.light_index().unwrap_or(0),
+            self.sequence_number().unwrap_or(0).wrapping_add(1),
+            mode,
+            beam_level,
+            0xFF,
+            0xFF,
+            0xFF,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LightMode {
+    Off,
+    Steady,
+    SlowFlash,
+    FastFlash,
+    RandomFlash,
+    Auto,
+    Custom(u8),
+}
+
+impl LightMode {
+    fn from(value: u8) -> Self {
+        match value & 0x3F {
+            0 => Self::Off,
+            1 => Self::Steady,
+            2 => Self::SlowFlash,
+            3 => Self::FastFlash,
+            4 => Self::RandomFlash,
+            5 => Self::Auto,
+            v => Self::Custom(v),
+        }
+    }
+}
+
+impl From<LightMode> for u8 {
+    fn from(mode: LightMode) -> u8 {
+        match mode {
+            LightMode::Off => 0,
+            LightMode::Steady => 1,
+            LightMode::SlowFlash => 2,
+            LightMode::FastFlash => 3,
+            LightMode::RandomFlash => 4,
+            LightMode::Auto => 5,
+            LightMode::Custom(v) => v & 0x3F,
+        }
+    }
+}
+
+// Light State Page
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x01([u8; 8]);
+
+impl Page0x01 {
+    fn light_index(&self) -> u8 {
+        self.0[1] & 0x3F
+    }
+
+    fn sequence_number(&self) -> u8 {
+        self.0[2]
+    }
+
+    fn mode(&self) -> LightMode {
+        LightMode::from(self.0[3])
+    }
+
+    fn beam_level(&self) -> u8 {
+        self.0[4]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_light_decode() {
+        let mut light = BikeLight::new();
+        assert_eq!(light.mode(), None);
+        light.decode([0x01, 0x02, 0x07, 0x03, 0x50, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(light.light_index(), Some(2));
+        assert_eq!(light.sequence_number(), Some(7));
+        assert_eq!(light.mode(), Some(LightMode::FastFlash));
+        assert_eq!(light.beam_level(), Some(80));
+        // Beam level not reported.
+        light.decode([0x01, 0x02, 0x07, 0x2A, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(light.mode(), Some(LightMode::Custom(0x2A)));
+        assert_eq!(light.beam_level(), None);
+    }
+
+    #[test]
+    fn test_light_commands() {
+        let mut light = BikeLight::new();
+        light.decode([0x01, 0x02, 0xFF, 0x01, 0x64, 0xFF, 0xFF, 0xFF]);
+        let mesg = light.turn_off(3);
+        assert_eq!(mesg.channel(), 3);
+        // Sequence number rolls over from the last command processed.
+        assert_eq!(
+            mesg.data(),
+            [0x10, 0x02, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF]
+        );
+        let mesg = light.set_mode(3, LightMode::SlowFlash);
+        assert_eq!(mesg.data()[3], 0x02);
+        let mesg = light.set_beam_level(3, 150);
+        assert_eq!(&mesg.data()[3..5], [0x01, 100]);
+    }
+}
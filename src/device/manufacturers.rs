@@ -0,0 +1,225 @@
+// Names of ANT+ manufacturer IDs, generated from the manufacturer type in the FIT SDK profile.
+// Sorted by ID for binary search.
+pub(crate) const MANUFACTURERS: &[(u16, &str)] = &[
+    (1, "Garmin"),
+    (2, "Garmin FR405 ANT-FS"),
+    (3, "Zephyr"),
+    (4, "Dayton"),
+    (5, "IDT"),
+    (6, "SRM"),
+    (7, "Quarq"),
+    (8, "iBike"),
+    (9, "Saris"),
+    (10, "Spark HK"),
+    (11, "Tanita"),
+    (12, "Echowell"),
+    (13, "Dynastream OEM"),
+    (14, "Nautilus"),
+    (15, "Dynastream"),
+    (16, "Timex"),
+    (17, "Metrigear"),
+    (18, "Xelic"),
+    (19, "Beurer"),
+    (20, "Cardiosport"),
+    (21, "A&D"),
+    (22, "HMM"),
+    (23, "Suunto"),
+    (24, "Thita Elektronik"),
+    (25, "gPulse"),
+    (26, "Clean Mobile"),
+    (27, "PedalBrain"),
+    (28, "Peaksware"),
+    (29, "Saxonar"),
+    (30, "LeMond Fitness"),
+    (31, "Dexcom"),
+    (32, "Wahoo Fitness"),
+    (33, "Octane Fitness"),
+    (34, "Archinoetics"),
+    (35, "The Hurt Box"),
+    (36, "Citizen Systems"),
+    (37, "Magellan"),
+    (38, "o-synce"),
+    (39, "Holux"),
+    (40, "Concept2"),
+    (41, "Shimano"),
+    (42, "One Giant Leap"),
+    (43, "ACE Sensor"),
+    (44, "Brim Brothers"),
+    (45, "Xplova"),
+    (46, "Perception Digital"),
+    (47, "BF1Systems"),
+    (48, "Pioneer"),
+    (49, "Spantec"),
+    (50, "Metalogics"),
+    (51, "4iiii"),
+    (52, "Seiko Epson"),
+    (53, "Seiko Epson OEM"),
+    (54, "Ifor Powell"),
+    (55, "Maxwell Guider"),
+    (56, "Star Trac"),
+    (57, "Breakaway"),
+    (58, "Alatech Technology Ltd"),
+    (59, "Mio Technology Europe"),
+    (60, "Rotor"),
+    (61, "Geonaute"),
+    (62, "ID Bike"),
+    (63, "Specialized"),
+    (64, "WTEK"),
+    (65, "Physical Enterprises"),
+    (66, "North Pole Engineering"),
+    (67, "BKOOL"),
+    (68, "CatEye"),
+    (69, "Stages Cycling"),
+    (70, "Sigma Sport"),
+    (71, "TomTom"),
+    (72, "Peripedal"),
+    (73, "Wattbike"),
+    (76, "Moxy"),
+    (77, "Ciclosport"),
+    (78, "Powerbahn"),
+    (79, "Acorn Projects ApS"),
+    (80, "LifeBEAM"),
+    (81, "Bontrager"),
+    (82, "Wellgo"),
+    (83, "Scosche"),
+    (84, "Magura"),
+    (85, "Woodway"),
+    (86, "Elite"),
+    (87, "Nielsen-Kellerman"),
+    (88, "DK City"),
+    (89, "Tacx"),
+    (90, "Direction Technology"),
+    (91, "Magtonic"),
+    (92, "1PartCarbon"),
+    (93, "Inside Ride Technologies"),
+    (94, "Sound Of Motion"),
+    (95, "Stryd"),
+    (96, "ICG"),
+    (97, "MiPulse"),
+    (98, "BSX Athletics"),
+    (99, "LOOK"),
+    (100, "Campagnolo"),
+    (101, "Body Bike Smart"),
+    (102, "Praxisworks"),
+    (103, "Limits Technology"),
+    (104, "TopAction Technology"),
+    (105, "Cosinuss"),
+    (106, "FitCare"),
+    (107, "Magene"),
+    (108, "Giant Manufacturing Co"),
+    (109, "Tigrasport"),
+    (110, "Salutron"),
+    (111, "Technogym"),
+    (112, "Bryton Sensors"),
+    (113, "Latitude Limited"),
+    (114, "Soaring Technology"),
+    (115, "iGPSPORT"),
+    (116, "ThinkRider"),
+    (117, "Gopher Sport"),
+    (118, "WaterRower"),
+    (119, "Orangetheory"),
+    (120, "inPeak"),
+    (121, "Kinetic"),
+    (122, "Johnson Health Tech"),
+    (123, "Polar Electro"),
+    (124, "See.Sense"),
+    (125, "NCI Technology"),
+    (126, "IQSquare"),
+    (127, "LEOMO"),
+    (128, "iFit.com"),
+    (129, "COROS"),
+    (130, "Versa Design"),
+    (131, "Chileaf"),
+    (132, "CYCPLUS"),
+    (133, "Gravaa"),
+    (134, "SIGEYI"),
+    (135, "COOSPO"),
+    (136, "Geoid"),
+    (137, "Bosch"),
+    (138, "KYTO"),
+    (139, "Kinetic Sports"),
+    (140, "Decathlon"),
+    (141, "TQ Systems"),
+    (142, "TAG Heuer"),
+    (143, "Keiser Fitness"),
+    (144, "Zwift"),
+    (145, "Porsche EP"),
+    (146, "Blackbird"),
+    (147, "Meilan"),
+    (148, "EZON"),
+    (149, "Laisi"),
+    (150, "MYZONE"),
+    (255, "Development"),
+    (257, "Health & Life"),
+    (258, "Lezyne"),
+    (259, "Scribe Labs"),
+    (260, "Zwift"),
+    (261, "Watteam"),
+    (262, "Recon"),
+    (263, "Favero"),
+    (264, "DynoVelo"),
+    (265, "Strava"),
+    (266, "Precor"),
+    (267, "Bryton"),
+    (268, "SRAM"),
+    (269, "Navman"),
+    (270, "COBI"),
+    (271, "Spivi"),
+    (272, "Mio Magellan"),
+    (273, "EVE Sports"),
+    (274, "Sensitivus Gauge"),
+    (275, "Podoon"),
+    (276, "Life Time Fitness"),
+    (277, "Falco eMotors"),
+    (278, "Minoura"),
+    (279, "Cycliq"),
+    (280, "Luxottica"),
+    (281, "TrainerRoad"),
+    (282, "The Sufferfest"),
+    (283, "Full Speed Ahead"),
+    (284, "Virtual Training"),
+    (285, "Feedback Sports"),
+    (286, "Omata"),
+    (287, "VDO"),
+    (288, "Magnetic Days"),
+    (289, "Hammerhead"),
+    (290, "Kinetic by Kurt"),
+    (291, "Shapelog"),
+    (292, "Dabuziduo"),
+    (293, "JetBlack"),
+    (294, "COROS"),
+    (295, "Virtugo"),
+    (296, "Velosense"),
+    (297, "Cycligent"),
+    (298, "Trailforks"),
+    (299, "Mahle ebikemotion"),
+    (300, "NURVV"),
+    (301, "Microprogram"),
+    (302, "Zone5Cloud"),
+    (303, "Greenteg"),
+    (304, "Yamaha Motors"),
+    (305, "WHOOP"),
+    (306, "Gravaa"),
+    (307, "Onelap"),
+    (308, "Monark Exercise"),
+    (309, "FORM"),
+    (310, "Decathlon"),
+    (311, "Syncros"),
+    (312, "HEATUP"),
+    (313, "Cannondale"),
+    (314, "True Fitness"),
+    (315, "RGT Cycling"),
+    (316, "Vasa"),
+    (317, "Race Republic"),
+    (318, "Fazua"),
+    (319, "Oreka Training"),
+    (320, "LSEC"),
+    (321, "lululemon Studio"),
+    (322, "Shanyue"),
+    (323, "Spinning MDA"),
+    (324, "Hilldating"),
+    (325, "Aero Sensor"),
+    (326, "Nike"),
+    (327, "Magicshine"),
+    (5759, "ActiGraph"),
+];
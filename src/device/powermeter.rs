@@ -1,6 +1,7 @@
-use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52};
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52, PageScheduler};
 use crate::channel::Config;
 use crate::message::{bytes_to_u16, AcknowledgeDataMessage};
+use std::collections::BTreeMap;
 use std::f32::consts::PI;
 
 // Constant values for PowerMeter channel.
@@ -9,6 +10,9 @@ const PM_DEVICE_TYPE: u8 = 0x0B;
 const PM_FREQUENCY: u8 = 0x39;
 const PM_EIGHT_HZ: u16 = 8182;
 const PM_FOUR_HZ: u16 = 4091;
+const PM_TRANSMISSION_TYPE: u8 = 0x05;
+// Common pages are interleaved at least every 121 messages.
+const PM_COMMON_PAGE_INTERVAL: u32 = 60;
 
 // PowerMeter provides a way to decode and use the broadcast data sent from the PowerMeter.
 // Page 0x01 -> Calibration Messages
@@ -26,6 +30,7 @@ const PM_FOUR_HZ: u16 = 4091;
 // Page 0xE1 -> Left Force Angle
 // Page 0xE2 -> Pedal Position data
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PowerMeter {
     cadence: u8,
     power: u16,
@@ -36,7 +41,9 @@ pub struct PowerMeter {
     page_0x01: Option<Page0x01>,
     page_0x50: Option<Page0x50>,
     page_0x51: Option<Page0x51>,
-    page_0x52: Option<Page0x52>,
+    // Battery pages by battery index, so power meters with a battery in each pedal report
+    // both. Power meters that don't identify their batteries use index 0.
+    page_0x52: BTreeMap<u8, Page0x52>,
 }
 
 impl PowerMeter {
@@ -47,7 +54,7 @@ impl PowerMeter {
             page_0x01: None,
             page_0x50: None,
             page_0x51: None,
-            page_0x52: None,
+            page_0x52: BTreeMap::new(),
             last_page_0x10: None,
             last_page_0x12: None,
             ..Default::default()
@@ -83,20 +90,40 @@ impl PowerMeter {
         None
     }
 
+    /// Status of the battery with the lowest index.
     pub fn battery_status(&self) -> Option<BatteryStatus> {
-        if let Some(page) = &self.page_0x52 {
+        if let Some(page) = self.page_0x52.values().next() {
             return Some(page.battery_status());
         }
         None
     }
 
+    /// Voltage of the battery with the lowest index.
     pub fn battery_voltage(&self) -> Option<f32> {
-        if let Some(page) = &self.page_0x52 {
+        if let Some(page) = self.page_0x52.values().next() {
             return page.battery_voltage();
         }
         None
     }
 
+    /// Indexes of the batteries reported by the power meter.
+    pub fn battery_indexes(&self) -> Vec<u8> {
+        self.page_0x52.keys().copied().collect()
+    }
+
+    /// Status of the battery with the index, such as the left or right pedal of pedal based
+    /// power meters.
+    pub fn battery_status_of(&self, index: u8) -> Option<BatteryStatus> {
+        self.page_0x52.get(&index).map(|page| page.battery_status())
+    }
+
+    /// Voltage of the battery with the index.
+    pub fn battery_voltage_of(&self, index: u8) -> Option<f32> {
+        self.page_0x52
+            .get(&index)
+            .and_then(|page| page.battery_voltage())
+    }
+
     pub fn serial_number(&self) -> Option<u32> {
         if let Some(page) = &self.page_0x51 {
             return Some(page.serial_number());
@@ -206,13 +233,18 @@ impl PowerMeter {
                     self.page_0x51 = Some(Page0x51(data));
                 }
             }
-            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            0x52 => {
+                let page = Page0x52(data);
+                self.page_0x52
+                    .insert(page.battery_index().unwrap_or(0), page);
+            }
             _ => {} // Do nothing with rest of pages for now.
         }
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum PedalPower {
     Right(u8),
     Unknown(u8),
@@ -251,6 +283,7 @@ enum AutozeroConfig {
 
 // Calibration Data Page.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Page0x01([u8; 8]);
 
 impl Page0x01 {
@@ -297,6 +330,7 @@ impl Page0x01 {
 
 // Standard Power Page
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Page0x10([u8; 8]);
 
 impl Page0x10 {
@@ -328,6 +362,7 @@ impl Page0x10 {
 
 // Standard Crank Torque Data Page
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Page0x12([u8; 8]);
 
 impl Page0x12 {
@@ -367,6 +402,144 @@ impl fmt::Display for Page0x12 {
     }
 }
 
+/// PowerMeterBroadcaster encodes power only pages from instantaneous power and cadence supplied by
+/// the application so power can be transmitted on a master channel without a physical power
+/// meter. Call next_page() each time an EventTx is received for the channel and send the page
+/// with Request::SendBroadcast.
+// Page 0x10 -> Power Only
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PowerMeterBroadcaster {
+    device_id: u16,
+    power: u16,
+    cadence: Option<u8>,
+    event_count: u8,
+    accumulated_power: u16,
+    scheduler: PageScheduler,
+    manufacturer_id: u16,
+    model_number: u16,
+    hardware_revision: u8,
+    software_version: u8,
+    serial_number: u32,
+}
+
+impl PowerMeterBroadcaster {
+    pub fn new(device_id: u16) -> Self {
+        Self {
+            device_id,
+            power: 0,
+            cadence: None,
+            event_count: 0,
+            accumulated_power: 0,
+            scheduler: PageScheduler::new(PM_COMMON_PAGE_INTERVAL, &[0x50, 0x51]),
+            // Development manufacturer ID
+            manufacturer_id: 0xFF,
+            model_number: 1,
+            hardware_revision: 1,
+            software_version: 1,
+            serial_number: device_id as u32,
+        }
+    }
+
+    /// Channel configuration for a master channel transmitting as this power meter.
+    pub fn channel_config(&self) -> Config {
+        PowerMeter::channel_config()
+            .master()
+            .device_id(self.device_id)
+            .transmission_type(PM_TRANSMISSION_TYPE)
+    }
+
+    /// Sets the manufacturer information sent in common page 0x50.
+    pub fn manufacturer(
+        mut self,
+        manufacturer_id: u16,
+        model_number: u16,
+        hardware_revision: u8,
+    ) -> Self {
+        self.manufacturer_id = manufacturer_id;
+        self.model_number = model_number;
+        self.hardware_revision = hardware_revision;
+        self
+    }
+
+    /// Sets the product information sent in common page 0x51.
+    pub fn product(mut self, software_version: u8, serial_number: u32) -> Self {
+        self.software_version = software_version;
+        self.serial_number = serial_number;
+        self
+    }
+
+    /// Sets the instantaneous power in watts.
+    pub fn set_power(&mut self, power: u16) {
+        self.power = power;
+    }
+
+    /// Sets the instantaneous cadence in RPM. None if cadence is not available.
+    pub fn set_cadence(&mut self, cadence: Option<u8>) {
+        self.cadence = cadence;
+    }
+
+    /// Encodes the next data page to transmit. Every power only page is a new power event,
+    /// incrementing the event count and adding the current power to the accumulated power.
+    /// Common pages 0x50 and 0x51 are interleaved.
+    pub fn next_page(&mut self) -> [u8; 8] {
+        match self.scheduler.next_page() {
+            Some(0x50) => self.page_0x50(),
+            Some(_) => self.page_0x51(),
+            None => self.page_0x10(),
+        }
+    }
+
+    fn page_0x10(&mut self) -> [u8; 8] {
+        self.event_count = self.event_count.wrapping_add(1);
+        self.accumulated_power = self.accumulated_power.wrapping_add(self.power);
+        let accumulated_power = self.accumulated_power.to_le_bytes();
+        let power = self.power.to_le_bytes();
+        [
+            0x10,
+            self.event_count,
+            // Pedal power not used
+            0xFF,
+            self.cadence.unwrap_or(0xFF),
+            accumulated_power[0],
+            accumulated_power[1],
+            power[0],
+            power[1],
+        ]
+    }
+
+    fn page_0x50(&self) -> [u8; 8] {
+        let manufacturer_id = self.manufacturer_id.to_le_bytes();
+        let model_number = self.model_number.to_le_bytes();
+        [
+            0x50,
+            0xFF,
+            0xFF,
+            self.hardware_revision,
+            manufacturer_id[0],
+            manufacturer_id[1],
+            model_number[0],
+            model_number[1],
+        ]
+    }
+
+    fn page_0x51(&self) -> [u8; 8] {
+        let serial_number = self.serial_number.to_le_bytes();
+        [
+            0x51,
+            0xFF,
+            0xFF,
+            self.software_version,
+            serial_number[0],
+            serial_number[1],
+            serial_number[2],
+            serial_number[3],
+        ]
+    }
+}
+
 pub fn manual_calibration(channel: u8) -> AcknowledgeDataMessage {
     AcknowledgeDataMessage::new(channel, &[0x01, 0xAA, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF])
 }
@@ -391,6 +564,40 @@ mod test {
         assert_eq!(pm.power, power);
     }
 
+    #[test]
+    fn test_powermeter_broadcaster() {
+        let mut broadcaster = PowerMeterBroadcaster::new(1234).product(2, 98765);
+        let mut pm = PowerMeter::new();
+        broadcaster.set_power(250);
+        broadcaster.set_cadence(Some(90));
+        for _ in 0..(PM_COMMON_PAGE_INTERVAL + 1) * 2 {
+            pm.decode(broadcaster.next_page());
+        }
+        assert_eq!(pm.power(), 250);
+        assert_eq!(pm.cadence(), 90);
+        assert_eq!(pm.serial_number(), Some(98765));
+        // Accumulated power rolls over and power is still calculated from the delta.
+        broadcaster.set_power(400);
+        for _ in 0..200 {
+            pm.decode(broadcaster.next_page());
+        }
+        assert_eq!(pm.power(), 400);
+    }
+
+    #[test]
+    fn test_powermeter_batteries() {
+        let mut pm = PowerMeter::new();
+        // Two batteries, with the first at 3.5V and the second low at 2.25V.
+        pm.decode([0x52, 0xFF, 0x02, 0x00, 0x00, 0x00, 0x80, 0x23]);
+        pm.decode([0x52, 0xFF, 0x12, 0x00, 0x00, 0x00, 0x40, 0x42]);
+        assert_eq!(pm.battery_indexes(), [0, 1]);
+        assert_eq!(pm.battery_voltage_of(0), Some(3.5));
+        assert_eq!(pm.battery_voltage_of(1), Some(2.25));
+        assert!(matches!(pm.battery_status_of(1), Some(BatteryStatus::Low)));
+        assert_eq!(pm.battery_voltage(), Some(3.5));
+        assert_eq!(pm.battery_voltage_of(2), None);
+    }
+
     #[test]
     fn test_powermeter_decode_page0x12() {
         let mut pm = PowerMeter::new();
@@ -0,0 +1,210 @@
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52};
+use crate::channel::Config;
+use crate::message::{bytes_to_u16, bytes_to_u32};
+
+// Constant values for the bike radar channel.
+const RADAR_DEVICE_TYPE: u8 = 0x28;
+const RADAR_FREQUENCY: u8 = 0x39;
+const RADAR_PERIOD: u16 = 2048;
+// Range is reported in 3.125m increments and closing speed in 3.04m/s increments.
+const RADAR_RANGE_RESOLUTION: f32 = 3.125;
+const RADAR_SPEED_RESOLUTION: f32 = 3.04;
+
+// Radar provides a way to decode the threat data sent from a bike radar such as a Garmin Varia.
+// Each targets page reports up to four targets, so two pages are needed for all eight targets.
+// Page 0x30 -> Radar Targets A (targets 1-4)
+// Page 0x31 -> Radar Targets B (targets 5-8)
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+// Page 0x52 -> Battery Voltage
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Radar {
+    targets: [RadarTarget; 8],
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+}
+
+impl Radar {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(RADAR_DEVICE_TYPE)
+            .frequency(RADAR_FREQUENCY)
+            .period(RADAR_PERIOD)
+    }
+
+    /// All eight target slots as last reported by the radar.
+    pub fn targets(&self) -> &[RadarTarget; 8] {
+        &self.targets
+    }
+
+    /// Targets currently considered a threat, i.e. approaching vehicles.
+    pub fn threats(&self) -> impl Iterator<Item = &RadarTarget> {
+        self.targets
+            .iter()
+            .filter(|target| target.threat_level != ThreatLevel::NoThreat)
+    }
+
+    /// Highest threat level across all targets.
+    pub fn threat_level(&self) -> ThreatLevel {
+        self.threats()
+            .map(|target| target.threat_level)
+            .max()
+            .unwrap_or(ThreatLevel::NoThreat)
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.battery_status());
+        }
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.serial_number());
+        }
+        None
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.manufacturer());
+        }
+        None
+    }
+
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] {
+            0x30 => self.decode_targets(0, &data),
+            0x31 => self.decode_targets(4, &data),
+            0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(data)),
+            0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(data)),
+            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            _ => {} // Do nothing with rest of pages for now.
+        }
+    }
+
+    // Byte layout of a radar targets page where each field packs four targets.
+    // [1] - Threat level, 2 bits per target
+    // [2] - Threat side, 2 bits per target
+    // [3..6] - Range, 6 bits per target
+    // [6..8] - Closing speed, 4 bits per target
+    fn decode_targets(&mut self, offset: usize, data: &[u8; 8]) {
+        let ranges = bytes_to_u32(&data[3..6]);
+        let speeds = bytes_to_u16(&data[6..]);
+        for i in 0..4 {
+            self.targets[offset + i] = RadarTarget {
+                threat_level: ThreatLevel::from((data[1] >> (2 * i)) & 0x03),
+                threat_side: ThreatSide::from((data[2] >> (2 * i)) & 0x03),
+                range: ((ranges >> (6 * i)) & 0x3F) as f32 * RADAR_RANGE_RESOLUTION,
+                closing_speed: ((speeds >> (4 * i)) & 0x0F) as f32 * RADAR_SPEED_RESOLUTION,
+            };
+        }
+    }
+}
+
+/// A single target tracked by the radar.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RadarTarget {
+    threat_level: ThreatLevel,
+    threat_side: ThreatSide,
+    range: f32,
+    closing_speed: f32,
+}
+
+impl RadarTarget {
+    pub fn threat_level(&self) -> ThreatLevel {
+        self.threat_level
+    }
+
+    pub fn threat_side(&self) -> ThreatSide {
+        self.threat_side
+    }
+
+    /// Range to the target in meters.
+    pub fn range(&self) -> f32 {
+        self.range
+    }
+
+    /// Closing speed of the target in meters per second.
+    pub fn closing_speed(&self) -> f32 {
+        self.closing_speed
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThreatLevel {
+    #[default]
+    NoThreat,
+    VehicleApproaching,
+    VehicleFastApproaching,
+    Reserved,
+}
+
+impl ThreatLevel {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::NoThreat,
+            1 => Self::VehicleApproaching,
+            2 => Self::VehicleFastApproaching,
+            _ => Self::Reserved,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThreatSide {
+    #[default]
+    Behind,
+    Right,
+    Left,
+    Reserved,
+}
+
+impl ThreatSide {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Behind,
+            1 => Self::Right,
+            2 => Self::Left,
+            _ => Self::Reserved,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_radar_decode_targets() {
+        let mut radar = Radar::new();
+        // Target 1 approaching from behind at 10 * 3.125m and 2 * 3.04m/s, target 2 approaching
+        // fast from the left at 20 * 3.125m and 5 * 3.04m/s.
+        radar.decode([0x30, 0x09, 0x08, 0x0A, 0x05, 0x00, 0x52, 0x00]);
+        let threats: Vec<&RadarTarget> = radar.threats().collect();
+        assert_eq!(threats.len(), 2);
+        assert_eq!(threats[0].threat_side(), ThreatSide::Behind);
+        assert_eq!(threats[0].range(), 31.25);
+        assert_eq!(threats[0].closing_speed(), 2_f32 * 3.04);
+        assert_eq!(
+            threats[1].threat_level(),
+            ThreatLevel::VehicleFastApproaching
+        );
+        assert_eq!(threats[1].threat_side(), ThreatSide::Left);
+        assert_eq!(threats[1].range(), 62.5);
+        assert_eq!(threats[1].closing_speed(), 5_f32 * 3.04);
+        assert_eq!(radar.threat_level(), ThreatLevel::VehicleFastApproaching);
+    }
+}
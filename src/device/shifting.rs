@@ -0,0 +1,203 @@
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52};
+use crate::channel::Config;
+
+// Constant values for the shifting channel.
+const SHIFTING_DEVICE_TYPE: u8 = 0x22;
+const SHIFTING_FREQUENCY: u8 = 0x39;
+const SHIFTING_PERIOD: u16 = 8192;
+
+// Shifting provides a way to decode the broadcast data sent from electronic shifting systems such
+// as Shimano Di2 or SRAM eTap.
+// Page 0x01 -> Shift System Status
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+// Page 0x52 -> Battery Voltage
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shifting {
+    shift_count: u32,
+    last_page_0x01: Option<Page0x01>,
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+}
+
+impl Shifting {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(SHIFTING_DEVICE_TYPE)
+            .frequency(SHIFTING_FREQUENCY)
+            .period(SHIFTING_PERIOD)
+    }
+
+    /// Current front gear where 0 is the smallest chainring. None if there is no front
+    /// derailleur.
+    pub fn front_gear(&self) -> Option<u8> {
+        match self.last_page_0x01 {
+            Some(page) if page.total_front_gears() != 0 => Some(page.front_gear()),
+            _ => None,
+        }
+    }
+
+    /// Current rear gear where 0 is the largest cog. None if there is no rear derailleur.
+    pub fn rear_gear(&self) -> Option<u8> {
+        match self.last_page_0x01 {
+            Some(page) if page.total_rear_gears() != 0 => Some(page.rear_gear()),
+            _ => None,
+        }
+    }
+
+    /// Total number of front gears.
+    pub fn total_front_gears(&self) -> Option<u8> {
+        self.last_page_0x01.map(|page| page.total_front_gears())
+    }
+
+    /// Total number of rear gears.
+    pub fn total_rear_gears(&self) -> Option<u8> {
+        self.last_page_0x01.map(|page| page.total_rear_gears())
+    }
+
+    /// Number of shifts counted since the first page was received.
+    pub fn shift_count(&self) -> u32 {
+        self.shift_count
+    }
+
+    /// Inbound shifts requested while already in the innermost gear.
+    pub fn invalid_inbound_shift_count(&self) -> Option<u8> {
+        self.last_page_0x01
+            .map(|page| page.invalid_inbound_shifts())
+    }
+
+    /// Outbound shifts requested while already in the outermost gear.
+    pub fn invalid_outbound_shift_count(&self) -> Option<u8> {
+        self.last_page_0x01
+            .map(|page| page.invalid_outbound_shifts())
+    }
+
+    /// Shifts that failed to complete.
+    pub fn shift_failure_count(&self) -> Option<u8> {
+        self.last_page_0x01.map(|page| page.shift_failures())
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.battery_status());
+        }
+        None
+    }
+
+    pub fn battery_voltage(&self) -> Option<f32> {
+        if let Some(page) = &self.page_0x52 {
+            return page.battery_voltage();
+        }
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.serial_number());
+        }
+        None
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.manufacturer());
+        }
+        None
+    }
+
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] {
+            0x01 => {
+                let p = Page0x01(data);
+                // The event count increments on every shift, so count shifts from the change in
+                // event count against the last page.
+                if let Some(last_page) = &self.last_page_0x01 {
+                    self.shift_count +=
+                        p.event_count().wrapping_sub(last_page.event_count()) as u32;
+                }
+                self.last_page_0x01 = Some(p);
+            }
+            0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(data)),
+            0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(data)),
+            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            _ => {} // Do nothing with rest of pages for now.
+        }
+    }
+}
+
+// Shift System Status Page
+// [1] - Event count
+// [3] - Current gear, rear in bits 0-4 and front in bits 5-7
+// [4] - Total gears, rear in bits 0-4 and front in bits 5-7
+// [5] - Invalid inbound shift count
+// [6] - Invalid outbound shift count
+// [7] - Shift failure count
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x01([u8; 8]);
+
+impl Page0x01 {
+    fn event_count(&self) -> u8 {
+        self.0[1]
+    }
+
+    fn rear_gear(&self) -> u8 {
+        self.0[3] & 0x1F
+    }
+
+    fn front_gear(&self) -> u8 {
+        self.0[3] >> 5
+    }
+
+    fn total_rear_gears(&self) -> u8 {
+        self.0[4] & 0x1F
+    }
+
+    fn total_front_gears(&self) -> u8 {
+        self.0[4] >> 5
+    }
+
+    fn invalid_inbound_shifts(&self) -> u8 {
+        self.0[5]
+    }
+
+    fn invalid_outbound_shifts(&self) -> u8 {
+        self.0[6]
+    }
+
+    fn shift_failures(&self) -> u8 {
+        self.0[7]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_shifting_decode() {
+        let mut shifting = Shifting::new();
+        // Front gear 1 of 2, rear gear 7 of 11.
+        shifting.decode([0x01, 0xFE, 0xFF, 0x27, 0x4B, 0x01, 0x00, 0x00]);
+        assert_eq!(shifting.front_gear(), Some(1));
+        assert_eq!(shifting.rear_gear(), Some(7));
+        assert_eq!(shifting.total_front_gears(), Some(2));
+        assert_eq!(shifting.total_rear_gears(), Some(11));
+        assert_eq!(shifting.invalid_inbound_shift_count(), Some(1));
+        assert_eq!(shifting.shift_count(), 0);
+        // Three shifts with the event count rolling over, and no front derailleur.
+        shifting.decode([0x01, 0x01, 0xFF, 0x04, 0x0B, 0x01, 0x00, 0x02]);
+        assert_eq!(shifting.shift_count(), 3);
+        assert_eq!(shifting.front_gear(), None);
+        assert_eq!(shifting.rear_gear(), Some(4));
+        assert_eq!(shifting.shift_failure_count(), Some(2));
+    }
+}
@@ -0,0 +1,201 @@
+use super::{BatteryStatus, Manufacturer};
+use crate::channel::Config;
+/// Bicycle speed sensor. Every data page includes the bike speed event time and the cumulative
+/// wheel revolution count. The MSB of the page number toggles every four pages for devices that
+/// support background pages.
+use crate::message::{bytes_to_u16, bytes_to_u32};
+
+const SPEED_DEVICE_TYPE: u8 = 0x7B;
+const SPEED_FREQUENCY: u8 = 0x39;
+const SPEED_PERIOD: u16 = 8118;
+// Default wheel circumference in meters for a 700x23c tire.
+const DEFAULT_WHEEL_CIRCUMFERENCE: f32 = 2.096;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpeedSensor {
+    wheel_circumference: f32,
+    speed: f32,
+    revolutions: u32,
+    last_event_time: Option<u16>,
+    last_revolution_count: Option<u16>,
+    operating_time: u32,
+    manufacturer_id: u8,
+    serial_number: u16,
+    hardware_version: u8,
+    software_version: u8,
+    model_number: u8,
+    fractional_battery_voltage: u8,
+    descriptive_bit_field: u8,
+    stopped: bool,
+}
+
+impl SpeedSensor {
+    pub fn new() -> Self {
+        SpeedSensor {
+            wheel_circumference: DEFAULT_WHEEL_CIRCUMFERENCE,
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(SPEED_DEVICE_TYPE)
+            .frequency(SPEED_FREQUENCY)
+            .period(SPEED_PERIOD)
+    }
+
+    /// Set the wheel circumference in meters used to calculate speed and distance.
+    pub fn set_wheel_circumference(&mut self, circumference: f32) {
+        self.wheel_circumference = circumference;
+    }
+
+    /// Wheel circumference in meters.
+    pub fn wheel_circumference(&self) -> f32 {
+        self.wheel_circumference
+    }
+
+    /// Instantaneous speed in meters per second calculated from the last two events.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Instantaneous speed in kilometers per hour.
+    pub fn speed_in_kph(&self) -> f32 {
+        self.speed * 3.6
+    }
+
+    /// Cumulative wheel revolution count as reported by the sensor. Rolls over at 65536.
+    pub fn revolution_count(&self) -> u16 {
+        self.last_revolution_count.unwrap_or(0)
+    }
+
+    /// Wheel revolutions counted since the first page was received.
+    pub fn revolutions(&self) -> u32 {
+        self.revolutions
+    }
+
+    /// Distance traveled in meters since the first page was received.
+    pub fn distance(&self) -> f32 {
+        self.revolutions as f32 * self.wheel_circumference
+    }
+
+    /// Cumulative operating time in seconds.
+    pub fn operating_time(&self) -> u32 {
+        self.operating_time
+    }
+
+    /// Manufacturer of the hardware device
+    pub fn manufacturer(&self) -> Manufacturer {
+        Manufacturer::from(self.manufacturer_id as u16)
+    }
+
+    /// Serial number of device, typically the ANT+ ID
+    pub fn serial_number(&self) -> u16 {
+        self.serial_number
+    }
+
+    /// Hardware version of the device
+    pub fn hardware_version(&self) -> u8 {
+        self.hardware_version
+    }
+
+    /// Software version of the device
+    pub fn software_version(&self) -> u8 {
+        self.software_version
+    }
+
+    /// Model number of the device
+    pub fn model_number(&self) -> u8 {
+        self.model_number
+    }
+
+    /// Battery voltage in volts. Returns None if the voltage is invalid.
+    pub fn battery_voltage(&self) -> Option<f32> {
+        let coarse = self.descriptive_bit_field & 0x0F;
+        if coarse == 0x0F {
+            return None;
+        }
+        Some(coarse as f32 + self.fractional_battery_voltage as f32 / 256_f32)
+    }
+
+    /// Battery status from the data provided by the device.
+    pub fn battery_status(&self) -> BatteryStatus {
+        BatteryStatus::from(self.descriptive_bit_field)
+    }
+
+    /// Whether the sensor has signaled the bike is stopped.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Decode broadcast data received from the speed sensor.
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] & 0x7F {
+            // Data page 0 Default data page
+            0x00 => {}
+            // Data page 1 Cumulative Operating Time
+            0x01 => self.operating_time = bytes_to_u32(&data[1..4]) * 2,
+            // Data page 2 Manufacturer Information
+            0x02 => {
+                self.manufacturer_id = data[1];
+                self.serial_number = bytes_to_u16(&data[2..4]);
+            }
+            // Data page 3 Product Information
+            0x03 => {
+                self.hardware_version = data[1];
+                self.software_version = data[2];
+                self.model_number = data[3];
+            }
+            // Data page 4 Battery Status
+            0x04 => {
+                self.fractional_battery_voltage = data[2];
+                self.descriptive_bit_field = data[3];
+            }
+            // Data page 5 Motion and Speed
+            0x05 => self.stopped = data[1] & 0x01 == 0x01,
+            _ => return, // Drop message if none of these pages
+        }
+        self.update_speed(bytes_to_u16(&data[4..6]), bytes_to_u16(&data[6..]));
+    }
+
+    // Speed is calculated from the change in revolution count over the change in event time
+    // since the last page. Both fields roll over at 65536.
+    fn update_speed(&mut self, event_time: u16, revolution_count: u16) {
+        if let (Some(last_event_time), Some(last_revolution_count)) =
+            (self.last_event_time, self.last_revolution_count)
+        {
+            let time_delta = event_time.wrapping_sub(last_event_time);
+            let rev_delta = revolution_count.wrapping_sub(last_revolution_count);
+            self.revolutions += rev_delta as u32;
+            if self.stopped {
+                self.speed = 0.0;
+            } else if time_delta != 0 {
+                self.speed =
+                    self.wheel_circumference * rev_delta as f32 / (time_delta as f32 / 1024_f32);
+            }
+        }
+        self.last_event_time = Some(event_time);
+        self.last_revolution_count = Some(revolution_count);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_speed_decode() {
+        let mut sensor = SpeedSensor::new();
+        sensor.set_wheel_circumference(2.0);
+        sensor.decode([0x00, 0xFF, 0xFF, 0xFF, 0x00, 0xFC, 0xFE, 0xFF]);
+        assert_eq!(sensor.speed(), 0.0);
+        assert_eq!(sensor.revolution_count(), 0xFFFE);
+        // Event time and revolution count both roll over.
+        sensor.decode([0x80, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x03, 0x00]);
+        assert_eq!(sensor.revolutions(), 5);
+        // 5 revolutions * 2m / 1s
+        assert_eq!(sensor.speed(), 10.0);
+        assert_eq!(sensor.distance(), 10.0);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::channel::Config;
+/// Combined bicycle speed and cadence sensor, such as the Garmin GSC-10. The device only sends a
+/// single data page without a page number containing both crank and wheel event data.
+use crate::message::bytes_to_u16;
+
+const SPEED_CADENCE_DEVICE_TYPE: u8 = 0x79;
+const SPEED_CADENCE_FREQUENCY: u8 = 0x39;
+const SPEED_CADENCE_PERIOD: u16 = 8086;
+// Default wheel circumference in meters for a 700x23c tire.
+const DEFAULT_WHEEL_CIRCUMFERENCE: f32 = 2.096;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpeedCadenceSensor {
+    wheel_circumference: f32,
+    speed: f32,
+    cadence: u8,
+    wheel_revolutions: u32,
+    crank_revolutions: u32,
+    last_page: Option<[u8; 8]>,
+}
+
+impl SpeedCadenceSensor {
+    pub fn new() -> Self {
+        SpeedCadenceSensor {
+            wheel_circumference: DEFAULT_WHEEL_CIRCUMFERENCE,
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(SPEED_CADENCE_DEVICE_TYPE)
+            .frequency(SPEED_CADENCE_FREQUENCY)
+            .period(SPEED_CADENCE_PERIOD)
+    }
+
+    /// Set the wheel circumference in meters used to calculate speed and distance.
+    pub fn set_wheel_circumference(&mut self, circumference: f32) {
+        self.wheel_circumference = circumference;
+    }
+
+    /// Wheel circumference in meters.
+    pub fn wheel_circumference(&self) -> f32 {
+        self.wheel_circumference
+    }
+
+    /// Instantaneous speed in meters per second calculated from the last two wheel events.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Instantaneous speed in kilometers per hour.
+    pub fn speed_in_kph(&self) -> f32 {
+        self.speed * 3.6
+    }
+
+    /// Cadence in RPM calculated from the last two crank events.
+    pub fn cadence(&self) -> u8 {
+        self.cadence
+    }
+
+    /// Wheel revolutions counted since the first page was received.
+    pub fn wheel_revolutions(&self) -> u32 {
+        self.wheel_revolutions
+    }
+
+    /// Crank revolutions counted since the first page was received.
+    pub fn crank_revolutions(&self) -> u32 {
+        self.crank_revolutions
+    }
+
+    /// Distance traveled in meters since the first page was received.
+    pub fn distance(&self) -> f32 {
+        self.wheel_revolutions as f32 * self.wheel_circumference
+    }
+
+    /// Decode broadcast data received from the speed and cadence sensor.
+    // Byte layout of the combined data page. All fields roll over at 65536.
+    // [0..2] - Cadence event time (1/1024s)
+    // [2..4] - Cumulative crank revolution count
+    // [4..6] - Speed event time (1/1024s)
+    // [6..8] - Cumulative wheel revolution count
+    pub fn decode(&mut self, data: [u8; 8]) {
+        if let Some(last_page) = self.last_page {
+            let cadence_time_delta =
+                bytes_to_u16(&data[0..2]).wrapping_sub(bytes_to_u16(&last_page[0..2]));
+            let crank_delta =
+                bytes_to_u16(&data[2..4]).wrapping_sub(bytes_to_u16(&last_page[2..4]));
+            let speed_time_delta =
+                bytes_to_u16(&data[4..6]).wrapping_sub(bytes_to_u16(&last_page[4..6]));
+            let wheel_delta = bytes_to_u16(&data[6..]).wrapping_sub(bytes_to_u16(&last_page[6..]));
+
+            self.crank_revolutions += crank_delta as u32;
+            self.wheel_revolutions += wheel_delta as u32;
+            if cadence_time_delta != 0 {
+                self.cadence = (60_f32 * crank_delta as f32
+                    / (cadence_time_delta as f32 / 1024_f32))
+                    .round() as u8;
+            }
+            if speed_time_delta != 0 {
+                self.speed = self.wheel_circumference * wheel_delta as f32
+                    / (speed_time_delta as f32 / 1024_f32);
+            }
+        }
+        self.last_page = Some(data);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_speed_cadence_decode() {
+        let mut sensor = SpeedCadenceSensor::new();
+        sensor.set_wheel_circumference(2.0);
+        sensor.decode([0x00, 0xFE, 0xFF, 0xFF, 0x00, 0xFC, 0xFE, 0xFF]);
+        assert_eq!(sensor.cadence(), 0);
+        assert_eq!(sensor.speed(), 0.0);
+        sensor.decode([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00]);
+        assert_eq!(sensor.crank_revolutions(), 1);
+        assert_eq!(sensor.cadence(), 120);
+        assert_eq!(sensor.wheel_revolutions(), 5);
+        assert_eq!(sensor.speed(), 10.0);
+    }
+}
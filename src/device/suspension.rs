@@ -0,0 +1,263 @@
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52};
+use crate::channel::Config;
+use crate::message::AcknowledgeDataMessage;
+
+// Constant values for the suspension channel.
+const SUSPENSION_DEVICE_TYPE: u8 = 0x15;
+const SUSPENSION_FREQUENCY: u8 = 0x39;
+const SUSPENSION_PERIOD: u16 = 8192;
+const SUSPENSION_CONTROL_PAGE: u8 = 0x10;
+
+// Suspension provides a way to decode the state of electronically controlled suspension and
+// dropper posts, and to build the command pages used to change the damper mode.
+// Page 0x01 -> Suspension Status
+// Page 0x10 -> Suspension Control (command)
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+// Page 0x52 -> Battery Voltage
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Suspension {
+    page_0x01: Option<Page0x01>,
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+}
+
+impl Suspension {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(SUSPENSION_DEVICE_TYPE)
+            .frequency(SUSPENSION_FREQUENCY)
+            .period(SUSPENSION_PERIOD)
+    }
+
+    /// Current mode of the front damper, if fitted.
+    pub fn front_mode(&self) -> Option<DamperMode> {
+        self.page_0x01.and_then(|page| page.front_mode())
+    }
+
+    /// Current mode of the rear damper, if fitted.
+    pub fn rear_mode(&self) -> Option<DamperMode> {
+        self.page_0x01.and_then(|page| page.rear_mode())
+    }
+
+    /// Current position of the dropper post, if fitted.
+    pub fn dropper_position(&self) -> Option<DropperPosition> {
+        self.page_0x01.and_then(|page| page.dropper_position())
+    }
+
+    /// Sequence number of the last command processed by the suspension.
+    pub fn sequence_number(&self) -> Option<u8> {
+        self.page_0x01.map(|page| page.sequence_number())
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.battery_status());
+        }
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.serial_number());
+        }
+        None
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.manufacturer());
+        }
+        None
+    }
+
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] {
+            0x01 => self.page_0x01 = Some(Page0x01(data)),
+            0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(data)),
+            0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(data)),
+            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            _ => {} // Do nothing with rest of pages for now.
+        }
+    }
+
+    /// Sends an Acknowledge data page to the suspension changing the mode of both dampers.
+    pub fn set_mode(&self, channel_number: u8, mode: DamperMode) -> AcknowledgeDataMessage {
+        AcknowledgeDataMessage::new(
+            channel_number,
+            &self.suspension_control(mode.into(), mode.into(), 0xFF),
+        )
+    }
+
+    /// Sends an Acknowledge data page to the suspension changing the mode of the front damper
+    /// only.
+    pub fn set_front_mode(&self, channel_number: u8, mode: DamperMode) -> AcknowledgeDataMessage {
+        AcknowledgeDataMessage::new(
+            channel_number,
+            &self.suspension_control(mode.into(), 0xFF, 0xFF),
+        )
+    }
+
+    /// Sends an Acknowledge data page to the suspension changing the mode of the rear damper
+    /// only.
+    pub fn set_rear_mode(&self, channel_number: u8, mode: DamperMode) -> AcknowledgeDataMessage {
+        AcknowledgeDataMessage::new(
+            channel_number,
+            &self.suspension_control(0xFF, mode.into(), 0xFF),
+        )
+    }
+
+    /// Sends an Acknowledge data page to the dropper post raising or lowering it.
+    pub fn set_dropper_position(
+        &self,
+        channel_number: u8,
+        position: DropperPosition,
+    ) -> AcknowledgeDataMessage {
+        AcknowledgeDataMessage::new(
+            channel_number,
+            &self.suspension_control(0xFF, 0xFF, position.into()),
+        )
+    }
+
+    // The suspension control page. Fields set to 0xFF are left unchanged by the suspension. The
+    // sequence number is incremented from the last processed command so new commands are
+    // detected.
+    fn suspension_control(&self, front: u8, rear: u8, dropper: u8) -> [u8; 8] {
+        [
+            SUSPENSION_CONTROL_PAGE,
+            self.sequence_number().unwrap_or(0).wrapping_add(1),
+            front,
+            rear,
+            dropper,
+            0xFF,
+            0xFF,
+            0xFF,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DamperMode {
+    Open,
+    Trail,
+    Lockout,
+}
+
+impl DamperMode {
+    fn from(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Open),
+            1 => Some(Self::Trail),
+            2 => Some(Self::Lockout),
+            _ => None, // 0xFF when not fitted
+        }
+    }
+}
+
+impl From<DamperMode> for u8 {
+    fn from(mode: DamperMode) -> u8 {
+        match mode {
+            DamperMode::Open => 0,
+            DamperMode::Trail => 1,
+            DamperMode::Lockout => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DropperPosition {
+    Raised,
+    Lowered,
+}
+
+impl DropperPosition {
+    fn from(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Raised),
+            1 => Some(Self::Lowered),
+            _ => None, // 0xFF when not fitted
+        }
+    }
+}
+
+impl From<DropperPosition> for u8 {
+    fn from(position: DropperPosition) -> u8 {
+        match position {
+            DropperPosition::Raised => 0,
+            DropperPosition::Lowered => 1,
+        }
+    }
+}
+
+// Suspension Status Page
+// [1] - Sequence number of the last command processed
+// [2] - Front damper mode, 0xFF if not fitted
+// [3] - Rear damper mode, 0xFF if not fitted
+// [4] - Dropper post position, 0xFF if not fitted
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x01([u8; 8]);
+
+impl Page0x01 {
+    fn sequence_number(&self) -> u8 {
+        self.0[1]
+    }
+
+    fn front_mode(&self) -> Option<DamperMode> {
+        DamperMode::from(self.0[2])
+    }
+
+    fn rear_mode(&self) -> Option<DamperMode> {
+        DamperMode::from(self.0[3])
+    }
+
+    fn dropper_position(&self) -> Option<DropperPosition> {
+        DropperPosition::from(self.0[4])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_suspension_decode() {
+        let mut suspension = Suspension::new();
+        assert_eq!(suspension.front_mode(), None);
+        // Front fork in trail mode, no rear shock, dropper post lowered.
+        suspension.decode([0x01, 0x04, 0x01, 0xFF, 0x01, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(suspension.sequence_number(), Some(4));
+        assert_eq!(suspension.front_mode(), Some(DamperMode::Trail));
+        assert_eq!(suspension.rear_mode(), None);
+        assert_eq!(
+            suspension.dropper_position(),
+            Some(DropperPosition::Lowered)
+        );
+    }
+
+    #[test]
+    fn test_suspension_commands() {
+        let mut suspension = Suspension::new();
+        suspension.decode([0x01, 0x04, 0x01, 0xFF, 0x01, 0xFF, 0xFF, 0xFF]);
+        let mesg = suspension.set_mode(2, DamperMode::Lockout);
+        assert_eq!(mesg.channel(), 2);
+        assert_eq!(
+            mesg.data(),
+            [0x10, 0x05, 0x02, 0x02, 0xFF, 0xFF, 0xFF, 0xFF]
+        );
+        let mesg = suspension.set_rear_mode(2, DamperMode::Open);
+        assert_eq!(&mesg.data()[2..5], [0xFF, 0x00, 0xFF]);
+        let mesg = suspension.set_dropper_position(2, DropperPosition::Raised);
+        assert_eq!(&mesg.data()[2..5], [0xFF, 0xFF, 0x00]);
+    }
+}
@@ -0,0 +1,164 @@
+use super::{BatteryStatus, Manufacturer, Page0x50, Page0x51, Page0x52};
+use crate::channel::Config;
+use crate::message::bytes_to_u16;
+
+// Constant values for the tire pressure monitoring channel.
+const TPMS_DEVICE_TYPE: u8 = 0x30;
+const TPMS_FREQUENCY: u8 = 0x39;
+const TPMS_PERIOD: u16 = 8192;
+
+// TirePressureMonitor provides a way to decode the broadcast data sent from a tire pressure
+// monitoring sensor mounted on a valve stem.
+// Page 0x01 -> Tire Pressure
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+// Page 0x52 -> Battery Voltage
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TirePressureMonitor {
+    page_0x01: Option<Page0x01>,
+    page_0x50: Option<Page0x50>,
+    page_0x51: Option<Page0x51>,
+    page_0x52: Option<Page0x52>,
+}
+
+impl TirePressureMonitor {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_config() -> Config {
+        Config::new()
+            .device_type(TPMS_DEVICE_TYPE)
+            .frequency(TPMS_FREQUENCY)
+            .period(TPMS_PERIOD)
+    }
+
+    /// Tire pressure in millibar. None if the sensor reports an invalid pressure.
+    pub fn pressure(&self) -> Option<u16> {
+        match self.page_0x01 {
+            Some(page) if page.pressure() != 0xFFFF => Some(page.pressure()),
+            _ => None,
+        }
+    }
+
+    /// Tire pressure in PSI.
+    pub fn pressure_in_psi(&self) -> Option<f32> {
+        self.pressure().map(|mbar| mbar as f32 * 0.0145038)
+    }
+
+    /// Position of the tire the sensor is mounted on.
+    pub fn position(&self) -> Option<SensorPosition> {
+        self.page_0x01.map(|page| page.position())
+    }
+
+    /// Whether the sensor has signaled a pressure alarm.
+    pub fn alarm(&self) -> Option<bool> {
+        self.page_0x01.map(|page| page.alarm())
+    }
+
+    pub fn battery_status(&self) -> Option<BatteryStatus> {
+        if let Some(page) = &self.page_0x52 {
+            return Some(page.battery_status());
+        }
+        None
+    }
+
+    pub fn battery_voltage(&self) -> Option<f32> {
+        if let Some(page) = &self.page_0x52 {
+            return page.battery_voltage();
+        }
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<u32> {
+        if let Some(page) = &self.page_0x51 {
+            return Some(page.serial_number());
+        }
+        None
+    }
+
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if let Some(page) = &self.page_0x50 {
+            return Some(page.manufacturer());
+        }
+        None
+    }
+
+    pub fn decode(&mut self, data: [u8; 8]) {
+        match data[0] {
+            0x01 => self.page_0x01 = Some(Page0x01(data)),
+            0x50 if self.page_0x50.is_none() => self.page_0x50 = Some(Page0x50(data)),
+            0x51 if self.page_0x51.is_none() => self.page_0x51 = Some(Page0x51(data)),
+            0x52 => self.page_0x52 = Some(Page0x52(data)),
+            _ => {} // Do nothing with rest of pages for now.
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SensorPosition {
+    Front,
+    Rear,
+    Unknown(u8),
+}
+
+impl SensorPosition {
+    fn from(value: u8) -> Self {
+        match value & 0x0F {
+            0 => Self::Front,
+            1 => Self::Rear,
+            v => Self::Unknown(v),
+        }
+    }
+}
+
+// Tire Pressure Page
+// [1] - Sensor position in bits 0-3, alarm in bit 7
+// [6..8] - Pressure in mbar
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Page0x01([u8; 8]);
+
+impl Page0x01 {
+    fn position(&self) -> SensorPosition {
+        SensorPosition::from(self.0[1])
+    }
+
+    fn alarm(&self) -> bool {
+        self.0[1] & 0x80 == 0x80
+    }
+
+    fn pressure(&self) -> u16 {
+        bytes_to_u16(&self.0[6..])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tpms_decode() {
+        let mut tpms = TirePressureMonitor::new();
+        assert_eq!(tpms.pressure(), None);
+        // 5000 mbar on the rear tire.
+        tpms.decode([0x01, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0x88, 0x13]);
+        assert_eq!(tpms.pressure(), Some(5000));
+        assert!((tpms.pressure_in_psi().unwrap() - 72.519).abs() < 0.001);
+        assert_eq!(tpms.position(), Some(SensorPosition::Rear));
+        assert_eq!(tpms.alarm(), Some(false));
+        // Alarm with an invalid pressure.
+        tpms.decode([0x01, 0x80, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(tpms.pressure(), None);
+        assert_eq!(tpms.position(), Some(SensorPosition::Front));
+        assert_eq!(tpms.alarm(), Some(true));
+        // 2.5V battery.
+        tpms.decode([0x52, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x80, 0x22]);
+        assert_eq!(tpms.battery_voltage(), Some(2.5));
+        assert!(matches!(tpms.battery_status(), Some(BatteryStatus::Good)));
+    }
+}
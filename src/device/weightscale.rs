@@ -1,3 +1,4 @@
+use super::Page0x53;
 use crate::channel::Config;
 /// Weightscale device for reading weight and potentially other data from the scale.
 // TODO Finish building out this to support all fields that could be returned from the weightscale.
@@ -11,14 +12,25 @@ const WS_FREQUENCY: u8 = 0x39;
 const WS_PERIOD: u16 = 8192;
 const WS_TIMEOUT: u8 = 10;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WeightScale {
     weight: f32, //default in KG
+    hydration: Option<f32>,
+    body_fat: Option<f32>,
+    active_metabolic_rate: Option<f32>,
+    basal_metabolic_rate: Option<f32>,
+    muscle_mass: Option<f32>,
+    bone_mass: Option<f32>,
+    page_0x53: Option<Page0x53>,
 }
 
 impl WeightScale {
     pub fn new() -> Self {
-        Self { weight: 0.0 }
+        Self {
+            weight: 0.0,
+            ..Default::default()
+        }
     }
 
     pub fn channel_config() -> Config {
@@ -39,6 +51,42 @@ impl WeightScale {
         self.weight * 2.2
     }
 
+    /// Hydration as a percentage of body weight.
+    pub fn hydration(&self) -> Option<f32> {
+        self.hydration
+    }
+
+    /// Body fat as a percentage of body weight.
+    pub fn body_fat(&self) -> Option<f32> {
+        self.body_fat
+    }
+
+    /// Active metabolic rate in kcal.
+    pub fn active_metabolic_rate(&self) -> Option<f32> {
+        self.active_metabolic_rate
+    }
+
+    /// Basal metabolic rate in kcal.
+    pub fn basal_metabolic_rate(&self) -> Option<f32> {
+        self.basal_metabolic_rate
+    }
+
+    /// Muscle mass in Kilograms.
+    pub fn muscle_mass(&self) -> Option<f32> {
+        self.muscle_mass
+    }
+
+    /// Bone mass in Kilograms.
+    pub fn bone_mass(&self) -> Option<f32> {
+        self.bone_mass
+    }
+
+    /// Time and date last reported by the weightscale. The weightscale's clock can be set by
+    /// sending a Page0x53 to it as acknowledged data.
+    pub fn date_time(&self) -> Option<Page0x53> {
+        self.page_0x53
+    }
+
     /// Decode broadcast data from the weightscale.
     // TODO: Properly decode the page and other pages that are part of the
     // ANT+ device that can be returned by a weightscale.
@@ -52,12 +100,93 @@ impl WeightScale {
                         self.weight = bytes_to_u16(&data[7..]) as f32 / 100.0;
                     }
                 }
+                // Body composition percentage
+                0x02 => {
+                    if let Some(hydration) = scaled_u16(&data[5..7], 100.0) {
+                        self.hydration = Some(hydration);
+                    }
+                    if let Some(body_fat) = scaled_u16(&data[7..], 100.0) {
+                        self.body_fat = Some(body_fat);
+                    }
+                }
+                // Metabolic information in 0.25 kcal increments
+                0x03 => {
+                    if let Some(amr) = scaled_u16(&data[5..7], 4.0) {
+                        self.active_metabolic_rate = Some(amr);
+                    }
+                    if let Some(bmr) = scaled_u16(&data[7..], 4.0) {
+                        self.basal_metabolic_rate = Some(bmr);
+                    }
+                }
+                // Body composition mass
+                0x04 => {
+                    if let Some(muscle_mass) = scaled_u16(&data[6..8], 100.0) {
+                        self.muscle_mass = Some(muscle_mass);
+                    }
+                    // Bone mass is a single byte in 0.1kg increments.
+                    if data[8] != 0xFF && data[8] != 0xFE {
+                        self.bone_mass = Some(data[8] as f32 / 10.0);
+                    }
+                }
+                0x53 => {
+                    let mut page = [0; 8];
+                    page.copy_from_slice(&data[1..]);
+                    self.page_0x53 = Some(Page0x53(page));
+                }
                 _ => {}
             }
         }
     }
 }
 
+// Scales a u16 field from a weightscale page. 0xFFFE signals the scale is still computing the
+// value and 0xFFFF signals the value is invalid.
+fn scaled_u16(data: &[u8], scale: f32) -> Option<f32> {
+    match bytes_to_u16(data) {
+        0xFFFE | 0xFFFF => None,
+        value => Some(value as f32 / scale),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_body_composition() {
+        let mut ws = WeightScale::new();
+        assert_eq!(ws.body_fat(), None);
+        // 55.5% hydration and 21.3% body fat.
+        ws.decode_broadcast_data(&[0x00, 0x02, 0xFF, 0xFF, 0xFF, 0xAE, 0x15, 0x52, 0x08]);
+        assert_eq!(ws.hydration(), Some(55.5));
+        assert_eq!(ws.body_fat(), Some(21.3));
+        // 2000 kcal active and 1600 kcal basal metabolic rate.
+        ws.decode_broadcast_data(&[0x00, 0x03, 0xFF, 0xFF, 0xFF, 0x40, 0x1F, 0x00, 0x19]);
+        assert_eq!(ws.active_metabolic_rate(), Some(2000.0));
+        assert_eq!(ws.basal_metabolic_rate(), Some(1600.0));
+        // 32.5kg muscle mass and 3.1kg bone mass.
+        ws.decode_broadcast_data(&[0x00, 0x04, 0xFF, 0xFF, 0xFF, 0xFF, 0xB2, 0x0C, 0x1F]);
+        assert_eq!(ws.muscle_mass(), Some(32.5));
+        assert_eq!(ws.bone_mass(), Some(3.1));
+    }
+
+    #[test]
+    fn test_body_composition_computing() {
+        let mut ws = WeightScale::new();
+        ws.decode_broadcast_data(&[0x00, 0x02, 0xFF, 0xFF, 0xFF, 0xFE, 0xFF, 0xFF, 0xFF]);
+        ws.decode_broadcast_data(&[0x00, 0x04, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0xFF, 0xFE]);
+        assert_eq!(ws.hydration(), None);
+        assert_eq!(ws.body_fat(), None);
+        assert_eq!(ws.muscle_mass(), None);
+        assert_eq!(ws.bone_mass(), None);
+        // Values already received are kept while the scale computes new ones.
+        ws.decode_broadcast_data(&[0x00, 0x02, 0xFF, 0xFF, 0xFF, 0xAE, 0x15, 0x52, 0x08]);
+        ws.decode_broadcast_data(&[0x00, 0x02, 0xFF, 0xFF, 0xFF, 0xFE, 0xFF, 0xFE, 0xFF]);
+        assert_eq!(ws.hydration(), Some(55.5));
+        assert_eq!(ws.body_fat(), Some(21.3));
+    }
+}
+
 // TODO Move testing of config into crate::channel and focus testing in devices
 // to decoding of data.
 /*#[cfg(test)]
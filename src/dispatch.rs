@@ -0,0 +1,99 @@
+/// The dispatch module provides Dispatcher, an alternative to receiving responses from the run
+/// loop where handlers are registered for all responses or for the responses of a single
+/// channel, for applications that already have an event system to forward responses into.
+use std::collections::BTreeMap;
+
+use crossbeam_channel::Receiver;
+
+use crate::ant::Response;
+
+/// Handler invoked with responses from the run loop.
+pub type Handler = Box<dyn FnMut(Response) + Send>;
+
+/// Dispatcher invokes the handler registered for a response's channel, or the handler for all
+/// responses if the channel has no handler. Responses without a handler are dropped.
+///
+/// let dispatcher = Dispatcher::new()
+///     .on_channel(0, move |response| hrm_events.push(response))
+///     .on_response(|response| println!("{:?}", response));
+/// let runtime = libant::runtime::spawn_with_dispatcher(RunConfig::new(), dispatcher);
+#[derive(Default)]
+pub struct Dispatcher {
+    handler: Option<Handler>,
+    channels: BTreeMap<u8, Handler>,
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the handler for responses not handled by a channel handler, replacing any handler
+    /// already set.
+    pub fn on_response<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(Response) + Send + 'static,
+    {
+        self.handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets the handler for the responses of a channel, replacing any handler already set for
+    /// the channel.
+    pub fn on_channel<F>(mut self, number: u8, handler: F) -> Self
+    where
+        F: FnMut(Response) + Send + 'static,
+    {
+        self.channels.insert(number, Box::new(handler));
+        self
+    }
+
+    /// Invokes the handler for a response.
+    pub fn dispatch(&mut self, response: Response) {
+        let handler = match response.channel() {
+            Some(number) if self.channels.contains_key(&number) => self.channels.get_mut(&number),
+            _ => self.handler.as_mut(),
+        };
+        if let Some(handler) = handler {
+            handler(response);
+        }
+    }
+
+    /// Dispatches responses until the run loop drops its Sender.
+    pub fn run(mut self, responses: Receiver<Response>) {
+        for response in responses.iter() {
+            self.dispatch(response);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn dispatch_to_handlers() {
+        let all = Arc::new(Mutex::new(Vec::new()));
+        let hrm = Arc::new(Mutex::new(Vec::new()));
+        let mut dispatcher = {
+            let all = all.clone();
+            let hrm = hrm.clone();
+            Dispatcher::new()
+                .on_response(move |response| all.lock().unwrap().push(response))
+                .on_channel(1, move |response| hrm.lock().unwrap().push(response))
+        };
+
+        dispatcher.dispatch(Response::ChannelOpened(0));
+        dispatcher.dispatch(Response::ChannelOpened(1));
+        dispatcher.dispatch(Response::StickDisconnected);
+
+        let all = all.lock().unwrap();
+        let hrm = hrm.lock().unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(matches!(all[0], Response::ChannelOpened(0)));
+        assert!(matches!(all[1], Response::StickDisconnected));
+        assert_eq!(hrm.len(), 1);
+        assert!(matches!(hrm[0], Response::ChannelOpened(1)));
+    }
+}
@@ -8,16 +8,49 @@ use thiserror::Error;
 pub enum AntError {
     #[error("{0}")]
     UsbDeviceError(#[from] USBError),
+    #[error("Unable to write message {id:#x}: {source}")]
+    WriteError { id: u8, source: USBError },
     #[error("Unable to decode message(s)")]
     UnableToDecode,
+    #[error("Malformed message {id:#x} with {len} bytes of data")]
+    Malformed { id: u8, len: usize },
+    #[error("Payload of {len} bytes is too large for message {id:#x}")]
+    PayloadTooLarge { id: u8, len: usize },
     #[error("Unable to send request message")]
     RequestSendError(SendError<Message>),
     #[error("Unable to receive message")]
     MessageTryRecvError(TryRecvError),
     #[error("ANT+ run loop already running")]
     AlreadyRunning,
+    #[error("ANT+ run loop is no longer running")]
+    RunLoopDisconnected,
     #[error("Unable to reset ANT+ USB stick")]
     Reset,
     #[error("Channel {0} already exists")]
     ChannelExists(u8),
+    #[error("Channel {0} is not supported by the ANT+ USB stick")]
+    InvalidChannel(u8),
+    #[error("Channel {0} is not an open master channel")]
+    NotMasterChannel(u8),
+    #[error("Selecting an ANT+ USB device by address is not supported on this platform")]
+    AddressNotSupported,
+    #[error("Event buffering is not configured on the ANT+ USB stick")]
+    EventBufferNotConfigured,
+    #[error("Command page {0:#x} is not supported by the device")]
+    UnsupportedCommand(u8),
+    #[error("Burst transfer on channel {0} received out of sequence")]
+    BurstSequenceError(u8),
+    #[error("Invalid trace at line {0}")]
+    InvalidTrace(usize),
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+}
+
+// Serializes an error as its message for types that carry an AntError.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize<S: serde::Serializer>(
+    error: &AntError,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(error)
 }
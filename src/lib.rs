@@ -15,7 +15,9 @@
 /// In the application, the ant run loop can be started by the following since it is
 /// a blocking process.
 ///
-/// let run_handle = std::thread::spawn(move || libant::ant::run(request_rx, message_tx));
+/// let run_handle = std::thread::spawn(move || {
+///     libant::ant::run(request_rx, message_tx, libant::RunConfig::new())
+/// });
 ///
 /// Internally, the library manages configured channels for channel configuration data only.
 /// All broadcast data is sent directly to the client to handle decoding. The library does provide
@@ -38,6 +40,104 @@
 ///     }
 /// }
 ///
+/// spawn starts the run loop on a background thread and returns an AntRuntime. Dropping the
+/// AntRuntime sends Quit and waits for the run loop to exit, releasing the ANT+ USB device.
+///
+/// let runtime = libant::spawn(libant::RunConfig::new());
+/// let request_tx = runtime.sender();
+/// let message_rx = runtime.receiver().unwrap();
+///
+/// AntClient wraps the request and message channels and hands out channel numbers so they
+/// don't have to be tracked by the application.
+/// use libant::AntClient;
+///
+/// let mut client = AntClient::new(request_tx, message_rx);
+/// let hrm_channel = client.open_channel(HeartRateMonitor::channel_config())?;
+///
+/// loop {
+///     match client.recv()? {
+///         Response::BroadcastData(mesg) if mesg.channel() == hrm_channel.number() => {
+///             hrm.decode_broadcast_data(mesg.data());
+///         }
+///         _ => {}
+///     }
+/// }
+///
+/// With the futures feature enabled, responses for a channel can be read as a Stream.
+/// use futures::StreamExt;
+///
+/// let mut hrm_responses = client.channel_stream(&hrm_channel);
+/// while let Some(response) = hrm_responses.next().await {
+///     if let Response::BroadcastData(mesg) = response {
+///         hrm.decode_broadcast_data(mesg.data());
+///     }
+/// }
+///
+/// With the serde feature enabled, responses, channel configurations, and decoded device data
+/// can be serialized, such as logging broadcast data as JSON.
+/// log::info!("{}", serde_json::to_string(&response)?);
+///
+/// With the nusb feature enabled, the ANT+ USB device can be used through nusb instead of libusb,
+/// which reads with asynchronous transfers instead of blocking reads.
+/// let runtime = libant::spawn(libant::RunConfig::new().nusb(true));
+///
+/// Instead of receiving responses, handlers can be registered with a Dispatcher and invoked with
+/// the responses for a channel or for all responses.
+/// use libant::Dispatcher;
+///
+/// let dispatcher = Dispatcher::new().on_channel(0, move |response| {
+///     if let Response::BroadcastData(mesg) = response {
+///         hrm.decode_broadcast_data(mesg.data());
+///     }
+/// });
+/// let runtime = libant::runtime::spawn_with_dispatcher(libant::RunConfig::new(), dispatcher);
+///
+/// Pages devices only send on request, such as the manufacturer information, can be requested
+/// with RequestPage. The run loop sends common page 70 again until the page arrives or the
+/// attempts are used up, then sends PageRequestResult.
+/// use libant::device::common::Transmission;
+///
+/// request_tx.send(Request::RequestPage {
+///     channel: 0,
+///     page: 0x50,
+///     transmission: Transmission::Broadcast(1),
+///     attempts: 3,
+/// })?;
+///
+/// A tap can be set on the RunConfig to receive the raw bytes written to and read from the ANT+
+/// USB device, such as to feed an external protocol analyzer.
+/// use libant::RawFrame;
+///
+/// let (tap_tx, tap_rx) = libant::unbounded();
+/// let runtime = libant::spawn(libant::RunConfig::new().tap(tap_tx));
+/// for frame in tap_rx.iter() {
+///     match frame {
+///         RawFrame::Tx(data) => println!("TX {}", libant::message::Hex(&data)),
+///         RawFrame::Rx(data) => println!("RX {}", libant::message::Hex(&data)),
+///     }
+/// }
+///
+/// The frames from a tap can be recorded to a trace with replay::record, and the trace replayed
+/// through the run loop without the ANT+ USB device to reproduce what was received.
+/// use libant::replay::{self, Replay};
+///
+/// let trace = std::fs::File::create("hrm.trace")?;
+/// std::thread::spawn(move || replay::record(tap_rx, trace));
+///
+/// let replay = Replay::from_trace(std::io::BufReader::new(std::fs::File::open("hrm.trace")?))?;
+/// libant::ant::run_transport(replay, request_rx, message_tx, libant::RunConfig::new());
+///
+/// Applications can test how they drive the run loop without an ANT+ USB device by running it
+/// on a transport::MockTransport, which replies to messages like an ANT+ USB device.
+/// use libant::transport::MockTransport;
+///
+/// let stick = MockTransport::stick(8);
+/// let mock = stick.clone();
+/// std::thread::spawn(move || {
+///     libant::ant::run_transport(stick, request_rx, message_tx, libant::RunConfig::new())
+/// });
+/// mock.push(&BroadcastDataMessage::new(0, &[0x04, 0, 0, 0, 0, 0, 0, 72]).to_message());
+///
 /// To handle multiple devices, an enum can be utilized.
 /// use libant::device::hrm::HeartRateMonitor;
 /// use libant::device::powermeter::PowerMeter;
@@ -69,14 +169,29 @@
 ///     }
 /// }
 pub mod ant;
+mod backoff;
 pub mod channel;
+pub mod client;
 pub mod device;
+pub mod dispatch;
 mod error;
 pub mod message;
+pub mod metrics;
+#[cfg(feature = "nusb")]
+pub mod nusb_device;
+pub mod replay;
+pub mod runtime;
+pub mod simulator;
+#[cfg(feature = "futures")]
+pub mod stream;
+pub mod transport;
 mod usb;
 
 pub type Result<T> = std::result::Result<T, error::AntError>;
 
-pub use ant::{Request, Response};
+pub use ant::{Backoff, RawFrame, Request, Response, RunConfig};
+pub use client::{AntClient, ChannelHandle};
 pub use crossbeam_channel::{unbounded, Receiver, Sender};
-pub use usb::Context;
+pub use dispatch::Dispatcher;
+pub use runtime::{spawn, AntRuntime};
+pub use usb::{Context, Selector, StickAddress, StickInfo, StickModel, KNOWN_STICKS};
@@ -2,8 +2,12 @@
 /// Message module provides a way for creating messages to send to the ANT+
 /// USB device or ANT+ device along with providing a way to decode messages
 /// received from the ANT+ USB device or ANT+ device sending data on a channel.
+use crate::error::AntError;
+use crate::Result;
 use log::debug;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::time::{Instant, SystemTime};
 
 // Starting to figure out legacy vs what's needed to support what this library
 // is being used for right now. I know some of these values and names were taken
@@ -32,11 +36,17 @@ const MESG_BUFFER_SIZE: usize =
 const MESG_FRAMED_SIZE: usize = MESG_ID_SIZE + MESG_CHANNEL_NUM_SIZE + MESG_MAX_DATA_SIZE;
 const MESG_HEADER_SIZE: usize = MESG_SYNC_SIZE + MESG_SIZE_SIZE + MESG_ID_SIZE;
 const MESG_FRAME_SIZE: usize = MESG_HEADER_SIZE + MESG_CHECKSUM_SIZE;
-const MESG_MAX_SIZE: usize = MESG_MAX_DATA_SIZE + MESG_FRAME_SIZE;
+/// Largest message once encoded, including the sync byte, size, ID, and checksum.
+pub const MESG_MAX_SIZE: usize = MESG_MAX_DATA_SIZE + MESG_FRAME_SIZE;
 const MESG_SIZE_OFFSET: usize = MESG_SYNC_SIZE;
 const MESG_ID_OFFSET: usize = MESG_SYNC_SIZE + MESG_SIZE_SIZE;
 const MESG_DATA_OFFSET: usize = MESG_HEADER_SIZE;
 const MESG_RECOMMENDED_BUFFER_SIZE: u8 = 64;
+// Largest read from the ANT+ USB device, plus room for an incomplete frame carried over from the
+// previous read. Reads are 512 bytes, a multiple of the recommended buffer size, so a single
+// read returns every message the ANT+ USB device has buffered when many channels are open.
+pub(crate) const READ_SIZE: usize = 8 * MESG_RECOMMENDED_BUFFER_SIZE as usize;
+const READ_BUFFER_SIZE: usize = READ_SIZE + MESG_MAX_SIZE_VALUE + MESG_FRAME_SIZE;
 
 const RESPONSE_NO_ERROR: u8 = 0x00;
 const MESG_EVENT_ID: u8 = 0x01;
@@ -46,91 +56,414 @@ pub const MESG_ASSIGN_CHANNEL_ID: u8 = 0x42;
 pub const MESG_CHANNEL_MESG_PERIOD_ID: u8 = 0x43;
 pub const MESG_CHANNEL_SEARCH_TIMEOUT_ID: u8 = 0x44;
 pub const MESG_CHANNEL_RADIO_FREQ_ID: u8 = 0x45;
-const MESG_NETWORK_KEY_ID: u8 = 0x46;
+pub const MESG_NETWORK_KEY_ID: u8 = 0x46;
+const MESG_VERSION_ID: u8 = 0x3E;
 const MESG_RESET: u8 = 0x4A;
 pub const MESG_OPEN_CHANNEL_ID: u8 = 0x4B;
 const MESG_CLOSE_CHANNEL_ID: u8 = 0x4C;
 const MESG_REQUEST: u8 = 0x4D;
 const MESG_BROADCAST_DATA_ID: u8 = 0x4E;
 const MESG_ACKNOWLEDGE_DATA_ID: u8 = 0x4F;
+const MESG_BURST_DATA_ID: u8 = 0x50;
 pub const MESG_CHANNEL_ID_ID: u8 = 0x51;
+const MESG_CHANNEL_STATUS_ID: u8 = 0x52;
+pub const MESG_OPEN_RX_SCAN_ID: u8 = 0x5B;
+pub const MESG_SET_LP_SEARCH_TIMEOUT_ID: u8 = 0x63;
+pub const MESG_AUTO_FREQ_CONFIG_ID: u8 = 0x70;
+pub const MESG_SDU_CONFIG_ID: u8 = 0x7A;
+const MESG_SDU_SET_MASK_ID: u8 = 0x7B;
+pub const MESG_ENABLE_ENCRYPTION_ID: u8 = 0x7D;
+const MESG_SET_ENCRYPTION_KEY_ID: u8 = 0x7E;
+const MESG_SET_ENCRYPTION_INFO_ID: u8 = 0x7F;
+const MESG_ENCRYPTION_KEY_NVM_ID: u8 = 0x83;
 const MESG_CAPABILITIES_ID: u8 = 0x54;
+const MESG_LIB_CONFIG_ID: u8 = 0x6E;
 const MESG_STARTUP_MESG_ID: u8 = 0x6F;
+const MESG_GET_SERIAL_NUM_ID: u8 = 0x61;
+const MESG_EVENT_BUFFERING_CONFIG_ID: u8 = 0x74;
+const MESG_CONFIG_ADV_BURST_ID: u8 = 0x78;
+const MESG_EVENT_FILTER_CONFIG_ID: u8 = 0x79;
 const MESG_CREATE_CHANNEL_ID: u8 = 0xFE;
 // Not part of ANT+ standard. Using as control message for quitting
 const MESG_QUIT: u8 = 0xFF;
 
+/// IDs of the messages sent to and received from the ANT+ USB device, so messages can be matched
+/// on by name. Event is the ID channel responses use for channel events rather than a reply to a
+/// message. IDs that are not supported are Unknown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageId {
+    Event,
+    ResponseEvent,
+    UnassignChannel,
+    AssignChannel,
+    ChannelPeriod,
+    ChannelSearchTimeout,
+    ChannelRadioFrequency,
+    NetworkKey,
+    Version,
+    Reset,
+    OpenChannel,
+    CloseChannel,
+    Request,
+    BroadcastData,
+    AcknowledgeData,
+    BurstData,
+    ChannelId,
+    ChannelStatus,
+    OpenRxScan,
+    LowPrioritySearchTimeout,
+    AutoFrequencyConfig,
+    SduConfig,
+    SduSetMask,
+    EnableEncryption,
+    SetEncryptionKey,
+    SetEncryptionInfo,
+    EncryptionKeyNvm,
+    Capabilities,
+    LibConfig,
+    Startup,
+    SerialNumber,
+    EventBufferingConfig,
+    AdvancedBurstConfig,
+    EventFilterConfig,
+    Unknown(u8),
+}
+
+impl From<u8> for MessageId {
+    fn from(id: u8) -> Self {
+        match id {
+            MESG_EVENT_ID => Self::Event,
+            MESG_RESPONSE_EVENT_ID => Self::ResponseEvent,
+            MESG_UNASSIGN_CHANNEL_ID => Self::UnassignChannel,
+            MESG_ASSIGN_CHANNEL_ID => Self::AssignChannel,
+            MESG_CHANNEL_MESG_PERIOD_ID => Self::ChannelPeriod,
+            MESG_CHANNEL_SEARCH_TIMEOUT_ID => Self::ChannelSearchTimeout,
+            MESG_CHANNEL_RADIO_FREQ_ID => Self::ChannelRadioFrequency,
+            MESG_NETWORK_KEY_ID => Self::NetworkKey,
+            MESG_VERSION_ID => Self::Version,
+            MESG_RESET => Self::Reset,
+            MESG_OPEN_CHANNEL_ID => Self::OpenChannel,
+            MESG_CLOSE_CHANNEL_ID => Self::CloseChannel,
+            MESG_REQUEST => Self::Request,
+            MESG_BROADCAST_DATA_ID => Self::BroadcastData,
+            MESG_ACKNOWLEDGE_DATA_ID => Self::AcknowledgeData,
+            MESG_BURST_DATA_ID => Self::BurstData,
+            MESG_CHANNEL_ID_ID => Self::ChannelId,
+            MESG_CHANNEL_STATUS_ID => Self::ChannelStatus,
+            MESG_OPEN_RX_SCAN_ID => Self::OpenRxScan,
+            MESG_SET_LP_SEARCH_TIMEOUT_ID => Self::LowPrioritySearchTimeout,
+            MESG_AUTO_FREQ_CONFIG_ID => Self::AutoFrequencyConfig,
+            MESG_SDU_CONFIG_ID => Self::SduConfig,
+            MESG_SDU_SET_MASK_ID => Self::SduSetMask,
+            MESG_ENABLE_ENCRYPTION_ID => Self::EnableEncryption,
+            MESG_SET_ENCRYPTION_KEY_ID => Self::SetEncryptionKey,
+            MESG_SET_ENCRYPTION_INFO_ID => Self::SetEncryptionInfo,
+            MESG_ENCRYPTION_KEY_NVM_ID => Self::EncryptionKeyNvm,
+            MESG_CAPABILITIES_ID => Self::Capabilities,
+            MESG_LIB_CONFIG_ID => Self::LibConfig,
+            MESG_STARTUP_MESG_ID => Self::Startup,
+            MESG_GET_SERIAL_NUM_ID => Self::SerialNumber,
+            MESG_EVENT_BUFFERING_CONFIG_ID => Self::EventBufferingConfig,
+            MESG_CONFIG_ADV_BURST_ID => Self::AdvancedBurstConfig,
+            MESG_EVENT_FILTER_CONFIG_ID => Self::EventFilterConfig,
+            id => Self::Unknown(id),
+        }
+    }
+}
+
+impl From<MessageId> for u8 {
+    fn from(id: MessageId) -> u8 {
+        match id {
+            MessageId::Event => MESG_EVENT_ID,
+            MessageId::ResponseEvent => MESG_RESPONSE_EVENT_ID,
+            MessageId::UnassignChannel => MESG_UNASSIGN_CHANNEL_ID,
+            MessageId::AssignChannel => MESG_ASSIGN_CHANNEL_ID,
+            MessageId::ChannelPeriod => MESG_CHANNEL_MESG_PERIOD_ID,
+            MessageId::ChannelSearchTimeout => MESG_CHANNEL_SEARCH_TIMEOUT_ID,
+            MessageId::ChannelRadioFrequency => MESG_CHANNEL_RADIO_FREQ_ID,
+            MessageId::NetworkKey => MESG_NETWORK_KEY_ID,
+            MessageId::Version => MESG_VERSION_ID,
+            MessageId::Reset => MESG_RESET,
+            MessageId::OpenChannel => MESG_OPEN_CHANNEL_ID,
+            MessageId::CloseChannel => MESG_CLOSE_CHANNEL_ID,
+            MessageId::Request => MESG_REQUEST,
+            MessageId::BroadcastData => MESG_BROADCAST_DATA_ID,
+            MessageId::AcknowledgeData => MESG_ACKNOWLEDGE_DATA_ID,
+            MessageId::BurstData => MESG_BURST_DATA_ID,
+            MessageId::ChannelId => MESG_CHANNEL_ID_ID,
+            MessageId::ChannelStatus => MESG_CHANNEL_STATUS_ID,
+            MessageId::OpenRxScan => MESG_OPEN_RX_SCAN_ID,
+            MessageId::LowPrioritySearchTimeout => MESG_SET_LP_SEARCH_TIMEOUT_ID,
+            MessageId::AutoFrequencyConfig => MESG_AUTO_FREQ_CONFIG_ID,
+            MessageId::SduConfig => MESG_SDU_CONFIG_ID,
+            MessageId::SduSetMask => MESG_SDU_SET_MASK_ID,
+            MessageId::EnableEncryption => MESG_ENABLE_ENCRYPTION_ID,
+            MessageId::SetEncryptionKey => MESG_SET_ENCRYPTION_KEY_ID,
+            MessageId::SetEncryptionInfo => MESG_SET_ENCRYPTION_INFO_ID,
+            MessageId::EncryptionKeyNvm => MESG_ENCRYPTION_KEY_NVM_ID,
+            MessageId::Capabilities => MESG_CAPABILITIES_ID,
+            MessageId::LibConfig => MESG_LIB_CONFIG_ID,
+            MessageId::Startup => MESG_STARTUP_MESG_ID,
+            MessageId::SerialNumber => MESG_GET_SERIAL_NUM_ID,
+            MessageId::EventBufferingConfig => MESG_EVENT_BUFFERING_CONFIG_ID,
+            MessageId::AdvancedBurstConfig => MESG_CONFIG_ADV_BURST_ID,
+            MessageId::EventFilterConfig => MESG_EVENT_FILTER_CONFIG_ID,
+            MessageId::Unknown(id) => id,
+        }
+    }
+}
+
+// Lib Config flags for enabling extended data on received data messages.
+pub const LIB_CONFIG_CHANNEL_ID: u8 = 0x80;
+pub const LIB_CONFIG_RSSI: u8 = 0x40;
+pub const LIB_CONFIG_RX_TIMESTAMP: u8 = 0x20;
+const EXT_CHANNEL_ID_SIZE: usize = 4;
+const EXT_RSSI_SIZE: usize = 3;
+const EXT_RX_TIMESTAMP_SIZE: usize = 2;
+
+// Extended assignment flags sent with assign channel.
+pub const EXT_ASSIGN_FREQUENCY_AGILITY: u8 = 0x04;
+
+// Event filter flags. Events with their flag set are not sent by the ANT+ USB device.
+pub const EVENT_FILTER_RX_SEARCH_TIMEOUT: u16 = 0x0001;
+pub const EVENT_FILTER_RX_FAIL: u16 = 0x0002;
+pub const EVENT_FILTER_TX: u16 = 0x0004;
+pub const EVENT_FILTER_TRANSFER_RX_FAILED: u16 = 0x0008;
+pub const EVENT_FILTER_TRANSFER_TX_COMPLETED: u16 = 0x0010;
+pub const EVENT_FILTER_TRANSFER_TX_FAILED: u16 = 0x0020;
+pub const EVENT_FILTER_CHANNEL_CLOSED: u16 = 0x0040;
+pub const EVENT_FILTER_RX_FAIL_GO_TO_SEARCH: u16 = 0x0080;
+pub const EVENT_FILTER_CHANNEL_COLLISION: u16 = 0x0100;
+
 const EVENT_RX_SEARCH_TIMEOUT: u8 = 0x01;
 const EVENT_CHANNEL_CLOSED: u8 = 0x07;
 const CHANNEL_IN_WRONG_STATE: u8 = 0x15;
 
 /// ReadBuffer provides a buffer to through data received from the ANT+ USB device and turn
-/// the data into a Message
+/// the data into a Message. A message split across two reads is kept at the start of the buffer
+/// until the rest of the message is read.
 pub struct ReadBuffer {
     index: usize,
-    inner: [u8; 512],
+    inner: [u8; READ_BUFFER_SIZE],
     len: usize,
+    // Bytes of an incomplete message carried over from the previous read.
+    carried: usize,
+    timestamp: Option<Timestamp>,
+    errors: u64,
 }
 
 impl ReadBuffer {
     pub fn new() -> Self {
         ReadBuffer {
             index: 0,
-            inner: [0; 512],
+            inner: [0; READ_BUFFER_SIZE],
             len: 0,
+            carried: 0,
+            timestamp: None,
+            errors: 0,
         }
     }
 
+    // Called right after data is read into the buffer, so this is also when the messages in
+    // the buffer are timestamped.
     pub fn len(&mut self, len: usize) {
-        self.len = len;
+        self.len = self.carried + len;
+        self.carried = 0;
+        self.timestamp = Some(Timestamp::now());
     }
 
+    /// Buffer to read data into, after any incomplete message carried over from the previous
+    /// read. Always holds at least 512 bytes.
     pub fn inner_as_mut(&mut self) -> &mut [u8] {
-        &mut self.inner
+        &mut self.inner[self.carried..]
+    }
+
+    /// Number of messages found with an invalid size, checksum, or data since the last call.
+    pub fn take_errors(&mut self) -> u64 {
+        std::mem::take(&mut self.errors)
+    }
+
+    // Moves the incomplete message at the end of the buffer to the start so the next read
+    // completes it.
+    fn carry(&mut self) {
+        self.inner.copy_within(self.index..self.len, 0);
+        self.carried = self.len - self.index;
+        self.index = 0;
+        self.len = 0;
     }
 }
 
-// This is an iterator over the read in buffer from the ANT+ USB stick.
-// The buffer is a variable size [u8] that we will loop through looking
-// for a sync bit and then creating an ANT message from the received
-// data. If the checksum of a message is invalid, then we continue searching
-// for another sync bit. If we find no sync bits or if checksums are invalid,
-// we do not create messages and none would be returned.
+// This is an iterator over the read in buffer from the ANT+ USB stick. Frames are found with
+// scan and turned into messages, counting the invalid frames skipped over. If the buffer ends
+// before the end of a message, the rest of the message is expected in the next read.
 impl Iterator for ReadBuffer {
     // Use this for now until switch to enum
     type Item = Response;
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            if self.index >= self.len {
-                self.index = 0;
-                self.len = 0;
-                return None;
-            }
-            if self.inner[self.index] == MESG_TX_SYNC {
-                let index = self.index;
-                // Length of message
-                let len = index + self.inner[index + 1] as usize + 4;
-                // Verify checksum
-                if checksum(&self.inner[index..len]) == 0 {
+            match scan(&self.inner[..self.len], self.index) {
+                Scan::End => {
+                    self.index = 0;
+                    self.len = 0;
+                    return None;
+                }
+                Scan::Incomplete(index) => {
+                    self.index = index;
+                    self.carry();
+                    return None;
+                }
+                Scan::Invalid(index) => {
+                    self.errors += 1;
+                    self.index = index + 1;
+                }
+                Scan::Frame(index, len) => {
                     self.index = len;
-                    return Some(process_message(&self.inner[index..len - 1]));
+                    match process_message(&self.inner[index..len - 1]) {
+                        Ok(mut mesg) => {
+                            if let Some(timestamp) = self.timestamp {
+                                mesg.set_timestamp(timestamp);
+                            }
+                            return Some(mesg);
+                        }
+                        Err(e) => {
+                            debug!("{}", e);
+                            self.errors += 1;
+                        }
+                    }
                 }
             }
-            self.index += 1;
         }
     }
 }
 
+// Result of scanning a buffer for the next frame.
+enum Scan {
+    // No sync byte found before the end of the buffer.
+    End,
+    // The buffer ends before the end of the frame starting at the index.
+    Incomplete(usize),
+    // The frame starting at the index has an invalid size or checksum.
+    Invalid(usize),
+    // A frame with a valid checksum from the index up to the end.
+    Frame(usize, usize),
+}
+
+// Scans the buffer from the index for a sync byte, either the 0xA4 sync used by ANT+ USB sticks
+// or the 0xA5 sync used by some serial framings, and checks the size and checksum of the frame
+// it starts. Bytes before the sync byte are skipped.
+fn scan(buf: &[u8], index: usize) -> Scan {
+    let index = match buf
+        .iter()
+        .skip(index)
+        .position(|&b| b == MESG_TX_SYNC || b == MESG_RX_SYNC)
+    {
+        Some(position) => index + position,
+        None => return Scan::End,
+    };
+    if index + MESG_SIZE_OFFSET >= buf.len() {
+        return Scan::Incomplete(index);
+    }
+    let size = buf[index + MESG_SIZE_OFFSET] as usize;
+    if size > MESG_MAX_SIZE_VALUE {
+        return Scan::Invalid(index);
+    }
+    let len = index + size + MESG_FRAME_SIZE;
+    if len > buf.len() {
+        return Scan::Incomplete(index);
+    }
+    if checksum(&buf[index..len]) != 0 {
+        return Scan::Invalid(index);
+    }
+    Scan::Frame(index, len)
+}
+
+/// Parses the messages in data read from the ANT+ USB device. Unlike ReadBuffer, nothing is kept
+/// between calls, so a message split across reads is not parsed, and messages are not
+/// timestamped. A frame with an invalid size or checksum, or cut off at the end of the data, is
+/// returned as UnableToDecode, and a frame with data too short for its message as Malformed.
+pub fn parse_frames(data: &[u8]) -> Vec<Result<Response>> {
+    let mut responses = Vec::new();
+    let mut index = 0;
+    loop {
+        match scan(data, index) {
+            Scan::End => break,
+            Scan::Incomplete(_) => {
+                responses.push(Err(AntError::UnableToDecode));
+                break;
+            }
+            Scan::Invalid(start) => {
+                responses.push(Err(AntError::UnableToDecode));
+                index = start + 1;
+            }
+            Scan::Frame(start, end) => {
+                responses.push(process_message(&data[start..end - 1]));
+                index = end;
+            }
+        }
+    }
+    responses
+}
+
 /// Responses that can be received from the ANT+ USB device.
 /// Startup are messages received when initially configuring the USB device.
 /// ChannelResponse are messages received from the channel during configuration of the channel or
 /// events received while running.
 /// BroadcastData is data received from ANT+ device.
+/// AcknowledgeData is acknowledged data received from an ANT+ device, i.e. commands sent to a
+/// master channel.
+/// BurstData is a single packet of a burst transfer received from an ANT+ device.
+/// ChannelId is the reply to a channel ID request with the device a channel is paired with.
+/// ChannelStatus is the reply to a channel status request for a channel.
+/// Capabilities, SerialNumber, AntVersion, EventBufferConfig, and AdvancedBurstCapabilities are
+/// replies to requests sent to the ANT+ USB device.
+/// Unknown is any message with an ID that is not supported, along with its data.
 #[derive(Debug, PartialEq)]
 pub enum Response {
     Startup(StartupMessage),
     ChannelResponse(ChannelResponseMessage),
     BroadcastData(BroadcastDataMessage),
+    AcknowledgeData(AcknowledgeDataMessage),
+    BurstData(BurstDataMessage),
+    ChannelId(ChannelIdMessage),
+    ChannelStatus(ChannelStatusMessage),
+    Capabilities(Capabilities),
+    SerialNumber(SerialNumberMessage),
+    AntVersion(AntVersionMessage),
+    EventBufferConfig(EventBufferConfigMessage),
+    AdvancedBurstCapabilities(AdvancedBurstCapabilitiesMessage),
+    Unknown { id: u8, data: Vec<u8> },
+}
+
+impl Response {
+    // Only data messages carry a timestamp.
+    fn set_timestamp(&mut self, timestamp: Timestamp) {
+        match self {
+            Response::BroadcastData(mesg) => mesg.timestamp = Some(timestamp),
+            Response::AcknowledgeData(mesg) => mesg.1 = Some(timestamp),
+            _ => {}
+        }
+    }
+}
+
+/// Host side time a message was read from the ANT+ USB device. Instant is used for measuring
+/// time between messages while SystemTime can be used to align with other recorded data. Only
+/// the SystemTime is serialized, as an Instant is only meaningful to the process that read the
+/// message.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timestamp {
+    #[cfg_attr(feature = "serde", serde(skip, default = "Instant::now"))]
+    pub instant: Instant,
+    pub system_time: SystemTime,
+}
+
+impl Timestamp {
+    pub fn now() -> Self {
+        Self {
+            instant: Instant::now(),
+            system_time: SystemTime::now(),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -168,29 +501,37 @@ pub enum ChannelResponseCode {
     EventRxSearchTimeout,
     EventRxFail,
     EventTx,
+    EventTransferRxFailed,
     EventTransferTxCompleted,
     EventTransferTxFailed,
     EventChannelClosed,
     EventRxFailGoToSearch,
     ChannelCollision,
+    EventEncryptNegotiationSuccess,
+    EventEncryptNegotiationFail,
     ChannelInWrongState,
+    Unknown(u8),
 }
 // TODO: May need to increase the size of this if support for encryption for devices
 // is added, but not needed right now.
 #[derive(Debug, PartialEq)]
 pub struct ChannelResponseMessage([u8; 3]);
 
-impl ChannelResponseMessage {
-    pub fn from(mesg: &[u8]) -> Self {
-        Self(mesg.try_into().expect("Wrong number of elements passed"))
+impl TryFrom<&[u8]> for ChannelResponseMessage {
+    type Error = AntError;
+
+    fn try_from(mesg: &[u8]) -> Result<Self> {
+        Ok(Self(fields(MESG_RESPONSE_EVENT_ID, mesg)?))
     }
+}
 
+impl ChannelResponseMessage {
     pub fn channel(&self) -> u8 {
         self.0[0]
     }
 
-    pub fn message_id(&self) -> u8 {
-        self.0[1]
+    pub fn message_id(&self) -> MessageId {
+        self.0[1].into()
     }
 
     pub fn code(&self) -> ChannelResponseCode {
@@ -199,32 +540,71 @@ impl ChannelResponseMessage {
             0x01 => ChannelResponseCode::EventRxSearchTimeout,
             0x02 => ChannelResponseCode::EventRxFail,
             0x03 => ChannelResponseCode::EventTx,
+            0x04 => ChannelResponseCode::EventTransferRxFailed,
             0x05 => ChannelResponseCode::EventTransferTxCompleted,
             0x06 => ChannelResponseCode::EventTransferTxFailed,
             0x07 => ChannelResponseCode::EventChannelClosed,
             0x08 => ChannelResponseCode::EventRxFailGoToSearch,
             0x09 => ChannelResponseCode::ChannelCollision,
             0x15 => ChannelResponseCode::ChannelInWrongState,
-            _ => {
-                debug!("Received ChannelResponseCode: {:x}", self.0[2]);
-                unimplemented!();
+            0x38 => ChannelResponseCode::EventEncryptNegotiationSuccess,
+            0x39 => ChannelResponseCode::EventEncryptNegotiationFail,
+            code => {
+                debug!("Received ChannelResponseCode: {:x}", code);
+                ChannelResponseCode::Unknown(code)
             }
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+// When extended messages are enabled with Lib Config, the 8 data bytes are followed by a flag
+// byte signaling which extended data follows.
+// [0] - Channel number
+// [1..9] - Data payload
+// [9] - Flag byte
+// [10..] - Extended data in the order of channel ID, RSSI, and Rx timestamp
+// Broadcast data is received on every channel period, so extended data is kept inline instead of
+// allocated, letting messages be copied to the application without touching the heap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BroadcastDataMessage {
     channel_id: u8,
     data: [u8; 8],
+    flag: Option<u8>,
+    extended_len: u8,
+    extended_data: [u8; MESG_MAX_EXT_DATA_SIZE],
+    timestamp: Option<Timestamp>,
+}
+
+impl TryFrom<&[u8]> for BroadcastDataMessage {
+    type Error = AntError;
+
+    fn try_from(mesg: &[u8]) -> Result<Self> {
+        let [channel_id, data @ ..] = fields::<9>(MESG_BROADCAST_DATA_ID, mesg)?;
+        let extended = mesg.get(10..).unwrap_or_default();
+        let extended_len = extended.len().min(MESG_MAX_EXT_DATA_SIZE);
+        let mut extended_data = [0; MESG_MAX_EXT_DATA_SIZE];
+        extended_data[..extended_len].copy_from_slice(&extended[..extended_len]);
+        Ok(Self {
+            channel_id,
+            data,
+            flag: mesg.get(9).copied(),
+            extended_len: extended_len as u8,
+            extended_data,
+            timestamp: None,
+        })
+    }
 }
 
 impl BroadcastDataMessage {
-    // Maybe change this to try_from and return an error
-    pub fn from(mesg: &[u8]) -> Self {
+    pub fn new(channel_number: u8, data: &[u8; 8]) -> Self {
         Self {
-            channel_id: mesg[0],
-            data: mesg[1..].try_into().unwrap(),
+            channel_id: channel_number,
+            data: *data,
+            flag: None,
+            extended_len: 0,
+            extended_data: [0; MESG_MAX_EXT_DATA_SIZE],
+            timestamp: None,
         }
     }
 
@@ -235,10 +615,114 @@ impl BroadcastDataMessage {
     pub fn data(self) -> [u8; 8] {
         self.data
     }
+
+    /// Flag byte of an extended message signaling which extended data is included. None if
+    /// extended messages are not enabled.
+    pub fn flag(&self) -> Option<u8> {
+        self.flag
+    }
+
+    /// Raw extended data following the flag byte.
+    pub fn extended_data(&self) -> &[u8] {
+        &self.extended_data[..self.extended_len as usize]
+    }
+
+    pub fn to_message(&self) -> Message {
+        let mut buf = [0; 9];
+        buf[0] = self.channel_id;
+        buf[1..].copy_from_slice(&self.data);
+        Message::with_payload(MESG_BROADCAST_DATA_ID, &buf)
+    }
+
+    /// Device number of the device that sent the message. Useful for channels opened with a
+    /// wildcard device ID. Requires LIB_CONFIG_CHANNEL_ID.
+    pub fn device_id(&self) -> Option<u16> {
+        self.extended_field(LIB_CONFIG_CHANNEL_ID)
+            .map(|field| bytes_to_u16(&field[..2]))
+    }
+
+    /// Device type of the device that sent the message. Requires LIB_CONFIG_CHANNEL_ID.
+    pub fn device_type(&self) -> Option<u8> {
+        self.extended_field(LIB_CONFIG_CHANNEL_ID)
+            .map(|field| field[2])
+    }
+
+    /// Transmission type of the device that sent the message. Requires LIB_CONFIG_CHANNEL_ID.
+    pub fn transmission_type(&self) -> Option<u8> {
+        self.extended_field(LIB_CONFIG_CHANNEL_ID)
+            .map(|field| field[3])
+    }
+
+    /// Signal strength the message was received with. Requires LIB_CONFIG_RSSI.
+    pub fn rssi(&self) -> Option<Rssi> {
+        self.extended_field(LIB_CONFIG_RSSI).map(|field| Rssi {
+            measurement_type: field[0],
+            value: field[1] as i8,
+            threshold: field[2] as i8,
+        })
+    }
+
+    /// Rx timestamp from the ANT+ USB device in 1/32768s. Rolls over every 2 seconds. Requires
+    /// LIB_CONFIG_RX_TIMESTAMP.
+    pub fn rx_timestamp(&self) -> Option<u16> {
+        self.extended_field(LIB_CONFIG_RX_TIMESTAMP)
+            .map(bytes_to_u16)
+    }
+
+    /// Host side time the message was read from the ANT+ USB device.
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        self.timestamp
+    }
+
+    // Returns the extended data for the flag if it is set. Extended data is always in the
+    // order of channel ID, RSSI, and Rx timestamp.
+    fn extended_field(&self, flag: u8) -> Option<&[u8]> {
+        let flags = self.flag?;
+        if flags & flag != flag {
+            return None;
+        }
+        let fields = [
+            (LIB_CONFIG_CHANNEL_ID, EXT_CHANNEL_ID_SIZE),
+            (LIB_CONFIG_RSSI, EXT_RSSI_SIZE),
+            (LIB_CONFIG_RX_TIMESTAMP, EXT_RX_TIMESTAMP_SIZE),
+        ];
+        let mut offset = 0;
+        for (field_flag, size) in fields.iter() {
+            if *field_flag == flag {
+                return self.extended_data().get(offset..offset + size);
+            }
+            if flags & field_flag == *field_flag {
+                offset += size;
+            }
+        }
+        None
+    }
+}
+
+/// RSSI extended data.
+/// measurement_type: Type of measurement, 0x20 for dBm.
+/// value: Signal strength in dBm.
+/// threshold: Threshold setting in dBm for the channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rssi {
+    pub measurement_type: u8,
+    pub value: i8,
+    pub threshold: i8,
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct AcknowledgeDataMessage([u8; 9]);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AcknowledgeDataMessage([u8; 9], Option<Timestamp>);
+
+// Extended data is dropped from received acknowledged data.
+impl TryFrom<&[u8]> for AcknowledgeDataMessage {
+    type Error = AntError;
+
+    fn try_from(mesg: &[u8]) -> Result<Self> {
+        Ok(Self(fields(MESG_ACKNOWLEDGE_DATA_ID, mesg)?, None))
+    }
+}
 
 impl AcknowledgeDataMessage {
     // TODO: Should this return an error if user tries to pass in
@@ -247,11 +731,7 @@ impl AcknowledgeDataMessage {
         let mut buf: [u8; 9] = [0; 9];
         buf[0] = channel_number;
         buf[1..].copy_from_slice(data);
-        Self(buf)
-    }
-
-    pub fn from(mesg: &[u8]) -> Self {
-        Self(mesg.try_into().expect("Wrong number of elements passed"))
+        Self(buf, None)
     }
 
     pub fn channel(&self) -> u8 {
@@ -262,8 +742,361 @@ impl AcknowledgeDataMessage {
         &self.0[1..]
     }
 
+    /// Host side time the message was read from the ANT+ USB device. None for messages
+    /// created to be sent.
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        self.1
+    }
+
     pub fn to_message(&self) -> Message {
-        Message::new(MESG_ACKNOWLEDGE_DATA_ID, &self.0)
+        Message::with_payload(MESG_ACKNOWLEDGE_DATA_ID, &self.0)
+    }
+}
+
+// Burst Data Message
+// [0] - Channel number in bits 0-4, sequence number in bits 5-6, and last packet in bit 7
+// [1..9] - Data payload
+#[derive(Clone, Debug, PartialEq)]
+pub struct BurstDataMessage {
+    channel_sequence: u8,
+    data: [u8; 8],
+}
+
+// Extended data is dropped from received burst data.
+impl TryFrom<&[u8]> for BurstDataMessage {
+    type Error = AntError;
+
+    fn try_from(mesg: &[u8]) -> Result<Self> {
+        let [channel_sequence, data @ ..] = fields::<9>(MESG_BURST_DATA_ID, mesg)?;
+        Ok(Self {
+            channel_sequence,
+            data,
+        })
+    }
+}
+
+impl BurstDataMessage {
+    pub fn channel(&self) -> u8 {
+        self.channel_sequence & 0x1F
+    }
+
+    /// Sequence number of the packet. The first packet is 0, followed by 1, 2, 3, 1, 2, 3...
+    pub fn sequence(&self) -> u8 {
+        (self.channel_sequence >> 5) & 0x03
+    }
+
+    /// Whether this is the last packet of the burst.
+    pub fn is_last(&self) -> bool {
+        self.channel_sequence & 0x80 == 0x80
+    }
+
+    pub fn data(&self) -> [u8; 8] {
+        self.data
+    }
+}
+
+/// BurstBuffer reassembles the packets of a burst transfer on a channel. Packets are appended
+/// until the last packet is received, returning the complete transfer. If a packet is received
+/// out of sequence, the transfer is dropped and an error is returned.
+#[derive(Debug, Default)]
+pub struct BurstBuffer {
+    data: Vec<u8>,
+    sequence: Option<u8>,
+}
+
+impl BurstBuffer {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn push(&mut self, mesg: &BurstDataMessage) -> Result<Option<Vec<u8>>> {
+        let sequence = mesg.sequence();
+        match self.sequence {
+            // First packet of a new transfer. Drop any partial transfer.
+            _ if sequence == 0 => self.data.clear(),
+            Some(last) if sequence == next_burst_sequence(last) => {}
+            _ => {
+                self.clear();
+                return Err(AntError::BurstSequenceError(mesg.channel()));
+            }
+        }
+        self.data.extend_from_slice(&mesg.data);
+        if mesg.is_last() {
+            self.sequence = None;
+            return Ok(Some(std::mem::take(&mut self.data)));
+        }
+        self.sequence = Some(sequence);
+        Ok(None)
+    }
+
+    /// Drops any partial transfer, i.e. when EVENT_TRANSFER_RX_FAILED is received.
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.sequence = None;
+    }
+}
+
+// Sequence numbers after the first packet rotate through 1, 2, 3.
+fn next_burst_sequence(sequence: u8) -> u8 {
+    if sequence == 3 {
+        1
+    } else {
+        sequence + 1
+    }
+}
+
+// Channel ID Message
+// [0] - Channel number
+// [1..3] - Device number
+// [3] - Device type
+// [4] - Transmission type
+#[derive(Debug, PartialEq)]
+pub struct ChannelIdMessage([u8; 5]);
+
+impl TryFrom<&[u8]> for ChannelIdMessage {
+    type Error = AntError;
+
+    fn try_from(mesg: &[u8]) -> Result<Self> {
+        Ok(Self(fields(MESG_CHANNEL_ID_ID, mesg)?))
+    }
+}
+
+impl ChannelIdMessage {
+    pub fn channel(&self) -> u8 {
+        self.0[0]
+    }
+
+    pub fn device_id(&self) -> u16 {
+        bytes_to_u16(&self.0[1..3])
+    }
+
+    pub fn device_type(&self) -> u8 {
+        self.0[3]
+    }
+
+    pub fn transmission_type(&self) -> u8 {
+        self.0[4]
+    }
+}
+
+// Channel Status Message
+// [0] - Channel number
+// [1] - Channel state in bits 0-1, network number in bits 2-3, and channel type in bits 4-7
+#[derive(Debug, PartialEq)]
+pub struct ChannelStatusMessage([u8; 2]);
+
+impl TryFrom<&[u8]> for ChannelStatusMessage {
+    type Error = AntError;
+
+    fn try_from(mesg: &[u8]) -> Result<Self> {
+        Ok(Self(fields(MESG_CHANNEL_STATUS_ID, mesg)?))
+    }
+}
+
+impl ChannelStatusMessage {
+    pub fn channel(&self) -> u8 {
+        self.0[0]
+    }
+
+    pub fn status(&self) -> ChannelStatus {
+        match self.0[1] & 0x03 {
+            0x00 => ChannelStatus::Unassigned,
+            0x01 => ChannelStatus::Assigned,
+            0x02 => ChannelStatus::Searching,
+            _ => ChannelStatus::Tracking,
+        }
+    }
+
+    pub fn network(&self) -> u8 {
+        (self.0[1] >> 2) & 0x03
+    }
+
+    pub fn channel_type(&self) -> u8 {
+        self.0[1] & 0xF0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelStatus {
+    Unassigned,
+    Assigned,
+    Searching,
+    Tracking,
+}
+
+// Capabilities Message
+// [0] - Max ANT channels
+// [1] - Max networks
+// [2] - Standard options
+// [3] - Advanced options
+// [4] - Advanced options 2
+// [5] - Max SensRcore channels
+// [6] - Advanced options 3
+// [7] - Advanced options 4
+// Older ANT+ USB devices only send the first 4 or 6 bytes. Missing bytes are set to 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Capabilities {
+    pub max_channels: u8,
+    pub max_networks: u8,
+    pub standard_options: u8,
+    pub advanced_options: u8,
+    pub advanced_options2: u8,
+    pub max_sensrcore_channels: u8,
+    pub advanced_options3: u8,
+    pub advanced_options4: u8,
+}
+
+// Option bits used by the library from the capabilities message.
+const CAPABILITIES_EXT_MESSAGE_ENABLED: u8 = 0x02; // Advanced options 2
+const CAPABILITIES_SCAN_MODE_ENABLED: u8 = 0x04; // Advanced options 2
+const CAPABILITIES_EVENT_BUFFERING_ENABLED: u8 = 0x02; // Advanced options 3
+const CAPABILITIES_ENCRYPTED_CHANNEL_ENABLED: u8 = 0x80; // Advanced options 3
+
+impl Capabilities {
+    pub fn from(mesg: &[u8]) -> Self {
+        let mut buf = [0; 8];
+        let len = mesg.len().min(8);
+        buf[..len].copy_from_slice(&mesg[..len]);
+        Self {
+            max_channels: buf[0],
+            max_networks: buf[1],
+            standard_options: buf[2],
+            advanced_options: buf[3],
+            advanced_options2: buf[4],
+            max_sensrcore_channels: buf[5],
+            advanced_options3: buf[6],
+            advanced_options4: buf[7],
+        }
+    }
+
+    pub fn supports_extended_messages(&self) -> bool {
+        self.advanced_options2 & CAPABILITIES_EXT_MESSAGE_ENABLED != 0
+    }
+
+    pub fn supports_scan_mode(&self) -> bool {
+        self.advanced_options2 & CAPABILITIES_SCAN_MODE_ENABLED != 0
+    }
+
+    pub fn supports_event_buffering(&self) -> bool {
+        self.advanced_options3 & CAPABILITIES_EVENT_BUFFERING_ENABLED != 0
+    }
+
+    pub fn supports_encryption(&self) -> bool {
+        self.advanced_options3 & CAPABILITIES_ENCRYPTED_CHANNEL_ENABLED != 0
+    }
+}
+
+// Serial Number Message
+// [0..4] - Serial number of the ANT+ USB device
+#[derive(Debug, PartialEq)]
+pub struct SerialNumberMessage([u8; 4]);
+
+impl TryFrom<&[u8]> for SerialNumberMessage {
+    type Error = AntError;
+
+    fn try_from(mesg: &[u8]) -> Result<Self> {
+        Ok(Self(fields(MESG_GET_SERIAL_NUM_ID, mesg)?))
+    }
+}
+
+impl SerialNumberMessage {
+    pub fn serial_number(&self) -> u32 {
+        bytes_to_u32(&self.0)
+    }
+}
+
+// ANT Version Message
+// [0..N] - Null terminated version string of the ANT+ USB device firmware
+#[derive(Debug, PartialEq)]
+pub struct AntVersionMessage(String);
+
+impl AntVersionMessage {
+    pub fn from(mesg: &[u8]) -> Self {
+        let end = mesg.iter().position(|&b| b == 0).unwrap_or(mesg.len());
+        Self(String::from_utf8_lossy(&mesg[..end]).into_owned())
+    }
+
+    pub fn version(&self) -> &str {
+        &self.0
+    }
+}
+
+// Event Buffer Configuration Message
+// [0] - Filler
+// [1] - Buffer config, 0x00 buffers low priority events only and 0x01 buffers all events
+// [2..4] - Size of the buffer in bytes before it is flushed
+// [4..6] - Time in 10ms before the buffer is flushed
+#[derive(Debug, PartialEq)]
+pub struct EventBufferConfigMessage([u8; 6]);
+
+impl TryFrom<&[u8]> for EventBufferConfigMessage {
+    type Error = AntError;
+
+    fn try_from(mesg: &[u8]) -> Result<Self> {
+        Ok(Self(fields(MESG_EVENT_BUFFERING_CONFIG_ID, mesg)?))
+    }
+}
+
+impl EventBufferConfigMessage {
+    pub fn buffer_config(&self) -> EventBufferConfig {
+        match self.0[1] {
+            0x01 => EventBufferConfig::AllEvents,
+            _ => EventBufferConfig::LowPriorityEvents,
+        }
+    }
+
+    /// Number of bytes buffered before the buffer is flushed. 0 disables the size threshold.
+    pub fn size(&self) -> u16 {
+        bytes_to_u16(&self.0[2..4])
+    }
+
+    /// Time in 10ms units before the buffer is flushed. 0 disables the time threshold.
+    pub fn time(&self) -> u16 {
+        bytes_to_u16(&self.0[4..6])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventBufferConfig {
+    LowPriorityEvents,
+    AllEvents,
+}
+
+impl From<EventBufferConfig> for u8 {
+    fn from(config: EventBufferConfig) -> u8 {
+        match config {
+            EventBufferConfig::LowPriorityEvents => 0x00,
+            EventBufferConfig::AllEvents => 0x01,
+        }
+    }
+}
+
+// Advanced Burst Capabilities Message
+// [0] - Filler
+// [1] - Max supported packet length, 0x01 = 8 bytes, 0x02 = 16 bytes, 0x03 = 24 bytes
+// [2..5] - Supported advanced burst features bit field
+#[derive(Debug, PartialEq)]
+pub struct AdvancedBurstCapabilitiesMessage([u8; 5]);
+
+impl TryFrom<&[u8]> for AdvancedBurstCapabilitiesMessage {
+    type Error = AntError;
+
+    fn try_from(mesg: &[u8]) -> Result<Self> {
+        Ok(Self(fields(MESG_CONFIG_ADV_BURST_ID, mesg)?))
+    }
+}
+
+impl AdvancedBurstCapabilitiesMessage {
+    /// Max supported packet length in bytes.
+    pub fn max_packet_length(&self) -> usize {
+        self.0[1] as usize * ANT_STANDARD_DATA_PAYLOAD_SIZE
+    }
+
+    pub fn features(&self) -> u32 {
+        bytes_to_u32(&self.0[2..5])
     }
 }
 
@@ -280,140 +1113,531 @@ impl AcknowledgeDataMessage {
 // [3..N+2] - Data payload
 // [N+3] - Checksum
 
+// The payload is stored inline as ANT payloads are bounded by MESG_MAX_DATA_SIZE, so messages
+// can be created and encoded without allocating.
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(into = "SerdeMessage", try_from = "SerdeMessage")
+)]
 pub struct Message {
     pub id: u8,
-    pub data: Vec<u8>,
+    len: u8,
+    data: [u8; MESG_MAX_DATA_SIZE],
+}
+
+// Messages are serialized with only the bytes of the payload, and validated like Message::new
+// when deserialized.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerdeMessage {
+    id: u8,
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Message> for SerdeMessage {
+    fn from(mesg: Message) -> Self {
+        Self {
+            id: mesg.id,
+            data: mesg.data().to_vec(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SerdeMessage> for Message {
+    type Error = AntError;
+
+    fn try_from(mesg: SerdeMessage) -> Result<Self> {
+        Message::new(mesg.id, &mesg.data)
+    }
 }
 
 impl Message {
-    pub fn new(id: u8, data: &[u8]) -> Message {
-        // TODO: Validate size of messgae is < MESG_MAX_DATA_SIZE
+    /// Creates a message with the data as its payload. Payloads longer than the largest ANT
+    /// payload are rejected with PayloadTooLarge so they can't be written to the ANT+ USB device.
+    pub fn new(id: u8, data: &[u8]) -> Result<Message> {
+        if data.len() > MESG_MAX_DATA_SIZE {
+            return Err(AntError::PayloadTooLarge {
+                id,
+                len: data.len(),
+            });
+        }
+        Ok(Self::with_payload(id, data))
+    }
+
+    // Creates a message from a payload that is known to fit, such as the fixed size payloads
+    // built by this module.
+    pub(crate) fn with_payload(id: u8, data: &[u8]) -> Message {
+        let mut buf = [0; MESG_MAX_DATA_SIZE];
+        buf[..data.len()].copy_from_slice(data);
         Message {
             id,
-            data: data.to_vec(),
+            len: data.len() as u8,
+            data: buf,
         }
     }
 
-    // Converts a message into something that can be written out
-    pub fn encode(&self) -> Vec<u8> {
-        let size = MESG_HEADER_SIZE + self.data.len() + MESG_CHECKSUM_SIZE;
-        let mut buf: Vec<u8> = vec![0; size];
+    pub fn message_id(&self) -> MessageId {
+        self.id.into()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+
+    /// Size of the message once encoded.
+    pub fn encoded_len(&self) -> usize {
+        MESG_HEADER_SIZE + self.len as usize + MESG_CHECKSUM_SIZE
+    }
+
+    /// Encodes the message into the start of buf, returning the number of bytes written. buf
+    /// must hold at least encoded_len() bytes. MESG_MAX_SIZE is enough for any message.
+    pub fn encode_into(&self, buf: &mut [u8]) -> usize {
+        let size = self.encoded_len();
         buf[0] = MESG_TX_SYNC;
-        buf[MESG_SIZE_OFFSET] = self.data.len() as u8;
+        buf[MESG_SIZE_OFFSET] = self.len;
         buf[MESG_ID_OFFSET] = self.id;
-        buf[MESG_DATA_OFFSET..(size - 1)].copy_from_slice(&self.data);
+        buf[MESG_DATA_OFFSET..(size - 1)].copy_from_slice(self.data());
 
         // Calculate checksum and store it in the last byte of the message.
         // Checksum is the XOR of all bytes of the message.
         buf[size - 1] = checksum(&buf[..size - 1]);
+        size
+    }
+
+    // Converts a message into something that can be written out
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![0; self.encoded_len()];
+        self.encode_into(&mut buf);
         buf
     }
 
     fn id_as_str(&self) -> &'static str {
-        match self.id {
-            MESG_STARTUP_MESG_ID => "Startup (0x6F)",
-            MESG_CAPABILITIES_ID => "Capabilities (0x54)",
-            MESG_RESPONSE_EVENT_ID => "Response Event (0x40)",
-            MESG_BROADCAST_DATA_ID => "Broadcast Data (0x4E)",
-            MESG_CHANNEL_ID_ID => "Channel ID Request (0x51)",
-            _ => "Unknown message",
+        match self.message_id() {
+            MessageId::Event => "Event",
+            MessageId::ResponseEvent => "Response Event",
+            MessageId::UnassignChannel => "Unassign Channel",
+            MessageId::AssignChannel => "Assign Channel",
+            MessageId::ChannelPeriod => "Channel Period",
+            MessageId::ChannelSearchTimeout => "Search Timeout",
+            MessageId::ChannelRadioFrequency => "Channel RF Frequency",
+            MessageId::NetworkKey => "Set Network Key",
+            MessageId::Version => "ANT Version",
+            MessageId::Reset => "Reset System",
+            MessageId::OpenChannel => "Open Channel",
+            MessageId::CloseChannel => "Close Channel",
+            MessageId::Request => "Request Message",
+            MessageId::BroadcastData => "Broadcast Data",
+            MessageId::AcknowledgeData => "Acknowledged Data",
+            MessageId::BurstData => "Burst Transfer Data",
+            MessageId::ChannelId => "Channel ID",
+            MessageId::ChannelStatus => "Channel Status",
+            MessageId::OpenRxScan => "Open Rx Scan Mode",
+            MessageId::LowPrioritySearchTimeout => "Low Priority Search Timeout",
+            MessageId::AutoFrequencyConfig => "Frequency Agility",
+            MessageId::SduConfig => "Configure Selective Data Update",
+            MessageId::SduSetMask => "Set Selective Data Update Mask",
+            MessageId::EnableEncryption => "Enable Single Channel Encryption",
+            MessageId::SetEncryptionKey => "Set Encryption Key",
+            MessageId::SetEncryptionInfo => "Set Encryption Info",
+            MessageId::EncryptionKeyNvm => "Encryption Key NVM Operation",
+            MessageId::Capabilities => "Capabilities",
+            MessageId::LibConfig => "Lib Config",
+            MessageId::Startup => "Startup",
+            MessageId::SerialNumber => "Serial Number",
+            MessageId::EventBufferingConfig => "Event Buffering Configuration",
+            MessageId::AdvancedBurstConfig => "Configure Advanced Burst",
+            MessageId::EventFilterConfig => "Event Filter",
+            MessageId::Unknown(_) => "Unknown message",
         }
     }
 }
 
+// Messages are shown with the name and ID of the message followed by the payload in hex, such as
+// "Broadcast Data (0x4E): 00 01 02 03 04 05 06 07 08".
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({:#04X}): {}",
+            self.id_as_str(),
+            self.id,
+            Hex(self.data())
+        )
+    }
+}
+
+impl fmt::Debug for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Message")
+            .field(
+                "id",
+                &format_args!("{} ({:#04X})", self.id_as_str(), self.id),
+            )
+            .field("data", &Hex(self.data()))
+            .finish()
+    }
+}
+
+/// Formats bytes as space separated hex, such as the raw frames read from and written to the
+/// ANT+ USB device.
+///
+/// log::trace!("Read {}", Hex(&buf));
+pub struct Hex<'a>(pub &'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+// Fixed size fields at the start of a received message, or Malformed if the message is too short.
+fn fields<const N: usize>(id: u8, mesg: &[u8]) -> Result<[u8; N]> {
+    mesg.get(..N)
+        .and_then(|fields| fields.try_into().ok())
+        .ok_or(AntError::Malformed {
+            id,
+            len: mesg.len(),
+        })
+}
+
 fn checksum(buf: &[u8]) -> u8 {
     buf[1..].iter().fold(buf[0], |acc, x| acc ^ x)
 }
 
 /// Process message takes a slice of bytes received in the ReadBuffer and converts the data into
-/// the correct Response
-fn process_message(buf: &[u8]) -> Response {
-    match buf[MESG_ID_OFFSET] {
-        MESG_STARTUP_MESG_ID => Response::Startup(StartupMessage(buf[MESG_DATA_OFFSET])),
-        MESG_RESPONSE_EVENT_ID => {
-            Response::ChannelResponse(ChannelResponseMessage::from(&buf[MESG_DATA_OFFSET..]))
+/// the correct Response. Messages too short for their message ID are Malformed.
+fn process_message(buf: &[u8]) -> Result<Response> {
+    let id = buf[MESG_ID_OFFSET];
+    let data = &buf[MESG_DATA_OFFSET..];
+    Ok(match MessageId::from(id) {
+        MessageId::Startup => Response::Startup(StartupMessage(fields::<1>(id, data)?[0])),
+        MessageId::ResponseEvent => {
+            Response::ChannelResponse(ChannelResponseMessage::try_from(data)?)
+        }
+        MessageId::BroadcastData => Response::BroadcastData(BroadcastDataMessage::try_from(data)?),
+        MessageId::AcknowledgeData => {
+            Response::AcknowledgeData(AcknowledgeDataMessage::try_from(data)?)
         }
-        MESG_BROADCAST_DATA_ID => {
-            Response::BroadcastData(BroadcastDataMessage::from(&buf[MESG_DATA_OFFSET..]))
+        MessageId::BurstData => Response::BurstData(BurstDataMessage::try_from(data)?),
+        MessageId::ChannelId => Response::ChannelId(ChannelIdMessage::try_from(data)?),
+        MessageId::ChannelStatus => Response::ChannelStatus(ChannelStatusMessage::try_from(data)?),
+        MessageId::Capabilities => {
+            let size = buf[MESG_SIZE_OFFSET] as usize;
+            Response::Capabilities(Capabilities::from(
+                &buf[MESG_DATA_OFFSET..(MESG_DATA_OFFSET + size).min(buf.len())],
+            ))
+        }
+        MessageId::SerialNumber => Response::SerialNumber(SerialNumberMessage::try_from(data)?),
+        MessageId::Version => Response::AntVersion(AntVersionMessage::from(data)),
+        MessageId::EventBufferingConfig => {
+            Response::EventBufferConfig(EventBufferConfigMessage::try_from(data)?)
+        }
+        MessageId::AdvancedBurstConfig => {
+            Response::AdvancedBurstCapabilities(AdvancedBurstCapabilitiesMessage::try_from(data)?)
         }
         _ => {
-            println!("Mesg: {:x?}", buf);
-            unimplemented!();
+            debug!("Received unknown message: {:x?}", buf);
+            let size = buf[MESG_SIZE_OFFSET] as usize;
+            Response::Unknown {
+                id,
+                data: buf[MESG_DATA_OFFSET..(MESG_DATA_OFFSET + size).min(buf.len())].to_vec(),
+            }
         }
-    }
+    })
 }
 
 pub fn reset() -> Message {
-    Message::new(MESG_RESET, &[0; 15])
+    Message::with_payload(MESG_RESET, &[0; 15])
 }
 
+/// Sets the key for a network. ANT network keys are 8 bytes, so any bytes after the first 8 are
+/// ignored.
 pub fn set_network_key(network_number: u8, key: &[u8]) -> Message {
     let mut data = vec![network_number];
-    data.extend(key);
-    Message::new(MESG_NETWORK_KEY_ID, &data)
+    data.extend(key.iter().take(8));
+    Message::with_payload(MESG_NETWORK_KEY_ID, &data)
+}
+
+/// Enables extended data on received data messages. Flags are a combination of
+/// LIB_CONFIG_CHANNEL_ID, LIB_CONFIG_RSSI, and LIB_CONFIG_RX_TIMESTAMP. A value of 0 disables
+/// extended data.
+pub fn lib_config(flags: u8) -> Message {
+    Message::with_payload(MESG_LIB_CONFIG_ID, &[0, flags])
 }
 
 pub fn get_capabilities() -> Message {
-    Message::new(MESG_REQUEST, &[0, MESG_CAPABILITIES_ID])
+    Message::with_payload(MESG_REQUEST, &[0, MESG_CAPABILITIES_ID])
+}
+
+pub fn get_serial_number() -> Message {
+    Message::with_payload(MESG_REQUEST, &[0, MESG_GET_SERIAL_NUM_ID])
+}
+
+pub fn get_ant_version() -> Message {
+    Message::with_payload(MESG_REQUEST, &[0, MESG_VERSION_ID])
+}
+
+/// Configures event buffering on the ANT+ USB device. Events are held until size bytes are
+/// buffered or time (in 10ms) has passed, trading USB interrupt rate against latency. A size and
+/// time of 0 disables buffering.
+pub fn set_event_buffer_config(config: EventBufferConfig, size: u16, time: u16) -> Message {
+    Message::with_payload(
+        MESG_EVENT_BUFFERING_CONFIG_ID,
+        &[
+            0,
+            config.into(),
+            (size & 0xFF) as u8,
+            ((size >> 8) & 0xFF) as u8,
+            (time & 0xFF) as u8,
+            ((time >> 8) & 0xFF) as u8,
+        ],
+    )
+}
+
+/// Filters events from being sent by the ANT+ USB device. Filter is a combination of the
+/// EVENT_FILTER_* flags. A filter of 0 sends all events.
+pub fn set_event_filter(filter: u16) -> Message {
+    Message::with_payload(
+        MESG_EVENT_FILTER_CONFIG_ID,
+        &[0, (filter & 0xFF) as u8, ((filter >> 8) & 0xFF) as u8],
+    )
+}
+
+pub fn get_event_buffer_config() -> Message {
+    Message::with_payload(MESG_REQUEST, &[0, MESG_EVENT_BUFFERING_CONFIG_ID])
+}
+
+pub fn get_advanced_burst_capabilities() -> Message {
+    Message::with_payload(MESG_REQUEST, &[0, MESG_CONFIG_ADV_BURST_ID])
 }
 
 pub fn get_channel_id(channel: u8) -> Message {
-    Message::new(MESG_REQUEST, &[channel, MESG_CHANNEL_ID_ID])
+    Message::with_payload(MESG_REQUEST, &[channel, MESG_CHANNEL_ID_ID])
+}
+
+/// Splits data into the burst data packets sent to transmit a burst transfer on a channel. The
+/// last packet is padded with zeros to 8 bytes.
+pub fn burst_data(channel: u8, data: &[u8]) -> Vec<Message> {
+    let mut packets: Vec<&[u8]> = data.chunks(8).collect();
+    if packets.is_empty() {
+        packets.push(&[]);
+    }
+    let last = packets.len() - 1;
+    let mut sequence = 0;
+    packets
+        .into_iter()
+        .enumerate()
+        .map(|(index, packet)| {
+            if index > 0 {
+                sequence = next_burst_sequence(sequence);
+            }
+            let mut channel_sequence = (channel & 0x1F) | (sequence << 5);
+            if index == last {
+                channel_sequence |= 0x80;
+            }
+            let mut payload = [0; 9];
+            payload[0] = channel_sequence;
+            payload[1..=packet.len()].copy_from_slice(packet);
+            Message::with_payload(MESG_BURST_DATA_ID, &payload)
+        })
+        .collect()
+}
+
+pub fn get_channel_status(channel: u8) -> Message {
+    Message::with_payload(MESG_REQUEST, &[channel, MESG_CHANNEL_STATUS_ID])
 }
 
 pub fn assign_channel(channel: u8, channel_type: u8, network: u8) -> Message {
-    Message::new(MESG_ASSIGN_CHANNEL_ID, &[channel, channel_type, network])
+    Message::with_payload(MESG_ASSIGN_CHANNEL_ID, &[channel, channel_type, network])
+}
+
+pub fn assign_channel_extended(
+    channel: u8,
+    channel_type: u8,
+    network: u8,
+    extended_assignment: u8,
+) -> Message {
+    Message::with_payload(
+        MESG_ASSIGN_CHANNEL_ID,
+        &[channel, channel_type, network, extended_assignment],
+    )
+}
+
+pub fn set_channel_id(
+    channel: u8,
+    device_id: u16,
+    device_type: u8,
+    transmission_type: u8,
+) -> Message {
+    Message::with_payload(
+        MESG_CHANNEL_ID_ID,
+        &[
+            channel,
+            (device_id & 0xFF) as u8,
+            ((device_id >> 8) & 0xFF) as u8,
+            device_type,
+            transmission_type,
+        ],
+    )
+}
+
+pub fn set_hp_search_timeout(channel: u8, timeout: u8) -> Message {
+    Message::with_payload(MESG_CHANNEL_SEARCH_TIMEOUT_ID, &[channel, timeout])
+}
+
+// Low priority search timeout is in 2.5s increments. 0xFF searches indefinitely.
+pub fn set_lp_search_timeout(channel: u8, timeout: u8) -> Message {
+    Message::with_payload(MESG_SET_LP_SEARCH_TIMEOUT_ID, &[channel, timeout])
+}
+
+pub fn set_channel_period(channel: u8, period: u16) -> Message {
+    Message::with_payload(
+        MESG_CHANNEL_MESG_PERIOD_ID,
+        &[channel, (period & 0xFF) as u8, ((period >> 8) & 0xFF) as u8],
+    )
+}
+
+pub fn set_channel_frequency(channel: u8, frequency: u8) -> Message {
+    Message::with_payload(MESG_CHANNEL_RADIO_FREQ_ID, &[channel, frequency])
+}
+
+// The three radio frequencies a channel with frequency agility hops between.
+pub fn set_frequency_agility(channel: u8, frequencies: [u8; 3]) -> Message {
+    Message::with_payload(
+        MESG_AUTO_FREQ_CONFIG_ID,
+        &[channel, frequencies[0], frequencies[1], frequencies[2]],
+    )
+}
+
+pub fn open_channel(channel: u8) -> Message {
+    Message::with_payload(MESG_OPEN_CHANNEL_ID, &[channel])
+}
+
+// Continuous scan mode always uses channel 0. The first byte is a filler byte.
+pub fn open_rx_scan_mode() -> Message {
+    Message::with_payload(MESG_OPEN_RX_SCAN_ID, &[0])
+}
+
+pub fn close_channel(channel: u8) -> Message {
+    Message::with_payload(MESG_CLOSE_CHANNEL_ID, &[channel])
+}
+
+pub fn unassign_channel(channel: u8) -> Message {
+    Message::with_payload(MESG_UNASSIGN_CHANNEL_ID, &[channel])
+}
+
+/// Stores a selective data update mask in the mask index. Set bits in the mask select the bytes
+/// of the data payload compared against the last message received. Byte 0 of the mask selects
+/// the data page.
+pub fn set_sdu_mask(mask_index: u8, mask: &[u8; 8]) -> Message {
+    let mut data = vec![mask_index];
+    data.extend(mask);
+    Message::with_payload(MESG_SDU_SET_MASK_ID, &data)
+}
+
+/// Configures a channel to only forward broadcast data when the bytes selected by the mask in
+/// the mask index change.
+pub fn config_selective_data_update(channel: u8, mask_index: u8) -> Message {
+    Message::with_payload(MESG_SDU_CONFIG_ID, &[channel, mask_index])
+}
+
+/// Disables selective data updates on a channel so all broadcast data is forwarded.
+pub fn disable_selective_data_update(channel: u8) -> Message {
+    Message::with_payload(MESG_SDU_CONFIG_ID, &[channel, 0xFF])
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EncryptionMode {
+    Disabled,
+    Enabled,
+    EnabledWithUserInfo,
+}
+
+impl From<EncryptionMode> for u8 {
+    fn from(mode: EncryptionMode) -> u8 {
+        match mode {
+            EncryptionMode::Disabled => 0x00,
+            EncryptionMode::Enabled => 0x01,
+            EncryptionMode::EnabledWithUserInfo => 0x02,
+        }
+    }
 }
 
-pub fn set_channel_id(
+/// Enables encryption on a channel using the key stored in the volatile key index. Decimation
+/// rate is only used by master channels to reduce the rate of encrypted messages to slaves.
+pub fn enable_channel_encryption(
     channel: u8,
-    device_id: u16,
-    device_type: u8,
-    transmission_type: u8,
+    mode: EncryptionMode,
+    key_index: u8,
+    decimation_rate: u8,
 ) -> Message {
-    Message::new(
-        MESG_CHANNEL_ID_ID,
-        &[
-            channel,
-            (device_id & 0xFF) as u8,
-            ((device_id >> 8) & 0xFF) as u8,
-            device_type,
-            transmission_type,
-        ],
+    Message::with_payload(
+        MESG_ENABLE_ENCRYPTION_ID,
+        &[channel, mode.into(), key_index, decimation_rate],
     )
 }
 
-pub fn set_hp_search_timeout(channel: u8, timeout: u8) -> Message {
-    Message::new(MESG_CHANNEL_SEARCH_TIMEOUT_ID, &[channel, timeout])
+/// Stores a 128-bit AES key in the volatile key index. Volatile keys are lost when the ANT+ USB
+/// device is reset.
+pub fn set_encryption_key(key_index: u8, key: &[u8; 16]) -> Message {
+    let mut data = vec![key_index];
+    data.extend(key);
+    Message::with_payload(MESG_SET_ENCRYPTION_KEY_ID, &data)
 }
 
-pub fn set_channel_period(channel: u8, period: u16) -> Message {
-    Message::new(
-        MESG_CHANNEL_MESG_PERIOD_ID,
-        &[channel, (period & 0xFF) as u8, ((period >> 8) & 0xFF) as u8],
-    )
-}
+// Encryption key NVM operations.
+const NVM_LOAD_KEY: u8 = 0x00;
+const NVM_STORE_KEY: u8 = 0x01;
 
-pub fn set_channel_frequency(channel: u8, frequency: u8) -> Message {
-    Message::new(MESG_CHANNEL_RADIO_FREQ_ID, &[channel, frequency])
+/// Loads the key stored in the NVM key slot into the volatile key index so it can be used by
+/// channels enabling encryption.
+pub fn load_encryption_key(nvm_index: u8, volatile_index: u8) -> Message {
+    Message::with_payload(
+        MESG_ENCRYPTION_KEY_NVM_ID,
+        &[NVM_LOAD_KEY, nvm_index, volatile_index],
+    )
 }
 
-pub fn open_channel(channel: u8) -> Message {
-    Message::new(MESG_OPEN_CHANNEL_ID, &[channel])
+/// Stores a 128-bit AES key in the NVM key slot. Keys stored in NVM persist across resets.
+pub fn store_encryption_key(nvm_index: u8, key: &[u8; 16]) -> Message {
+    let mut data = vec![NVM_STORE_KEY, nvm_index];
+    data.extend(key);
+    Message::with_payload(MESG_ENCRYPTION_KEY_NVM_ID, &data)
 }
 
-pub fn close_channel(channel: u8) -> Message {
-    Message::new(MESG_CLOSE_CHANNEL_ID, &[channel])
+/// Sets the 4 byte encryption ID used during encryption negotiation.
+pub fn set_encryption_id(id: u32) -> Message {
+    let mut data = vec![0x00];
+    data.extend(&id.to_le_bytes());
+    Message::with_payload(MESG_SET_ENCRYPTION_INFO_ID, &data)
 }
 
-pub fn unassign_channel(channel: u8) -> Message {
-    Message::new(MESG_UNASSIGN_CHANNEL_ID, &[channel])
+/// Sets the 19 byte user information string sent during encryption negotiation.
+pub fn set_encryption_user_info(info: &[u8; 19]) -> Message {
+    let mut data = vec![0x01];
+    data.extend(info);
+    Message::with_payload(MESG_SET_ENCRYPTION_INFO_ID, &data)
 }
 
 // App message to quit our threads for now
 pub fn quit() -> Message {
-    Message::new(MESG_QUIT, &[0])
+    Message::with_payload(MESG_QUIT, &[0])
 }
 
 // bytes_to_u16 takes a byte slice formatted in [LSB, MSB] and combines the two fields together
@@ -451,15 +1675,23 @@ mod test {
     #[test]
     fn test_new() {
         let data = vec![0; 5];
-        let m = Message::new(0, &data);
+        let m = Message::new(0, &data).unwrap();
         assert_eq!(m.id, 0);
-        assert_eq!(m.data, vec![0; 5]);
+        assert_eq!(m.data(), [0; 5]);
+        assert!(Message::new(0, &[0; MESG_MAX_DATA_SIZE]).is_ok());
+        assert!(matches!(
+            Message::new(MESG_BROADCAST_DATA_ID, &[0; MESG_MAX_DATA_SIZE + 1]),
+            Err(AntError::PayloadTooLarge {
+                id: MESG_BROADCAST_DATA_ID,
+                len: 41
+            })
+        ));
     }
 
     #[test]
     fn test_read_buffer() {
         let mut read_buffer = ReadBuffer::new();
-        let startup_message = Message::new(MESG_STARTUP_MESG_ID, &[0x00]);
+        let startup_message = Message::new(MESG_STARTUP_MESG_ID, &[0x00]).unwrap();
         let mut buffer = startup_message.encode();
         buffer.extend_from_slice(&startup_message.encode()[..]);
         buffer.extend_from_slice(&startup_message.encode()[..]);
@@ -481,10 +1713,36 @@ mod test {
         assert_eq!(read_buffer.next(), None);
     }
 
+    #[test]
+    fn test_parse_frames() {
+        let startup_message = Message::with_payload(MESG_STARTUP_MESG_ID, &[0x00]);
+        let mut buffer = vec![0, 1, 2];
+        buffer.extend_from_slice(&startup_message.encode()[..]);
+        buffer.extend_from_slice(&[MESG_TX_SYNC, 1, 2, 0]);
+        buffer.extend_from_slice(&Message::with_payload(MESG_BROADCAST_DATA_ID, &[0]).encode());
+        buffer.extend_from_slice(&startup_message.encode()[..3]);
+        let responses = parse_frames(&buffer);
+        assert_eq!(responses.len(), 4);
+        assert!(matches!(
+            responses[0],
+            Ok(Response::Startup(StartupMessage(0x00)))
+        ));
+        assert!(matches!(responses[1], Err(AntError::UnableToDecode)));
+        assert!(matches!(
+            responses[2],
+            Err(AntError::Malformed {
+                id: MESG_BROADCAST_DATA_ID,
+                len: 1
+            })
+        ));
+        assert!(matches!(responses[3], Err(AntError::UnableToDecode)));
+        assert!(parse_frames(&[]).is_empty());
+    }
+
     #[test]
     fn test_read_buffer_with_invalid_data() {
         let mut read_buffer = ReadBuffer::new();
-        let startup_message = Message::new(MESG_STARTUP_MESG_ID, &[0x00]);
+        let startup_message = Message::new(MESG_STARTUP_MESG_ID, &[0x00]).unwrap();
         let mut buffer = startup_message.encode();
         buffer.extend_from_slice(&startup_message.encode()[..]);
         buffer.extend_from_slice(&[0, 1, 2, 3]);
@@ -509,7 +1767,7 @@ mod test {
     #[test]
     fn test_read_buffer_with_invalid_mesg() {
         let mut read_buffer = ReadBuffer::new();
-        let startup_message = Message::new(MESG_STARTUP_MESG_ID, &[0x00]);
+        let startup_message = Message::new(MESG_STARTUP_MESG_ID, &[0x00]).unwrap();
         let mut buffer = startup_message.encode();
         buffer.extend_from_slice(&startup_message.encode()[..]);
         buffer.extend_from_slice(&[MESG_TX_SYNC, 1, 2, 0]);
@@ -531,6 +1789,24 @@ mod test {
         assert_eq!(read_buffer.next(), None);
     }
 
+    #[test]
+    fn test_read_buffer_errors() {
+        let mut read_buffer = ReadBuffer::new();
+        let startup_message = Message::with_payload(MESG_STARTUP_MESG_ID, &[0x00]);
+        let mut buffer = startup_message.encode();
+        buffer.extend_from_slice(&[0, 1, 2, 3]);
+        buffer.extend_from_slice(&[MESG_TX_SYNC, 1, 2, 0]);
+        buffer.extend_from_slice(&startup_message.encode()[..]);
+        buffer.extend_from_slice(&[MESG_TX_SYNC, 1, 2, 0]);
+        buffer.extend_from_slice(&startup_message.encode()[..]);
+        read_buffer.inner_as_mut()[..buffer.len()].copy_from_slice(&buffer[..]);
+        read_buffer.len(buffer.len());
+        // Data skipped looking for the sync byte isn't counted, only invalid messages are.
+        assert_eq!(read_buffer.by_ref().count(), 3);
+        assert_eq!(read_buffer.take_errors(), 2);
+        assert_eq!(read_buffer.take_errors(), 0);
+    }
+
     #[test]
     fn test_startup_message() {
         assert_eq!(StartupMessage(0).reason(), StartupReason::PowerOnReset);
@@ -551,7 +1827,7 @@ mod test {
     #[test]
     fn test_encode() {
         let data = [MESG_TX_SYNC, 5, MESG_CAPABILITIES_ID, 1, 0xac, 2, 0x5c, 3];
-        let m = Message::new(data[2], &data[3..]);
+        let m = Message::new(data[2], &data[3..]).unwrap();
         let buf = m.encode();
         let checksum = checksum(&data);
         assert_eq!(buf[0], data[0]);
@@ -562,6 +1838,68 @@ mod test {
         assert_eq!(buf[8], checksum);
     }
 
+    #[test]
+    fn test_message_id() {
+        assert_eq!(MessageId::from(0x4E), MessageId::BroadcastData);
+        assert_eq!(u8::from(MessageId::BroadcastData), MESG_BROADCAST_DATA_ID);
+        assert_eq!(MessageId::from(0x99), MessageId::Unknown(0x99));
+        assert_eq!(u8::from(MessageId::Unknown(0x99)), 0x99);
+        for id in 0..=u8::MAX {
+            assert_eq!(u8::from(MessageId::from(id)), id);
+        }
+        assert_eq!(reset().message_id(), MessageId::Reset);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mesg = BroadcastDataMessage::try_from(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 0x40, 1, 2, 3][..])
+            .unwrap();
+        let json = serde_json::to_string(&mesg).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BroadcastDataMessage>(&json).unwrap(),
+            mesg
+        );
+
+        let mesg = lib_config(LIB_CONFIG_RSSI);
+        let json = serde_json::to_string(&mesg).unwrap();
+        assert_eq!(json, r#"{"id":110,"data":[0,64]}"#);
+        assert!(serde_json::from_str::<Message>(&json).unwrap() == mesg);
+        let json = format!(r#"{{"id":110,"data":{:?}}}"#, [0; MESG_MAX_DATA_SIZE + 1]);
+        assert!(serde_json::from_str::<Message>(&json).is_err());
+    }
+
+    #[test]
+    fn test_format_message() {
+        let mesg = BroadcastDataMessage::new(1, &[0, 1, 2, 3, 4, 5, 0xAB, 0xFF]).to_message();
+        assert_eq!(
+            mesg.to_string(),
+            "Broadcast Data (0x4E): 01 00 01 02 03 04 05 AB FF"
+        );
+        assert_eq!(
+            format!("{:?}", Message::new(MESG_RESET, &[0]).unwrap()),
+            "Message { id: Reset System (0x4A), data: 00 }"
+        );
+        assert_eq!(
+            Message::new(0x99, &[]).unwrap().to_string(),
+            "Unknown message (0x99): "
+        );
+        assert_eq!(
+            Hex(&get_capabilities().encode()).to_string(),
+            "A4 02 4D 00 54 BF"
+        );
+    }
+
+    #[test]
+    fn test_encode_into() {
+        let m = Message::with_payload(MESG_CAPABILITIES_ID, &[1, 0xac, 2, 0x5c, 3]);
+        let mut buf = [0; MESG_MAX_SIZE];
+        let len = m.encode_into(&mut buf);
+        assert_eq!(len, m.encoded_len());
+        assert_eq!(buf[..len], m.encode()[..]);
+        assert_eq!(buf[len..], [0; MESG_MAX_SIZE - 9]);
+    }
+
     #[test]
     fn test_checksum() {
         assert_eq!(checksum(&[2, 3]), 1);
@@ -569,12 +1907,356 @@ mod test {
 
     #[test]
     fn test_process_message() {
-        let startup_message = Message::new(MESG_STARTUP_MESG_ID, &[0x00]);
+        let startup_message = Message::new(MESG_STARTUP_MESG_ID, &[0x00]).unwrap();
         let buf = startup_message.encode();
-        let mesg = process_message(&buf);
+        let mesg = process_message(&buf).unwrap();
         assert_eq!(mesg, Response::Startup(StartupMessage(0x00)));
     }
 
+    #[test]
+    fn test_process_request_responses() {
+        let buf = Message::with_payload(MESG_GET_SERIAL_NUM_ID, &[0x78, 0x56, 0x34, 0x12]).encode();
+        match process_message(&buf[..buf.len() - 1]).unwrap() {
+            Response::SerialNumber(mesg) => assert_eq!(mesg.serial_number(), 0x12345678),
+            _ => panic!("Expected SerialNumber response"),
+        }
+
+        let buf = Message::with_payload(MESG_VERSION_ID, b"AJK1.04RAF\0").encode();
+        match process_message(&buf[..buf.len() - 1]).unwrap() {
+            Response::AntVersion(mesg) => assert_eq!(mesg.version(), "AJK1.04RAF"),
+            _ => panic!("Expected AntVersion response"),
+        }
+
+        let buf = Message::with_payload(MESG_EVENT_BUFFERING_CONFIG_ID, &[0, 1, 0x40, 0, 0x0A, 0])
+            .encode();
+        match process_message(&buf[..buf.len() - 1]).unwrap() {
+            Response::EventBufferConfig(mesg) => {
+                assert_eq!(mesg.buffer_config(), EventBufferConfig::AllEvents);
+                assert_eq!(mesg.size(), 64);
+                assert_eq!(mesg.time(), 10);
+            }
+            _ => panic!("Expected EventBufferConfig response"),
+        }
+
+        let buf = Message::with_payload(MESG_CONFIG_ADV_BURST_ID, &[0, 0x03, 0x01, 0, 0]).encode();
+        match process_message(&buf[..buf.len() - 1]).unwrap() {
+            Response::AdvancedBurstCapabilities(mesg) => {
+                assert_eq!(mesg.max_packet_length(), 24);
+                assert_eq!(mesg.features(), 0x01);
+            }
+            _ => panic!("Expected AdvancedBurstCapabilities response"),
+        }
+    }
+
+    #[test]
+    fn test_process_channel_id() {
+        let buf = Message::with_payload(MESG_CHANNEL_ID_ID, &[1, 0x39, 0x30, 0x78, 0x01]).encode();
+        match process_message(&buf[..buf.len() - 1]).unwrap() {
+            Response::ChannelId(mesg) => {
+                assert_eq!(mesg.channel(), 1);
+                assert_eq!(mesg.device_id(), 12345);
+                assert_eq!(mesg.device_type(), 0x78);
+                assert_eq!(mesg.transmission_type(), 0x01);
+            }
+            _ => panic!("Expected ChannelId response"),
+        }
+    }
+
+    #[test]
+    fn test_process_channel_status() {
+        let buf = Message::with_payload(MESG_CHANNEL_STATUS_ID, &[2, 0x17]).encode();
+        match process_message(&buf[..buf.len() - 1]).unwrap() {
+            Response::ChannelStatus(mesg) => {
+                assert_eq!(mesg.channel(), 2);
+                assert_eq!(mesg.status(), ChannelStatus::Tracking);
+                assert_eq!(mesg.network(), 1);
+                assert_eq!(mesg.channel_type(), 0x10);
+            }
+            _ => panic!("Expected ChannelStatus response"),
+        }
+    }
+
+    #[test]
+    fn test_process_capabilities() {
+        let buf = Message::with_payload(MESG_CAPABILITIES_ID, &[8, 3, 0, 0xBA, 0x36, 0, 0xDF, 0])
+            .encode();
+        match process_message(&buf[..buf.len() - 1]).unwrap() {
+            Response::Capabilities(capabilities) => {
+                assert_eq!(capabilities.max_channels, 8);
+                assert_eq!(capabilities.max_networks, 3);
+                assert!(capabilities.supports_extended_messages());
+                assert!(capabilities.supports_scan_mode());
+                assert!(capabilities.supports_event_buffering());
+                assert!(capabilities.supports_encryption());
+            }
+            _ => panic!("Expected Capabilities response"),
+        }
+
+        // Older devices only send the first 4 bytes.
+        let capabilities = Capabilities::from(&[4, 1, 0, 0]);
+        assert_eq!(capabilities.max_channels, 4);
+        assert!(!capabilities.supports_extended_messages());
+    }
+
+    #[test]
+    fn test_process_unknown_message() {
+        let buf = Message::with_payload(0xE2, &[0x01, 0x02]).encode();
+        assert_eq!(
+            process_message(&buf[..buf.len() - 1]).unwrap(),
+            Response::Unknown {
+                id: 0xE2,
+                data: vec![0x01, 0x02]
+            }
+        );
+
+        let mesg = ChannelResponseMessage::try_from(&[0, 1, 0x40][..]).unwrap();
+        assert_eq!(mesg.code(), ChannelResponseCode::Unknown(0x40));
+    }
+
+    #[test]
+    fn test_process_extended_broadcast_message() {
+        let data = [
+            0, 0x04, 0xFF, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0x80, 0xD2, 0x04, 0x78, 0x01,
+        ];
+        let buf = Message::with_payload(MESG_BROADCAST_DATA_ID, &data).encode();
+        // ReadBuffer strips the checksum before processing the message.
+        match process_message(&buf[..buf.len() - 1]).unwrap() {
+            Response::BroadcastData(mesg) => {
+                assert_eq!(mesg.channel(), 0);
+                assert_eq!(mesg.flag(), Some(LIB_CONFIG_CHANNEL_ID));
+                assert_eq!(mesg.extended_data(), [0xD2, 0x04, 0x78, 0x01]);
+                assert_eq!(
+                    mesg.data(),
+                    [0x04, 0xFF, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC]
+                );
+            }
+            _ => panic!("Expected broadcast data"),
+        }
+    }
+
+    #[test]
+    fn test_broadcast_rssi() {
+        let data = [
+            1, 0, 0, 0, 0, 0, 0, 0, 0, 0xC0, 0xD2, 0x04, 0x78, 0x01, 0x20, 0xB5, 0x80,
+        ];
+        let mesg = BroadcastDataMessage::try_from(&data[..]).unwrap();
+        assert_eq!(
+            mesg.rssi(),
+            Some(Rssi {
+                measurement_type: 0x20,
+                value: -75,
+                threshold: -128,
+            })
+        );
+        let mesg = BroadcastDataMessage::try_from(&data[..9]).unwrap();
+        assert_eq!(mesg.rssi(), None);
+    }
+
+    #[test]
+    fn test_broadcast_rx_timestamp() {
+        let data = [
+            1, 0, 0, 0, 0, 0, 0, 0, 0, 0xE0, 0xD2, 0x04, 0x78, 0x01, 0x20, 0xB5, 0x80, 0x34, 0x12,
+        ];
+        let mesg = BroadcastDataMessage::try_from(&data[..]).unwrap();
+        assert_eq!(mesg.rx_timestamp(), Some(0x1234));
+        let mesg =
+            BroadcastDataMessage::try_from(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0x20, 0x34, 0x12][..])
+                .unwrap();
+        assert_eq!(mesg.rx_timestamp(), Some(0x1234));
+        assert_eq!(mesg.rssi(), None);
+    }
+
+    #[test]
+    fn test_broadcast_channel_id() {
+        let data = [
+            1, 0, 0, 0, 0, 0, 0, 0, 0, 0xC0, 0xD2, 0x04, 0x78, 0x01, 0x20, 0xB5, 0x80,
+        ];
+        let mesg = BroadcastDataMessage::try_from(&data[..]).unwrap();
+        assert_eq!(mesg.device_id(), Some(1234));
+        assert_eq!(mesg.device_type(), Some(0x78));
+        assert_eq!(mesg.transmission_type(), Some(0x01));
+        let mesg =
+            BroadcastDataMessage::try_from(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0x20, 0x34, 0x12][..])
+                .unwrap();
+        assert_eq!(mesg.device_id(), None);
+    }
+
+    #[test]
+    fn test_read_buffer_split_message() {
+        let mut read_buffer = ReadBuffer::new();
+        let startup = Message::with_payload(MESG_STARTUP_MESG_ID, &[0x00]).encode();
+        let broadcast = BroadcastDataMessage::new(1, &[2; 8]).to_message().encode();
+        let mut buffer = startup.clone();
+        buffer.extend_from_slice(&broadcast[..6]);
+        read_buffer.inner_as_mut()[..buffer.len()].copy_from_slice(&buffer[..]);
+        read_buffer.len(buffer.len());
+        assert_eq!(
+            read_buffer.next(),
+            Some(Response::Startup(StartupMessage(0x00)))
+        );
+        assert_eq!(read_buffer.next(), None);
+
+        // Rest of the broadcast data is received in the next read.
+        let buffer = &broadcast[6..];
+        read_buffer.inner_as_mut()[..buffer.len()].copy_from_slice(buffer);
+        read_buffer.len(buffer.len());
+        match read_buffer.next() {
+            Some(Response::BroadcastData(mesg)) => assert_eq!(mesg.data(), [2; 8]),
+            _ => panic!("Expected broadcast data"),
+        }
+        assert_eq!(read_buffer.next(), None);
+
+        // Message split right after the sync byte.
+        read_buffer.inner_as_mut()[..1].copy_from_slice(&startup[..1]);
+        read_buffer.len(1);
+        assert_eq!(read_buffer.next(), None);
+        let buffer = &startup[1..];
+        read_buffer.inner_as_mut()[..buffer.len()].copy_from_slice(buffer);
+        read_buffer.len(buffer.len());
+        assert_eq!(
+            read_buffer.next(),
+            Some(Response::Startup(StartupMessage(0x00)))
+        );
+        assert_eq!(read_buffer.next(), None);
+        assert_eq!(read_buffer.take_errors(), 0);
+    }
+
+    #[test]
+    fn test_read_buffer_invalid_size() {
+        let mut read_buffer = ReadBuffer::new();
+        let startup = Message::with_payload(MESG_STARTUP_MESG_ID, &[0x00]).encode();
+        let mut buffer = vec![MESG_TX_SYNC, 0xFF];
+        buffer.extend_from_slice(&startup);
+        read_buffer.inner_as_mut()[..buffer.len()].copy_from_slice(&buffer[..]);
+        read_buffer.len(buffer.len());
+        assert_eq!(
+            read_buffer.next(),
+            Some(Response::Startup(StartupMessage(0x00)))
+        );
+        assert_eq!(read_buffer.next(), None);
+        assert_eq!(read_buffer.take_errors(), 1);
+    }
+
+    #[test]
+    fn test_read_buffer_rx_sync() {
+        let mut read_buffer = ReadBuffer::new();
+        let mut buffer = Message::with_payload(MESG_STARTUP_MESG_ID, &[0x00]).encode();
+        // Checksum covers the sync byte.
+        buffer[0] = MESG_RX_SYNC;
+        let last = buffer.len() - 1;
+        buffer[last] ^= MESG_TX_SYNC ^ MESG_RX_SYNC;
+        buffer.extend_from_slice(&Message::with_payload(MESG_STARTUP_MESG_ID, &[0x20]).encode());
+        read_buffer.inner_as_mut()[..buffer.len()].copy_from_slice(&buffer[..]);
+        read_buffer.len(buffer.len());
+        assert_eq!(
+            read_buffer.next(),
+            Some(Response::Startup(StartupMessage(0x00)))
+        );
+        assert_eq!(
+            read_buffer.next(),
+            Some(Response::Startup(StartupMessage(0x20)))
+        );
+        assert_eq!(read_buffer.next(), None);
+        assert_eq!(read_buffer.take_errors(), 0);
+    }
+
+    #[test]
+    fn test_malformed_message() {
+        // Broadcast data with only 4 bytes of data.
+        let buf = Message::with_payload(MESG_BROADCAST_DATA_ID, &[0, 1, 2, 3]).encode();
+        assert!(matches!(
+            process_message(&buf[..buf.len() - 1]),
+            Err(AntError::Malformed {
+                id: MESG_BROADCAST_DATA_ID,
+                len: 4
+            })
+        ));
+        assert!(ChannelResponseMessage::try_from(&[0, 1][..]).is_err());
+        assert!(AcknowledgeDataMessage::try_from(&[0; 8][..]).is_err());
+
+        // Malformed message is dropped without stopping the messages after it.
+        let mut read_buffer = ReadBuffer::new();
+        let mut buffer = buf;
+        buffer.extend_from_slice(&Message::with_payload(MESG_STARTUP_MESG_ID, &[0x00]).encode());
+        read_buffer.inner_as_mut()[..buffer.len()].copy_from_slice(&buffer[..]);
+        read_buffer.len(buffer.len());
+        assert_eq!(
+            read_buffer.next(),
+            Some(Response::Startup(StartupMessage(0x00)))
+        );
+        assert_eq!(read_buffer.next(), None);
+        assert_eq!(read_buffer.take_errors(), 1);
+    }
+
+    #[test]
+    fn test_read_buffer_timestamp() {
+        let mut read_buffer = ReadBuffer::new();
+        let buffer = BroadcastDataMessage::new(0, &[0; 8]).to_message().encode();
+        read_buffer.inner_as_mut()[..buffer.len()].copy_from_slice(&buffer[..]);
+        read_buffer.len(buffer.len());
+        match read_buffer.next() {
+            Some(Response::BroadcastData(mesg)) => assert!(mesg.timestamp().is_some()),
+            _ => panic!("Expected broadcast data"),
+        }
+    }
+
+    #[test]
+    fn test_burst_buffer() {
+        let mut burst = BurstBuffer::new();
+        let packet = |channel_sequence: u8, value: u8| {
+            let mut data = [value; 9];
+            data[0] = channel_sequence;
+            BurstDataMessage::try_from(&data[..]).unwrap()
+        };
+        assert_eq!(burst.push(&packet(0x01, 0)).unwrap(), None);
+        assert_eq!(burst.push(&packet(0x21, 1)).unwrap(), None);
+        assert_eq!(burst.push(&packet(0x41, 2)).unwrap(), None);
+        assert_eq!(burst.push(&packet(0x61, 3)).unwrap(), None);
+        let data = burst.push(&packet(0xA1, 4)).unwrap().unwrap();
+        assert_eq!(data.len(), 40);
+        assert_eq!(data[32..], [4; 8]);
+        // Out of sequence packet drops the transfer.
+        assert_eq!(burst.push(&packet(0x01, 0)).unwrap(), None);
+        assert!(matches!(
+            burst.push(&packet(0x41, 2)),
+            Err(AntError::BurstSequenceError(1))
+        ));
+        assert!(burst.push(&packet(0xA1, 1)).is_err());
+    }
+
+    #[test]
+    fn test_burst_data() {
+        let data: Vec<u8> = (0..36).collect();
+        let packets = burst_data(1, &data);
+        assert_eq!(packets.len(), 5);
+        //MESG_BURST_DATA_ID = 0x50
+        assert_eq!(packets[0].id, 0x50);
+        assert_eq!(packets[0].data()[..], [0x01, 0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(packets[1].data()[0], 0x21);
+        assert_eq!(packets[3].data()[0], 0x61);
+        assert_eq!(packets[4].data()[..], [0xA1, 32, 33, 34, 35, 0, 0, 0, 0]);
+
+        // Packets sent are reassembled by a BurstBuffer.
+        let mut burst = BurstBuffer::new();
+        for packet in &packets[..4] {
+            assert_eq!(
+                burst
+                    .push(&BurstDataMessage::try_from(packet.data()).unwrap())
+                    .unwrap(),
+                None
+            );
+        }
+        let received = burst
+            .push(&BurstDataMessage::try_from(packets[4].data()).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(received[..36], data[..]);
+
+        let packets = burst_data(0, &[]);
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].data()[0], 0x80);
+    }
+
     // The following tests test message creation. Since we use constants
     // for the ID, we want to assert against the value of the constant.
     // This way if the value of the constant is changed above, the test will
@@ -589,7 +2271,7 @@ mod test {
         let mesg = reset();
         //MESG_RESET = 0x4A
         assert_eq!(mesg.id, 0x4A);
-        assert_eq!(mesg.data[..], [0; 15]);
+        assert_eq!(mesg.data()[..], [0; 15]);
     }
 
     #[test]
@@ -598,7 +2280,7 @@ mod test {
         let mesg = set_network_key(0, &key);
         // MESG_NETWORK_KEY_ID = 0x46
         assert_eq!(mesg.id, 0x46);
-        assert_eq!(mesg.data[..], [0; 9]);
+        assert_eq!(mesg.data()[..], [0; 9]);
     }
 
     #[test]
@@ -607,7 +2289,59 @@ mod test {
         // MESG_REQUEST = 0x4D
         // MESG_CAPABILITIES_ID = 0x54
         assert_eq!(mesg.id, 0x4D);
-        assert_eq!(mesg.data[..], [0, 0x54]);
+        assert_eq!(mesg.data()[..], [0, 0x54]);
+    }
+
+    #[test]
+    fn get_serial_number_message() {
+        let mesg = get_serial_number();
+        // MESG_REQUEST = 0x4D
+        // MESG_GET_SERIAL_NUM_ID = 0x61
+        assert_eq!(mesg.id, 0x4D);
+        assert_eq!(mesg.data()[..], [0, 0x61]);
+    }
+
+    #[test]
+    fn get_ant_version_message() {
+        let mesg = get_ant_version();
+        // MESG_REQUEST = 0x4D
+        // MESG_VERSION_ID = 0x3E
+        assert_eq!(mesg.id, 0x4D);
+        assert_eq!(mesg.data()[..], [0, 0x3E]);
+    }
+
+    #[test]
+    fn get_event_buffer_config_message() {
+        let mesg = get_event_buffer_config();
+        // MESG_REQUEST = 0x4D
+        // MESG_EVENT_BUFFERING_CONFIG_ID = 0x74
+        assert_eq!(mesg.id, 0x4D);
+        assert_eq!(mesg.data()[..], [0, 0x74]);
+    }
+
+    #[test]
+    fn set_event_buffer_config_message() {
+        let mesg = set_event_buffer_config(EventBufferConfig::AllEvents, 256, 10);
+        // MESG_EVENT_BUFFERING_CONFIG_ID = 0x74
+        assert_eq!(mesg.id, 0x74);
+        assert_eq!(mesg.data()[..], [0, 1, 0, 1, 10, 0]);
+    }
+
+    #[test]
+    fn set_event_filter_message() {
+        let mesg = set_event_filter(EVENT_FILTER_RX_FAIL | EVENT_FILTER_CHANNEL_COLLISION);
+        // MESG_EVENT_FILTER_CONFIG_ID = 0x79
+        assert_eq!(mesg.id, 0x79);
+        assert_eq!(mesg.data()[..], [0, 0x02, 0x01]);
+    }
+
+    #[test]
+    fn get_advanced_burst_capabilities_message() {
+        let mesg = get_advanced_burst_capabilities();
+        // MESG_REQUEST = 0x4D
+        // MESG_CONFIG_ADV_BURST_ID = 0x78
+        assert_eq!(mesg.id, 0x4D);
+        assert_eq!(mesg.data()[..], [0, 0x78]);
     }
 
     #[test]
@@ -616,7 +2350,16 @@ mod test {
         // MESG_REQUEST = 0x4D
         // MESG_CHANNEL_ID_ID = 0x51
         assert_eq!(mesg.id, 0x4D);
-        assert_eq!(mesg.data[..], [0, 0x51]);
+        assert_eq!(mesg.data()[..], [0, 0x51]);
+    }
+
+    #[test]
+    fn get_channel_status_message() {
+        let mesg = get_channel_status(2);
+        // MESG_REQUEST = 0x4D
+        // MESG_CHANNEL_STATUS_ID = 0x52
+        assert_eq!(mesg.id, 0x4D);
+        assert_eq!(mesg.data()[..], [2, 0x52]);
     }
 
     #[test]
@@ -624,7 +2367,23 @@ mod test {
         let mesg = assign_channel(0, 0, 0);
         // MESG_ASSIGN_CHANNEL_ID = 0x42
         assert_eq!(mesg.id, 0x42);
-        assert_eq!(mesg.data[..], [0, 0, 0]);
+        assert_eq!(mesg.data()[..], [0, 0, 0]);
+    }
+
+    #[test]
+    fn assign_channel_extended_message() {
+        let mesg = assign_channel_extended(0, 0x10, 1, EXT_ASSIGN_FREQUENCY_AGILITY);
+        // MESG_ASSIGN_CHANNEL_ID = 0x42
+        assert_eq!(mesg.id, 0x42);
+        assert_eq!(mesg.data()[..], [0, 0x10, 1, 0x04]);
+    }
+
+    #[test]
+    fn set_frequency_agility_message() {
+        let mesg = set_frequency_agility(0, [3, 39, 75]);
+        // MESG_AUTO_FREQ_CONFIG_ID = 0x70
+        assert_eq!(mesg.id, 0x70);
+        assert_eq!(mesg.data()[..], [0, 3, 39, 75]);
     }
 
     #[test]
@@ -632,11 +2391,11 @@ mod test {
         let mesg = set_channel_id(0, 1000, 0x78, 0);
         // MESG_CHANNEL_ID_ID = 0x51
         assert_eq!(mesg.id, 0x51);
-        assert_eq!(mesg.data[0], 0);
-        assert_eq!(mesg.data[1], (1000 & 0xFF) as u8);
-        assert_eq!(mesg.data[2], ((1000 >> 8) & 0xFF) as u8);
-        assert_eq!(mesg.data[3], 0x78);
-        assert_eq!(mesg.data[4], 0);
+        assert_eq!(mesg.data()[0], 0);
+        assert_eq!(mesg.data()[1], (1000 & 0xFF) as u8);
+        assert_eq!(mesg.data()[2], ((1000 >> 8) & 0xFF) as u8);
+        assert_eq!(mesg.data()[3], 0x78);
+        assert_eq!(mesg.data()[4], 0);
     }
 
     #[test]
@@ -644,7 +2403,15 @@ mod test {
         let mesg = set_hp_search_timeout(0, 30);
         // MESG_CHANNEL_SEARCH_TIMEOUT_ID = 0x44
         assert_eq!(mesg.id, 0x44);
-        assert_eq!(mesg.data[..], [0, 30]);
+        assert_eq!(mesg.data()[..], [0, 30]);
+    }
+
+    #[test]
+    fn set_lp_search_timeout_message() {
+        let mesg = set_lp_search_timeout(0, 0xFF);
+        // MESG_SET_LP_SEARCH_TIMEOUT_ID = 0x63
+        assert_eq!(mesg.id, 0x63);
+        assert_eq!(mesg.data()[..], [0, 0xFF]);
     }
 
     #[test]
@@ -652,9 +2419,9 @@ mod test {
         let mesg = set_channel_period(0, 8070);
         // MESG_CHANNEL_MESG_PERIOD_ID = 0x43
         assert_eq!(mesg.id, 0x43);
-        assert_eq!(mesg.data[0], 0);
-        assert_eq!(mesg.data[1], (8070 & 0xFF) as u8);
-        assert_eq!(mesg.data[2], ((8070 >> 8) & 0xFF) as u8);
+        assert_eq!(mesg.data()[0], 0);
+        assert_eq!(mesg.data()[1], (8070 & 0xFF) as u8);
+        assert_eq!(mesg.data()[2], ((8070 >> 8) & 0xFF) as u8);
     }
 
     #[test]
@@ -662,7 +2429,7 @@ mod test {
         let mesg = set_channel_frequency(0, 0x39);
         // MESG_CHANNEL_RADIO_FREQ_ID = 0x45
         assert_eq!(mesg.id, 0x45);
-        assert_eq!(mesg.data[..], [0, 0x39]);
+        assert_eq!(mesg.data()[..], [0, 0x39]);
     }
 
     #[test]
@@ -670,7 +2437,15 @@ mod test {
         let mesg = open_channel(0);
         // MESG_OPEN_CHANNEL_ID = 0x4B
         assert_eq!(mesg.id, 0x4B);
-        assert_eq!(mesg.data[..], [0]);
+        assert_eq!(mesg.data()[..], [0]);
+    }
+
+    #[test]
+    fn open_rx_scan_mode_message() {
+        let mesg = open_rx_scan_mode();
+        // MESG_OPEN_RX_SCAN_ID = 0x5B
+        assert_eq!(mesg.id, 0x5B);
+        assert_eq!(mesg.data()[..], [0]);
     }
 
     #[test]
@@ -678,7 +2453,79 @@ mod test {
         let mesg = close_channel(0);
         // MESG_CLOSE_CHANNEL_ID = 0x4C
         assert_eq!(mesg.id, 0x4C);
-        assert_eq!(mesg.data[..], [0]);
+        assert_eq!(mesg.data()[..], [0]);
+    }
+
+    #[test]
+    fn broadcast_data_message() {
+        let mesg = BroadcastDataMessage::new(1, &[0x10, 1, 2, 3, 4, 5, 6, 7]).to_message();
+        // MESG_BROADCAST_DATA_ID = 0x4E
+        assert_eq!(mesg.id, 0x4E);
+        assert_eq!(mesg.data()[..], [1, 0x10, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn lib_config_message() {
+        let mesg = lib_config(LIB_CONFIG_CHANNEL_ID | LIB_CONFIG_RSSI);
+        // MESG_LIB_CONFIG_ID = 0x6E
+        assert_eq!(mesg.id, 0x6E);
+        assert_eq!(mesg.data()[..], [0, 0xC0]);
+    }
+
+    #[test]
+    fn selective_data_update_messages() {
+        let mesg = set_sdu_mask(1, &[0xFF, 0, 0, 0, 0, 0, 0xFF, 0xFF]);
+        // MESG_SDU_SET_MASK_ID = 0x7B
+        assert_eq!(mesg.id, 0x7B);
+        assert_eq!(mesg.data()[..], [1, 0xFF, 0, 0, 0, 0, 0, 0xFF, 0xFF]);
+        let mesg = config_selective_data_update(2, 1);
+        // MESG_SDU_CONFIG_ID = 0x7A
+        assert_eq!(mesg.id, 0x7A);
+        assert_eq!(mesg.data()[..], [2, 1]);
+        let mesg = disable_selective_data_update(2);
+        assert_eq!(mesg.id, 0x7A);
+        assert_eq!(mesg.data()[..], [2, 0xFF]);
+    }
+
+    #[test]
+    fn enable_channel_encryption_message() {
+        let mesg = enable_channel_encryption(1, EncryptionMode::Enabled, 0, 1);
+        // MESG_ENABLE_ENCRYPTION_ID = 0x7D
+        assert_eq!(mesg.id, 0x7D);
+        assert_eq!(mesg.data()[..], [1, 1, 0, 1]);
+    }
+
+    #[test]
+    fn set_encryption_key_message() {
+        let mesg = set_encryption_key(0, &[0xAA; 16]);
+        // MESG_SET_ENCRYPTION_KEY_ID = 0x7E
+        assert_eq!(mesg.id, 0x7E);
+        assert_eq!(mesg.data()[0], 0);
+        assert_eq!(mesg.data()[1..], [0xAA; 16]);
+    }
+
+    #[test]
+    fn encryption_key_nvm_message() {
+        let mesg = load_encryption_key(2, 0);
+        // MESG_ENCRYPTION_KEY_NVM_ID = 0x83
+        assert_eq!(mesg.id, 0x83);
+        assert_eq!(mesg.data()[..], [0, 2, 0]);
+        let mesg = store_encryption_key(2, &[0xAA; 16]);
+        assert_eq!(mesg.id, 0x83);
+        assert_eq!(mesg.data()[..2], [1, 2]);
+        assert_eq!(mesg.data()[2..], [0xAA; 16]);
+    }
+
+    #[test]
+    fn set_encryption_info_message() {
+        let mesg = set_encryption_id(0x12345678);
+        // MESG_SET_ENCRYPTION_INFO_ID = 0x7F
+        assert_eq!(mesg.id, 0x7F);
+        assert_eq!(mesg.data()[..], [0, 0x78, 0x56, 0x34, 0x12]);
+        let mesg = set_encryption_user_info(&[0x41; 19]);
+        assert_eq!(mesg.id, 0x7F);
+        assert_eq!(mesg.data()[0], 1);
+        assert_eq!(mesg.data()[1..], [0x41; 19]);
     }
 
     #[test]
@@ -686,6 +2533,6 @@ mod test {
         let mesg = unassign_channel(0);
         // MESG_UNASSIGN_CHANNEL_ID = 0x41
         assert_eq!(mesg.id, 0x41);
-        assert_eq!(mesg.data[..], [0]);
+        assert_eq!(mesg.data()[..], [0]);
     }
 }
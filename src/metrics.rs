@@ -0,0 +1,43 @@
+/// The metrics module provides the counters kept by the run loop. A snapshot is sent back as
+/// Response::Metrics when Request::GetMetrics is received.
+use std::collections::BTreeMap;
+
+/// Counters for the ANT+ USB device since the run loop started. Channel counters are kept by
+/// channel number and are not cleared when a channel is closed.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metrics {
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    /// Messages read with an invalid size or checksum, or with data that could not be decoded.
+    pub parse_errors: u64,
+    pub channels: BTreeMap<u8, ChannelMetrics>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    /// Counters for a channel, or None if nothing has been counted for the channel.
+    pub fn channel(&self, number: u8) -> Option<&ChannelMetrics> {
+        self.channels.get(&number)
+    }
+
+    pub(crate) fn channel_mut(&mut self, number: u8) -> &mut ChannelMetrics {
+        self.channels.entry(number).or_default()
+    }
+}
+
+/// Counters for a single channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelMetrics {
+    pub broadcasts: u64,
+    pub rx_fails: u64,
+    pub search_timeouts: u64,
+    pub transfers_completed: u64,
+    pub transfers_failed: u64,
+}
@@ -0,0 +1,374 @@
+/// The nusb_device module provides NusbDevice, an ANT+ USB device backend built on nusb instead
+/// of libusb. Reads are kept queued as asynchronous bulk transfers, so data is delivered as soon
+/// as the ANT+ USB device sends it instead of between blocking reads, and the read timeout only
+/// bounds how long the reader takes to notice it has been stopped.
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures::executor::block_on;
+use futures::future;
+use futures::task::{self, ArcWake};
+use nusb::transfer::{
+    Completion, Direction, EndpointType, Queue, RequestBuffer, TransferError, TransferRequest,
+};
+use nusb::DeviceInfo;
+
+use crate::error::AntError;
+use crate::message::READ_SIZE;
+use crate::transport::Transport;
+use crate::usb::{self, BulkEndpoints, Endpoints, Selector, StickAddress, StickInfo, StickModel};
+use crate::Result;
+
+// Number of reads kept queued so data is read while the previous read is handled.
+const QUEUED_READS: usize = 2;
+
+/// NusbDevice holds the claimed interface of an ANT+ USB device opened with nusb.
+pub struct NusbDevice {
+    reads: Mutex<Queue<RequestBuffer>>,
+    writes: Mutex<Queue<Vec<u8>>>,
+    read_signal: Arc<Signal>,
+    write_signal: Arc<Signal>,
+    info: StickInfo,
+}
+
+impl NusbDevice {
+    /// Initialize the ANT+ device plugged in that matches the selector and one of the models,
+    /// as UsbDevice::init_with_models does. Selecting by address is only supported on Linux,
+    /// where nusb reports the bus number.
+    pub fn init(
+        selector: &Selector,
+        models: &[StickModel],
+        detach_kernel_driver: bool,
+    ) -> Result<NusbDevice> {
+        // nusb only reports the bus number on Linux, so addresses can't be matched elsewhere.
+        #[cfg(not(target_os = "linux"))]
+        {
+            if let Selector::Address(_) = selector {
+                return Err(AntError::AddressNotSupported);
+            }
+        }
+        let mut index = 0;
+        for device in nusb::list_devices()? {
+            let model = match usb::find_model(models, device.vendor_id(), device.product_id()) {
+                Some(model) => model,
+                None => continue,
+            };
+            let address = StickAddress {
+                bus: device.bus_id().parse().unwrap_or_default(),
+                address: device.device_address(),
+            };
+            let selected =
+                selector.selects(address, index, || device.serial_number().map(String::from));
+            index += 1;
+            if selected {
+                log::debug!("Opening {} with nusb", model.name);
+                return Self::open(&device, model, detach_kernel_driver);
+            }
+        }
+        Err(AntError::UsbDeviceError(rusb::Error::NoDevice))
+    }
+
+    // The configuration is left as selected by the operating system, which is the only
+    // configuration of the known ANT+ USB devices.
+    fn open(device: &DeviceInfo, model: StickModel, detach_kernel_driver: bool) -> Result<Self> {
+        let handle = device.open()?;
+        let endpoints = match handle.active_configuration() {
+            Ok(config) => usb::find_endpoints(bulk_endpoints(&config), &model),
+            Err(_) => Endpoints::from(&model),
+        };
+        log::debug!("Using {:?}", endpoints);
+        // Kernel drivers can only be detached on Linux. Elsewhere this claims the interface.
+        let interface = if detach_kernel_driver {
+            handle.detach_and_claim_interface(endpoints.interface)?
+        } else {
+            handle.claim_interface(endpoints.interface)?
+        };
+        let info = StickInfo {
+            manufacturer: device.manufacturer_string().map(String::from),
+            product: device.product_string().map(String::from),
+            serial_number: device.serial_number().map(String::from),
+            vendor_id: device.vendor_id(),
+            product_id: device.product_id(),
+        };
+        Ok(Self {
+            reads: Mutex::new(interface.bulk_in_queue(endpoints.endpoint_in)),
+            writes: Mutex::new(interface.bulk_out_queue(endpoints.endpoint_out)),
+            read_signal: Arc::new(Signal::default()),
+            write_signal: Arc::new(Signal::default()),
+            info,
+        })
+    }
+
+    /// Manufacturer, product, serial number, and USB IDs of the USB device.
+    pub fn info(&self) -> &StickInfo {
+        &self.info
+    }
+}
+
+// Bulk endpoints of the first alternate setting of each interface in a configuration.
+fn bulk_endpoints(config: &nusb::descriptors::Configuration) -> Vec<BulkEndpoints> {
+    let mut interfaces = Vec::new();
+    for desc in config.interface_alt_settings() {
+        if desc.alternate_setting() != 0 {
+            continue;
+        }
+        let mut bulk = BulkEndpoints {
+            interface: desc.interface_number(),
+            ..Default::default()
+        };
+        for endpoint in desc.endpoints() {
+            if endpoint.transfer_type() != EndpointType::Bulk {
+                continue;
+            }
+            let address = match endpoint.direction() {
+                Direction::In => &mut bulk.endpoint_in,
+                Direction::Out => &mut bulk.endpoint_out,
+            };
+            address.get_or_insert(endpoint.address());
+        }
+        interfaces.push(bulk);
+    }
+    interfaces
+}
+
+impl Transport for NusbDevice {
+    fn read_with_timeout(&self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        let mut reads = self.reads.lock().unwrap();
+        while reads.pending() < QUEUED_READS {
+            reads.submit(RequestBuffer::new(READ_SIZE));
+        }
+        // A read still queued when the timeout passes is kept for the next call.
+        let completion = wait(&mut reads, &self.read_signal, timeout)
+            .ok_or(AntError::UsbDeviceError(rusb::Error::Timeout))?;
+        let data = completion.into_result().map_err(transfer_error)?;
+        let len = data.len().min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        reads.submit(RequestBuffer::reuse(data, READ_SIZE));
+        Ok(len)
+    }
+
+    fn write_with_timeout(&self, data: &[u8], timeout: Duration) -> Result<usize> {
+        let mut writes = self.writes.lock().unwrap();
+        writes.submit(data.to_vec());
+        let response = finish_write(&mut *writes, &self.write_signal, timeout)?;
+        Ok(response.actual_length())
+    }
+
+    fn stick_info(&self) -> Option<StickInfo> {
+        Some(self.info.clone())
+    }
+}
+
+// The parts of an nusb Queue used to wait on transfers, so waiting can be tested without an
+// ANT+ USB device.
+trait Transfers {
+    type Response;
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Completion<Self::Response>>;
+    fn cancel_all(&mut self);
+}
+
+impl<R: TransferRequest> Transfers for Queue<R> {
+    type Response = R::Response;
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Completion<R::Response>> {
+        Queue::poll_next(self, cx)
+    }
+
+    fn cancel_all(&mut self) {
+        Queue::cancel_all(self)
+    }
+}
+
+// Waits for the next transfer in the queue to complete, returning None if the timeout passes
+// first.
+fn wait<Q: Transfers>(
+    queue: &mut Q,
+    signal: &Arc<Signal>,
+    timeout: Duration,
+) -> Option<Completion<Q::Response>> {
+    let deadline = Instant::now() + timeout;
+    let waker = task::waker(Arc::clone(signal));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(completion) = queue.poll_next(&mut cx) {
+            return Some(completion);
+        }
+        if !signal.wait_until(deadline) {
+            return None;
+        }
+    }
+}
+
+// Waits for the submitted write to complete. A write that times out is cancelled, and its
+// completion waited for so it isn't taken as the next write's result.
+fn finish_write<Q: Transfers>(
+    writes: &mut Q,
+    signal: &Arc<Signal>,
+    timeout: Duration,
+) -> Result<Q::Response> {
+    match wait(writes, signal, timeout) {
+        Some(completion) => completion.into_result().map_err(transfer_error),
+        None => {
+            writes.cancel_all();
+            let _ = block_on(future::poll_fn(|cx| writes.poll_next(cx)));
+            Err(AntError::UsbDeviceError(rusb::Error::Timeout))
+        }
+    }
+}
+
+// Errors are reported as the libusb error the run loop handles the same way.
+fn transfer_error(e: TransferError) -> AntError {
+    AntError::UsbDeviceError(match e {
+        TransferError::Cancelled => rusb::Error::Interrupted,
+        TransferError::Stall => rusb::Error::Pipe,
+        TransferError::Disconnected => rusb::Error::NoDevice,
+        _ => rusb::Error::Io,
+    })
+}
+
+// Wakes the thread waiting on a transfer when nusb completes it.
+#[derive(Default)]
+struct Signal {
+    woken: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl Signal {
+    // Waits to be woken, returning false if the deadline passes first.
+    fn wait_until(&self, deadline: Instant) -> bool {
+        let mut woken = self.woken.lock().unwrap();
+        while !*woken {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            woken = self.condvar.wait_timeout(woken, deadline - now).unwrap().0;
+        }
+        *woken = false;
+        true
+    }
+}
+
+impl ArcWake for Signal {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        *arc_self.woken.lock().unwrap() = true;
+        arc_self.condvar.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::thread;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    // Completes transfers in order, and completes each cancelled transfer as nusb does.
+    #[derive(Default)]
+    struct MockTransfers {
+        completed: VecDeque<Completion<usize>>,
+        cancelled: usize,
+    }
+
+    impl MockTransfers {
+        fn complete(&mut self, data: usize, status: std::result::Result<(), TransferError>) {
+            self.completed.push_back(Completion { data, status });
+        }
+    }
+
+    impl Transfers for MockTransfers {
+        type Response = usize;
+
+        fn poll_next(&mut self, _cx: &mut Context<'_>) -> Poll<Completion<usize>> {
+            if let Some(completion) = self.completed.pop_front() {
+                return Poll::Ready(completion);
+            }
+            if self.cancelled > 0 {
+                self.cancelled -= 1;
+                return Poll::Ready(Completion {
+                    data: 0,
+                    status: Err(TransferError::Cancelled),
+                });
+            }
+            Poll::Pending
+        }
+
+        fn cancel_all(&mut self) {
+            self.cancelled += 1;
+        }
+    }
+
+    #[test]
+    fn signal_wait() {
+        let signal = Arc::new(Signal::default());
+        let start = Instant::now();
+        assert!(!signal.wait_until(start + TIMEOUT));
+        assert!(start.elapsed() >= TIMEOUT);
+
+        // A wake before waiting is kept until the next wait.
+        ArcWake::wake_by_ref(&signal);
+        assert!(signal.wait_until(Instant::now()));
+        assert!(!signal.wait_until(Instant::now()));
+
+        let waker = Arc::clone(&signal);
+        let handle = thread::spawn(move || {
+            thread::sleep(TIMEOUT);
+            ArcWake::wake_by_ref(&waker);
+        });
+        assert!(signal.wait_until(Instant::now() + TIMEOUT * 20));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn wait_timeout() {
+        let signal = Arc::new(Signal::default());
+        let mut transfers = MockTransfers::default();
+        let start = Instant::now();
+        assert!(wait(&mut transfers, &signal, TIMEOUT).is_none());
+        assert!(start.elapsed() >= TIMEOUT);
+
+        transfers.complete(8, Ok(()));
+        let completion = wait(&mut transfers, &signal, TIMEOUT).unwrap();
+        assert_eq!(completion.data, 8);
+    }
+
+    #[test]
+    fn cancelled_write() {
+        let signal = Arc::new(Signal::default());
+        let mut writes = MockTransfers::default();
+        let result = finish_write(&mut writes, &signal, TIMEOUT);
+        assert!(matches!(
+            result,
+            Err(AntError::UsbDeviceError(rusb::Error::Timeout))
+        ));
+        // The cancelled write has been taken from the queue.
+        assert_eq!(writes.cancelled, 0);
+
+        writes.complete(5, Ok(()));
+        assert_eq!(finish_write(&mut writes, &signal, TIMEOUT).unwrap(), 5);
+        writes.complete(0, Err(TransferError::Stall));
+        assert!(matches!(
+            finish_write(&mut writes, &signal, TIMEOUT),
+            Err(AntError::UsbDeviceError(rusb::Error::Pipe))
+        ));
+    }
+
+    #[test]
+    fn transfer_errors() {
+        let errors = vec![
+            (TransferError::Cancelled, rusb::Error::Interrupted),
+            (TransferError::Stall, rusb::Error::Pipe),
+            (TransferError::Disconnected, rusb::Error::NoDevice),
+            (TransferError::Fault, rusb::Error::Io),
+        ];
+        for (error, expected) in errors {
+            match transfer_error(error) {
+                AntError::UsbDeviceError(e) => assert_eq!(e, expected),
+                e => panic!("Unexpected error {:?}", e),
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+/// The replay module records the raw frames from a RunConfig tap to a trace, and replays a
+/// trace through the run loop so decoding bugs can be reproduced without the ANT+ USB device
+/// they were seen with.
+use std::collections::VecDeque;
+use std::io::{BufRead, Write};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Receiver;
+
+use crate::ant::RawFrame;
+use crate::error::AntError;
+use crate::message::Hex;
+use crate::transport::Transport;
+use crate::Result;
+
+/// Writes the raw frames received from a RunConfig tap to a trace until the tap's Sender is
+/// dropped. Each frame is written on a line with the milliseconds since the first frame, TX or
+/// RX, and the data in hex.
+///
+/// 0 TX A4 01 4A 00 EF
+/// 21 RX A4 01 6F 20 EA
+pub fn record<W: Write>(frames: Receiver<RawFrame>, mut trace: W) -> Result<()> {
+    let mut start = None;
+    for frame in frames.iter() {
+        let elapsed = start.get_or_insert_with(Instant::now).elapsed();
+        let (direction, data) = match &frame {
+            RawFrame::Tx(data) => ("TX", data),
+            RawFrame::Rx(data) => ("RX", data),
+        };
+        writeln!(trace, "{} {} {}", elapsed.as_millis(), direction, Hex(data))?;
+    }
+    trace.flush()?;
+    Ok(())
+}
+
+/// Replay is a transport that replays the RX frames of a trace written by record. Writes are
+/// discarded. Once all frames are replayed, reads return NoDevice as if the ANT+ USB device was
+/// unplugged.
+///
+/// let replay = Replay::from_trace(BufReader::new(File::open("hrm.trace")?))?;
+/// libant::ant::run_transport(replay, request_rx, message_tx, RunConfig::new());
+pub struct Replay {
+    frames: Mutex<VecDeque<(Duration, Vec<u8>)>>,
+    timing: bool,
+    // Set on the first read, which the time of each frame is replayed relative to.
+    start: Mutex<Option<Instant>>,
+}
+
+impl Replay {
+    /// Reads a trace written by record.
+    pub fn from_trace<R: BufRead>(trace: R) -> Result<Self> {
+        let mut frames = VecDeque::new();
+        for (i, line) in trace.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // Only the frames read from the ANT+ USB device are replayed.
+            match parse_line(&line).ok_or(AntError::InvalidTrace(i + 1))? {
+                (at, RawFrame::Rx(data)) => frames.push_back((at, data)),
+                (_, RawFrame::Tx(_)) => {}
+            }
+        }
+        Ok(Self {
+            frames: Mutex::new(frames),
+            timing: true,
+            start: Mutex::new(None),
+        })
+    }
+
+    /// Sets whether frames are replayed with the timing they were recorded with. Defaults to
+    /// true, otherwise frames are replayed as fast as the run loop reads them.
+    pub fn timing(mut self, timing: bool) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+// Parses a line of a trace into the time and frame, returning None if the line is not valid.
+fn parse_line(line: &str) -> Option<(Duration, RawFrame)> {
+    let mut fields = line.split_whitespace();
+    let at = Duration::from_millis(fields.next()?.parse().ok()?);
+    let direction = fields.next()?;
+    let data = fields
+        .map(|byte| u8::from_str_radix(byte, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    match direction {
+        "TX" => Some((at, RawFrame::Tx(data))),
+        "RX" => Some((at, RawFrame::Rx(data))),
+        _ => None,
+    }
+}
+
+impl Transport for Replay {
+    fn read_with_timeout(&self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        let mut frames = self.frames.lock().unwrap();
+        let (at, data) = match frames.front() {
+            Some(frame) => frame,
+            None => return Err(AntError::UsbDeviceError(rusb::Error::NoDevice)),
+        };
+        if self.timing {
+            let start = *self.start.lock().unwrap().get_or_insert_with(Instant::now);
+            let wait = (start + *at).saturating_duration_since(Instant::now());
+            if wait > timeout {
+                thread::sleep(timeout);
+                return Err(AntError::UsbDeviceError(rusb::Error::Timeout));
+            }
+            thread::sleep(wait);
+        }
+        let len = data.len().min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        frames.pop_front();
+        Ok(len)
+    }
+
+    fn write_with_timeout(&self, data: &[u8], _timeout: Duration) -> Result<usize> {
+        Ok(data.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn record_and_replay() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(RawFrame::Tx(vec![0xA4, 0x01, 0x4A, 0x00, 0xEF]))
+            .unwrap();
+        tx.send(RawFrame::Rx(vec![0xA4, 0x01, 0x6F, 0x20, 0xEA]))
+            .unwrap();
+        drop(tx);
+        let mut trace = Vec::new();
+        record(rx, &mut trace).unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        assert!(trace.ends_with(" RX A4 01 6F 20 EA\n"));
+
+        let replay = Replay::from_trace(trace.as_bytes()).unwrap().timing(false);
+        let mut buf = [0; 16];
+        let timeout = Duration::from_millis(10);
+        assert_eq!(replay.write_with_timeout(&[0xA4], timeout).unwrap(), 1);
+        let len = replay.read_with_timeout(&mut buf, timeout).unwrap();
+        assert_eq!(buf[..len], [0xA4, 0x01, 0x6F, 0x20, 0xEA]);
+        assert!(matches!(
+            replay.read_with_timeout(&mut buf, timeout),
+            Err(AntError::UsbDeviceError(rusb::Error::NoDevice))
+        ));
+    }
+
+    #[test]
+    fn replay_timing() {
+        let replay = Replay::from_trace("0 RX A4\n50 RX 01\n".as_bytes()).unwrap();
+        let mut buf = [0; 16];
+        assert_eq!(
+            replay
+                .read_with_timeout(&mut buf, Duration::from_millis(10))
+                .unwrap(),
+            1
+        );
+        assert!(matches!(
+            replay.read_with_timeout(&mut buf, Duration::from_millis(10)),
+            Err(AntError::UsbDeviceError(rusb::Error::Timeout))
+        ));
+        assert_eq!(
+            replay
+                .read_with_timeout(&mut buf, Duration::from_millis(100))
+                .unwrap(),
+            1
+        );
+        assert_eq!(buf[0], 0x01);
+    }
+
+    #[test]
+    fn invalid_trace() {
+        assert!(matches!(
+            Replay::from_trace("0 RX A4\n\n10 XX 01\n".as_bytes()),
+            Err(AntError::InvalidTrace(3))
+        ));
+        assert!(matches!(
+            Replay::from_trace("0 RX A4 G1\n".as_bytes()),
+            Err(AntError::InvalidTrace(1))
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+/// The runtime module provides spawn() to start the run loop on a background thread. The
+/// returned AntRuntime stops the run loop and waits for the thread to exit when dropped, so the
+/// ANT+ USB device is always released.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crossbeam_channel::{Receiver, Sender};
+use log::error;
+
+use crate::{
+    ant::{self, Request, Response, RunConfig},
+    client::AntClient,
+    dispatch::Dispatcher,
+};
+
+/// Starts the run loop on a background thread.
+///
+/// let runtime = libant::spawn(RunConfig::new());
+/// let mut client = runtime.client();
+/// let hrm = client.open_channel(HeartRateMonitor::channel_config())?;
+pub fn spawn(config: RunConfig) -> AntRuntime {
+    let (request_tx, request_rx) = crossbeam_channel::unbounded();
+    let (response_tx, response_rx) = crossbeam_channel::unbounded();
+    let quit = Arc::new(AtomicBool::new(false));
+    let run_quit = quit.clone();
+    let handle = thread::spawn(move || ant::run_until(request_rx, response_tx, config, &run_quit));
+    AntRuntime {
+        requests: request_tx,
+        responses: Some(response_rx),
+        quit,
+        handle: Some(handle),
+        dispatcher: None,
+    }
+}
+
+/// Starts the run loop on a background thread, invoking the dispatcher's handlers with the
+/// responses from the run loop on a second thread. The dispatcher takes every response, so
+/// receiver() returns None and clients only send requests.
+///
+/// let dispatcher = Dispatcher::new().on_response(|response| println!("{:?}", response));
+/// let runtime = libant::runtime::spawn_with_dispatcher(RunConfig::new(), dispatcher);
+/// runtime.client().open_channel(HeartRateMonitor::channel_config())?;
+pub fn spawn_with_dispatcher(config: RunConfig, dispatcher: Dispatcher) -> AntRuntime {
+    let mut runtime = spawn(config);
+    if let Some(responses) = runtime.responses.take() {
+        runtime.dispatcher = Some(thread::spawn(move || dispatcher.run(responses)));
+    }
+    runtime
+}
+
+/// AntRuntime owns the thread the run loop was spawned on. When dropped, a Quit request is sent
+/// and the thread is joined.
+pub struct AntRuntime {
+    requests: Sender<Request>,
+    // Taken by the dispatcher when spawned with one.
+    responses: Option<Receiver<Response>>,
+    quit: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+    dispatcher: Option<JoinHandle<()>>,
+}
+
+impl AntRuntime {
+    /// Sender for requests to the run loop.
+    pub fn sender(&self) -> Sender<Request> {
+        self.requests.clone()
+    }
+
+    /// Receiver for responses from the run loop. Responses are shared between all receivers, so
+    /// each response is delivered to only one of them. None if the run loop was spawned with a
+    /// dispatcher, as the dispatcher takes every response.
+    pub fn receiver(&self) -> Option<Receiver<Response>> {
+        self.responses.clone()
+    }
+
+    /// Returns a client for the run loop. Channel numbers are tracked per client, so only one
+    /// client should be used to open channels. If the run loop was spawned with a dispatcher,
+    /// the client only sends requests and recv() returns RunLoopDisconnected.
+    pub fn client(&self) -> AntClient {
+        let responses = match &self.responses {
+            Some(responses) => responses.clone(),
+            None => crossbeam_channel::unbounded().1,
+        };
+        AntClient::new(self.sender(), responses)
+    }
+}
+
+impl Drop for AntRuntime {
+    fn drop(&mut self) {
+        self.quit.store(true, Ordering::SeqCst);
+        // The run loop may have already exited, in which case there is no one to receive Quit.
+        let _ = self.requests.send(Request::Quit);
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                error!("ANT+ run loop panicked");
+            }
+        }
+        // The dispatcher exits once the run loop has dropped its Sender.
+        if let Some(dispatcher) = self.dispatcher.take() {
+            if dispatcher.join().is_err() {
+                error!("Response handler panicked");
+            }
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use crate::channel::Config;
+use crate::device::{fec::FitnessEquipment, PageScheduler};
+/// Simulators act as the master for a device profile so applications can be tested without the
+/// hardware. The simulator encodes the data pages to send with Request::SendBroadcast each time an
+/// EventTx is received for the channel and handles acknowledged data sent by the application
+/// under test.
+use crate::message::bytes_to_u16;
+use std::f32::consts::PI;
+
+const FE_TRANSMISSION_TYPE: u8 = 0x05;
+// Trainer equipment type sent on the general FE data page.
+const FE_EQUIPMENT_TYPE_TRAINER: u8 = 25;
+// Channel period of 8192 is 4Hz. Elapsed time is sent in 0.25s units.
+const FE_MESSAGE_PERIOD: f32 = 0.25;
+// Common pages are interleaved every 65 messages.
+const FE_COMMON_PAGE_INTERVAL: u32 = 64;
+const FE_MAX_RESISTANCE: u16 = 100;
+const GRAVITY: f32 = 9.81;
+
+/// VirtualTrainer is a FE-C master that broadcasts the general FE data and trainer data pages,
+/// accepts the basic resistance, target power, wind resistance, track resistance, and user
+/// configuration control pages, and models the speed and power of a trainer from the cadence
+/// supplied by the application.
+// Page 0x10 -> General FE Data
+// Page 0x19 -> Specific Trainer/Stationary Bike Data
+// Page 0x36 -> FE Capabilities (on request)
+// Page 0x47 -> Command Status (on request)
+// Page 0x50 -> Manufacturer Information
+// Page 0x51 -> Product Information
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualTrainer {
+    device_id: u16,
+    mode: Mode,
+    cadence: u8,
+    // Simulation parameters from the wind and track resistance pages.
+    grade: f32,
+    rolling_resistance: f32,
+    wind_resistance: f32,
+    wind_speed: f32,
+    drafting_factor: f32,
+    // User configuration
+    user_weight: f32,
+    bike_weight: f32,
+    wheel_diameter: f32,
+    gear_ratio: f32,
+    // Accumulated values sent on the data pages.
+    elapsed_time: f32,
+    distance: f32,
+    event_count: u8,
+    accumulated_power: u16,
+    message_count: u32,
+    scheduler: PageScheduler,
+    // Last command received for the command status page.
+    last_command: u8,
+    command_sequence: u8,
+    command_data: [u8; 4],
+    // Page requested with common page 70 and the number of times to send it.
+    requested_page: Option<(u8, u8)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    BasicResistance(f32),
+    TargetPower(f32),
+    Simulation,
+}
+
+impl VirtualTrainer {
+    pub fn new(device_id: u16) -> Self {
+        Self {
+            device_id,
+            mode: Mode::BasicResistance(0.0),
+            cadence: 0,
+            grade: 0.0,
+            rolling_resistance: 0.004,
+            wind_resistance: 0.51,
+            wind_speed: 0.0,
+            drafting_factor: 1.0,
+            user_weight: 75.0,
+            bike_weight: 10.0,
+            wheel_diameter: 0.7,
+            gear_ratio: 2.75,
+            elapsed_time: 0.0,
+            distance: 0.0,
+            event_count: 0,
+            accumulated_power: 0,
+            message_count: 0,
+            scheduler: PageScheduler::new(FE_COMMON_PAGE_INTERVAL, &[0x50, 0x51]),
+            last_command: 0xFF,
+            command_sequence: 0xFF,
+            command_data: [0xFF; 4],
+            requested_page: None,
+        }
+    }
+
+    /// Channel configuration for a master channel transmitting as this trainer.
+    pub fn channel_config(&self) -> Config {
+        FitnessEquipment::channel_config()
+            .master()
+            .device_id(self.device_id)
+            .transmission_type(FE_TRANSMISSION_TYPE)
+    }
+
+    /// Sets the cadence of the rider in RPM.
+    pub fn set_cadence(&mut self, cadence: u8) {
+        self.cadence = cadence;
+    }
+
+    /// Speed in meters per second modeled from the cadence, gear ratio, and wheel diameter.
+    pub fn speed(&self) -> f32 {
+        self.cadence as f32 / 60_f32 * self.gear_ratio * PI * self.wheel_diameter
+    }
+
+    /// Power in watts modeled from the current mode of the trainer. In target power mode, the
+    /// trainer holds the target power. In basic resistance mode, the resistance is a percentage
+    /// of the maximum resistance. In simulation mode, power is calculated from the grade,
+    /// rolling resistance, and wind resistance.
+    pub fn power(&self) -> f32 {
+        if self.cadence == 0 {
+            return 0_f32;
+        }
+        let speed = self.speed();
+        match self.mode {
+            Mode::TargetPower(power) => power,
+            Mode::BasicResistance(resistance) => {
+                resistance / 100_f32 * FE_MAX_RESISTANCE as f32 * speed
+            }
+            Mode::Simulation => {
+                let mass = self.user_weight + self.bike_weight;
+                let angle = (self.grade / 100_f32).atan();
+                let gravity =
+                    mass * GRAVITY * (self.rolling_resistance * angle.cos() + angle.sin());
+                let air_speed = speed + self.wind_speed;
+                let aero =
+                    0.5 * self.wind_resistance * air_speed * air_speed.abs() * self.drafting_factor;
+                ((gravity + aero) * speed).max(0_f32)
+            }
+        }
+    }
+
+    /// Handles acknowledged data sent to the trainer. Control pages change the mode of the
+    /// trainer, and common page 70 requests a page to be sent back.
+    pub fn handle_command(&mut self, data: &[u8]) {
+        if data.len() != 8 {
+            return;
+        }
+        match data[0] {
+            // Basic Resistance in 0.5%
+            0x30 => self.mode = Mode::BasicResistance(data[7] as f32 / 2_f32),
+            // Target Power in 0.25W
+            0x31 => self.mode = Mode::TargetPower(bytes_to_u16(&data[6..]) as f32 / 4_f32),
+            // Wind Resistance
+            0x32 => {
+                if data[5] != 0xFF {
+                    self.wind_resistance = data[5] as f32 / 100_f32;
+                }
+                if data[6] != 0xFF {
+                    self.wind_speed = (data[6] as f32 - 127_f32) / 3.6;
+                }
+                if data[7] != 0xFF {
+                    self.drafting_factor = data[7] as f32 / 100_f32;
+                }
+                self.mode = Mode::Simulation;
+            }
+            // Track Resistance
+            0x33 => {
+                let grade = bytes_to_u16(&data[5..7]);
+                if grade != 0xFFFF {
+                    self.grade = grade as f32 / 100_f32 - 200_f32;
+                }
+                if data[7] != 0xFF {
+                    self.rolling_resistance = data[7] as f32 * 0.00005;
+                }
+                self.mode = Mode::Simulation;
+            }
+            // User Configuration
+            0x37 => {
+                let user_weight = bytes_to_u16(&data[1..3]);
+                if user_weight != 0xFFFF {
+                    self.user_weight = user_weight as f32 / 100_f32;
+                }
+                let bike_weight = ((data[4] >> 4) as u16) | ((data[5] as u16) << 4);
+                if bike_weight != 0xFFF {
+                    self.bike_weight = bike_weight as f32 / 20_f32;
+                }
+                if data[6] != 0xFF {
+                    let offset = if data[4] & 0x0F != 0x0F {
+                        data[4] & 0x0F
+                    } else {
+                        0
+                    };
+                    self.wheel_diameter = (data[6] as f32 * 10_f32 + offset as f32) / 1000_f32;
+                }
+                if data[7] != 0x00 {
+                    self.gear_ratio = data[7] as f32 * 0.03;
+                }
+            }
+            // Common page 70 Request Data Page
+            0x46 => {
+                let count = (data[5] & 0x7F).max(1);
+                self.requested_page = Some((data[6], count));
+                return;
+            }
+            _ => return,
+        }
+        self.last_command = data[0];
+        self.command_sequence = self.command_sequence.wrapping_add(1);
+        self.command_data = [data[4], data[5], data[6], data[7]];
+    }
+
+    /// Encodes the next data page to transmit. Each call advances time by one channel period.
+    /// Requested pages are sent first, otherwise the general FE data and trainer data pages are
+    /// sent two at a time with the common pages interleaved.
+    pub fn next_page(&mut self) -> [u8; 8] {
+        self.elapsed_time += FE_MESSAGE_PERIOD;
+        self.distance += self.speed() * FE_MESSAGE_PERIOD;
+        self.message_count = self.message_count.wrapping_add(1);
+
+        if let Some((page_number, count)) = self.requested_page {
+            self.requested_page = if count > 1 {
+                Some((page_number, count - 1))
+            } else {
+                None
+            };
+            match page_number {
+                0x36 => return self.page_0x36(),
+                0x47 => return self.page_0x47(),
+                0x50 => return self.page_0x50(),
+                0x51 => return self.page_0x51(),
+                _ => {} // Unsupported page, continue with the normal pages.
+            }
+        }
+
+        match self.scheduler.next_page() {
+            Some(0x50) => self.page_0x50(),
+            Some(_) => self.page_0x51(),
+            None if self.message_count % 4 < 2 => self.page_0x10(),
+            None => self.page_0x19(),
+        }
+    }
+
+    // FE state in bits 4-6 of the last byte of the FE specific pages. In use if the rider is
+    // pedaling, otherwise ready.
+    fn fe_state(&self) -> u8 {
+        if self.cadence > 0 {
+            0x30
+        } else {
+            0x20
+        }
+    }
+
+    fn page_0x10(&self) -> [u8; 8] {
+        let speed = ((self.speed() * 1000_f32).round() as u16).to_le_bytes();
+        [
+            0x10,
+            FE_EQUIPMENT_TYPE_TRAINER,
+            ((self.elapsed_time / FE_MESSAGE_PERIOD) as u32 & 0xFF) as u8,
+            (self.distance as u32 & 0xFF) as u8,
+            speed[0],
+            speed[1],
+            // Heart rate not available
+            0xFF,
+            // Distance traveled enabled and virtual speed
+            0x0C | self.fe_state(),
+        ]
+    }
+
+    fn page_0x19(&mut self) -> [u8; 8] {
+        let power = (self.power().round() as u16).min(0xFFE);
+        self.event_count = self.event_count.wrapping_add(1);
+        self.accumulated_power = self.accumulated_power.wrapping_add(power);
+        let accumulated_power = self.accumulated_power.to_le_bytes();
+        let target_power_status = match self.mode {
+            Mode::TargetPower(_) if self.cadence == 0 => 0x01,
+            _ => 0x00,
+        };
+        [
+            0x19,
+            self.event_count,
+            self.cadence,
+            accumulated_power[0],
+            accumulated_power[1],
+            (power & 0xFF) as u8,
+            (power >> 8) as u8,
+            target_power_status | self.fe_state(),
+        ]
+    }
+
+    fn page_0x36(&self) -> [u8; 8] {
+        let max_resistance = FE_MAX_RESISTANCE.to_le_bytes();
+        [
+            0x36,
+            0xFF,
+            0xFF,
+            0xFF,
+            0xFF,
+            max_resistance[0],
+            max_resistance[1],
+            // Basic resistance, target power, and simulation modes supported
+            0x07,
+        ]
+    }
+
+    fn page_0x47(&self) -> [u8; 8] {
+        let status = if self.last_command == 0xFF {
+            0xFF
+        } else {
+            0x00
+        };
+        [
+            0x47,
+            self.last_command,
+            self.command_sequence,
+            status,
+            self.command_data[0],
+            self.command_data[1],
+            self.command_data[2],
+            self.command_data[3],
+        ]
+    }
+
+    fn page_0x50(&self) -> [u8; 8] {
+        // Development manufacturer ID
+        [0x50, 0xFF, 0xFF, 0x01, 0xFF, 0x00, 0x01, 0x00]
+    }
+
+    fn page_0x51(&self) -> [u8; 8] {
+        let serial_number = (self.device_id as u32).to_le_bytes();
+        [
+            0x51,
+            0xFF,
+            0xFF,
+            0x01,
+            serial_number[0],
+            serial_number[1],
+            serial_number[2],
+            serial_number[3],
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::device::fec::{CommandStatus, FeState};
+
+    #[test]
+    fn test_virtual_trainer_target_power() {
+        let mut trainer = VirtualTrainer::new(1234);
+        let mut fe = FitnessEquipment::new();
+        let command = fe.set_target_power(0, 250.0).unwrap();
+        trainer.handle_command(command.data());
+        trainer.set_cadence(90);
+        for _ in 0..8 {
+            fe.decode(trainer.next_page());
+        }
+        assert_eq!(fe.power(), 250);
+        assert_eq!(fe.cadence(), 90);
+        assert_eq!(fe.state(), Some(FeState::InUse));
+
+        trainer.handle_command(fe.request_command_status(0).data());
+        fe.decode(trainer.next_page());
+        assert_eq!(fe.last_command(), Some(0x31));
+        assert_eq!(fe.command_status(), Some(CommandStatus::Pass));
+    }
+
+    #[test]
+    fn test_virtual_trainer_simulation() {
+        let mut trainer = VirtualTrainer::new(1234);
+        let fe = FitnessEquipment::new();
+        trainer.set_cadence(90);
+        let flat = trainer.clone();
+        trainer.handle_command(fe.set_track_resistance(0, 5.0, 0.004).unwrap().data());
+        assert!(trainer.power() > flat.power());
+        assert_eq!(trainer.speed(), flat.speed());
+    }
+}
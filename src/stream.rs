@@ -0,0 +1,213 @@
+/// The stream module exposes responses from the run loop as a futures Stream so async
+/// applications can use StreamExt combinators instead of blocking on recv().
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures::stream::Stream;
+use futures::task::{Context, Poll};
+
+use crate::ant::Response;
+
+// How often the forwarder checks whether every stream and client routing responses is dropped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// ResponseStream yields responses received from the run loop. Responses are forwarded from the
+/// crossbeam Receiver by a background thread, and the stream ends when the run loop drops its
+/// Sender. The thread exits shortly after the stream is dropped.
+///
+/// let mut responses = libant::stream::ResponseStream::new(message_rx);
+/// while let Some(response) = responses.next().await {
+///     // Handle response
+/// }
+pub struct ResponseStream {
+    responses: UnboundedReceiver<Response>,
+    // Keeps the forwarder running while the stream is in use.
+    _routes: Arc<Mutex<Routes>>,
+}
+
+impl ResponseStream {
+    pub fn new(responses: Receiver<Response>) -> Self {
+        let (router, _) = Router::spawn(responses);
+        router.stream()
+    }
+}
+
+impl Stream for ResponseStream {
+    type Item = Response;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Response>> {
+        Pin::new(&mut self.responses).poll_next(cx)
+    }
+}
+
+// Router owns the only reader of the run loop's responses, so each response is delivered once.
+// Responses for a channel go to the channel's stream, other responses go to the stream for all
+// responses, and responses no stream takes, such as those for a dropped stream, go to the
+// Receiver returned by spawn.
+pub(crate) struct Router {
+    routes: Arc<Mutex<Routes>>,
+}
+
+#[derive(Default)]
+struct Routes {
+    channels: BTreeMap<u8, UnboundedSender<Response>>,
+    responses: Option<UnboundedSender<Response>>,
+}
+
+impl Router {
+    // Starts forwarding responses. The forwarder exits when the run loop drops its Sender, or
+    // once the router and every stream it returned are dropped.
+    pub(crate) fn spawn(responses: Receiver<Response>) -> (Self, Receiver<Response>) {
+        let routes = Arc::new(Mutex::new(Routes::default()));
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let weak = Arc::downgrade(&routes);
+        thread::spawn(move || forward(responses, weak, tx));
+        (Self { routes }, rx)
+    }
+
+    // Returns a stream of the responses not taken by a channel stream, ending any stream
+    // already returned for them.
+    pub(crate) fn stream(&self) -> ResponseStream {
+        let (tx, rx) = mpsc::unbounded();
+        self.routes.lock().unwrap().responses = Some(tx);
+        self.response_stream(rx)
+    }
+
+    // Returns a stream of the responses for a channel, ending any stream already returned for
+    // the channel.
+    pub(crate) fn channel_stream(&self, number: u8) -> ResponseStream {
+        let (tx, rx) = mpsc::unbounded();
+        self.routes.lock().unwrap().channels.insert(number, tx);
+        self.response_stream(rx)
+    }
+
+    fn response_stream(&self, responses: UnboundedReceiver<Response>) -> ResponseStream {
+        ResponseStream {
+            responses,
+            _routes: self.routes.clone(),
+        }
+    }
+}
+
+impl Routes {
+    // Sends the response to the stream taking it. Returns the response if no stream took it.
+    fn route(&mut self, response: Response) -> Option<Response> {
+        let response = match response.channel() {
+            Some(number) => match self.channels.get(&number) {
+                Some(tx) => match tx.unbounded_send(response) {
+                    Ok(_) => return None,
+                    Err(e) => {
+                        // Stream has been dropped.
+                        self.channels.remove(&number);
+                        e.into_inner()
+                    }
+                },
+                None => response,
+            },
+            None => response,
+        };
+        match &self.responses {
+            Some(tx) => match tx.unbounded_send(response) {
+                Ok(_) => None,
+                Err(e) => {
+                    self.responses = None;
+                    Some(e.into_inner())
+                }
+            },
+            None => Some(response),
+        }
+    }
+}
+
+fn forward(responses: Receiver<Response>, routes: Weak<Mutex<Routes>>, rest: Sender<Response>) {
+    loop {
+        let response = match responses.recv_timeout(POLL_INTERVAL) {
+            Ok(response) => response,
+            Err(RecvTimeoutError::Timeout) if routes.strong_count() > 0 => continue,
+            Err(RecvTimeoutError::Timeout) => return,
+            Err(RecvTimeoutError::Disconnected) => {
+                // Ends the streams, as the run loop has exited.
+                if let Some(routes) = routes.upgrade() {
+                    *routes.lock().unwrap() = Routes::default();
+                }
+                return;
+            }
+        };
+        let routes = match routes.upgrade() {
+            Some(routes) => routes,
+            None => return,
+        };
+        let response = routes.lock().unwrap().route(response);
+        if let Some(response) = response {
+            // The Receiver may have been dropped if only streams are used.
+            let _ = rest.send(response);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::stream::StreamExt;
+
+    #[test]
+    fn response_stream() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(Response::ChannelOpened(0)).unwrap();
+        tx.send(Response::SearchTimeout(1)).unwrap();
+        tx.send(Response::StickDisconnected).unwrap();
+        drop(tx);
+
+        let responses: Vec<Response> = block_on(ResponseStream::new(rx).collect());
+        assert_eq!(responses.len(), 3);
+        assert!(matches!(responses[0], Response::ChannelOpened(0)));
+        assert!(matches!(responses[1], Response::SearchTimeout(1)));
+        assert!(matches!(responses[2], Response::StickDisconnected));
+    }
+
+    #[test]
+    fn route_responses() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let (router, rest) = Router::spawn(rx);
+        let first = router.channel_stream(0);
+        let second = router.channel_stream(1);
+        let dropped = router.channel_stream(2);
+        drop(dropped);
+        tx.send(Response::ChannelOpened(0)).unwrap();
+        tx.send(Response::ChannelOpened(1)).unwrap();
+        tx.send(Response::ChannelOpened(2)).unwrap();
+        tx.send(Response::SearchTimeout(1)).unwrap();
+        tx.send(Response::StickDisconnected).unwrap();
+        drop(tx);
+
+        let first: Vec<Response> = block_on(first.collect());
+        let second: Vec<Response> = block_on(second.collect());
+        let rest: Vec<Response> = rest.iter().collect();
+        assert_eq!(first.len(), 1);
+        assert!(matches!(first[0], Response::ChannelOpened(0)));
+        assert_eq!(second.len(), 2);
+        assert!(matches!(second[0], Response::ChannelOpened(1)));
+        assert!(matches!(second[1], Response::SearchTimeout(1)));
+        assert_eq!(rest.len(), 2);
+        assert!(matches!(rest[0], Response::ChannelOpened(2)));
+        assert!(matches!(rest[1], Response::StickDisconnected));
+    }
+
+    #[test]
+    fn forwarder_exits() {
+        let (tx, rx) = crossbeam_channel::unbounded::<Response>();
+        drop(ResponseStream::new(rx));
+        // The forwarder drops the run loop's Receiver once it exits.
+        let start = std::time::Instant::now();
+        while tx.send(Response::StickDisconnected).is_ok() {
+            assert!(start.elapsed() < POLL_INTERVAL * 10);
+            thread::sleep(POLL_INTERVAL / 10);
+        }
+    }
+}
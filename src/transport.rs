@@ -0,0 +1,280 @@
+/// The transport module abstracts what the run loop reads from and writes to, which is an ANT+
+/// USB device unless the run loop is started with run_transport, such as to replay a trace or
+/// to test the run loop with a MockTransport.
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Sender;
+
+use crate::error::AntError;
+use crate::message::{Message, MessageId, READ_SIZE};
+use crate::usb::StickInfo;
+use crate::Result;
+
+// Replies sent by MockTransport::stick.
+const RESPONSE_NO_ERROR: u8 = 0x00;
+const EVENT_TRANSFER_TX_COMPLETED: u8 = 0x05;
+const EVENT_CHANNEL_CLOSED: u8 = 0x07;
+const STICK_MAX_NETWORKS: u8 = 3;
+// Extended messages and scan mode are supported.
+const STICK_ADVANCED_OPTIONS2: u8 = 0x06;
+
+/// Transport reads the data sent by an ANT+ USB device and writes messages to it. Errors are
+/// reported as UsbDeviceError so the run loop handles them the same as an ANT+ USB device: a
+/// Timeout when nothing is read within the timeout, Interrupted or Overflow when the data read
+/// was lost, and NoDevice when the transport is gone.
+pub trait Transport: Send + Sync + 'static {
+    /// Reads from the transport with the specified timeout.
+    fn read_with_timeout(&self, buf: &mut [u8], timeout: Duration) -> Result<usize>;
+
+    /// Writes to the transport with the specified timeout.
+    fn write_with_timeout(&self, data: &[u8], timeout: Duration) -> Result<usize>;
+
+    /// Identifies the ANT+ USB device, if the transport is for one.
+    fn stick_info(&self) -> Option<StickInfo> {
+        None
+    }
+}
+
+/// Reader reads from a transport on a dedicated thread, sending the data read to the run loop.
+/// The thread is stopped when the Reader is dropped.
+pub struct Reader {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Reader {
+    /// Spawns a thread reading from the transport with the specified timeout. The timeout
+    /// bounds how long the thread takes to notice it has been stopped. Transient errors are sent
+    /// along with the data read, while any other error is sent and stops the thread.
+    pub fn spawn<D: Transport>(
+        transport: &Arc<D>,
+        data: Sender<Result<Vec<u8>>>,
+        timeout: Duration,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let stop = Arc::clone(&stop);
+            let transport = Arc::clone(transport);
+            thread::spawn(move || {
+                let mut buf = [0; READ_SIZE];
+                while !stop.load(Ordering::Relaxed) {
+                    let result = match transport.read_with_timeout(&mut buf, timeout) {
+                        Ok(len) => Ok(buf[..len].to_vec()),
+                        Err(AntError::UsbDeviceError(rusb::Error::Timeout)) => continue,
+                        Err(e) => Err(e),
+                    };
+                    let fatal = match &result {
+                        Err(AntError::UsbDeviceError(e)) => {
+                            *e != rusb::Error::Interrupted && *e != rusb::Error::Overflow
+                        }
+                        _ => false,
+                    };
+                    if data.send(result).is_err() || fatal {
+                        break;
+                    }
+                }
+            })
+        };
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Responder returns the messages a MockTransport replies with to a message written to it.
+pub type Responder = Box<dyn FnMut(&Message) -> Vec<Message> + Send>;
+
+/// MockTransport stands in for an ANT+ USB device to test the run loop without hardware.
+/// Messages written are recorded and passed to a responder, and the messages it replies with are
+/// read back by the run loop. Data and errors can also be queued to be read at any time. Clones
+/// share the same state, so a clone can be kept to script and inspect the MockTransport once it
+/// is moved into the run loop.
+///
+/// let stick = MockTransport::stick(8);
+/// let mock = stick.clone();
+/// std::thread::spawn(move || libant::ant::run_transport(stick, request_rx, message_tx, config));
+/// mock.push(&BroadcastDataMessage::new(0, &[0; 8]).to_message());
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    inner: Arc<(Mutex<MockState>, Condvar)>,
+}
+
+#[derive(Default)]
+struct MockState {
+    responder: Option<Responder>,
+    written: Vec<Message>,
+    reads: VecDeque<Result<Vec<u8>>>,
+    disconnected: bool,
+}
+
+impl MockTransport {
+    /// MockTransport that does not reply to the messages written to it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// MockTransport that replies like an ANT+ USB device with the max channels: Startup to a
+    /// reset, Capabilities to a capabilities request, no error to channel and configuration
+    /// commands, a channel closed event to a close channel command, and a transfer completed
+    /// event to acknowledged data.
+    pub fn stick(max_channels: u8) -> Self {
+        Self::new().respond_with(move |mesg| stick_replies(max_channels, mesg))
+    }
+
+    /// Sets the responder, replacing any responder already set.
+    pub fn respond_with<F>(self, responder: F) -> Self
+    where
+        F: FnMut(&Message) -> Vec<Message> + Send + 'static,
+    {
+        self.state().responder = Some(Box::new(responder));
+        self
+    }
+
+    /// Queues a message to be read.
+    pub fn push(&self, mesg: &Message) {
+        self.push_read(Ok(mesg.encode()));
+    }
+
+    /// Queues raw data to be read, such as a message split across reads or invalid data.
+    pub fn push_bytes(&self, data: &[u8]) {
+        self.push_read(Ok(data.to_vec()));
+    }
+
+    /// Queues an error to be returned from a read.
+    pub fn push_error(&self, error: rusb::Error) {
+        self.push_read(Err(AntError::UsbDeviceError(error)));
+    }
+
+    /// Unplugs the mock ANT+ USB device. Once the data queued is read, reads and writes return
+    /// NoDevice.
+    pub fn disconnect(&self) {
+        self.state().disconnected = true;
+        self.inner.1.notify_all();
+    }
+
+    /// Messages written so far.
+    pub fn written(&self) -> Vec<Message> {
+        self.state().written.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.inner.0.lock().unwrap()
+    }
+
+    fn push_read(&self, read: Result<Vec<u8>>) {
+        self.state().reads.push_back(read);
+        self.inner.1.notify_all();
+    }
+}
+
+impl Transport for MockTransport {
+    fn read_with_timeout(&self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state();
+        loop {
+            if let Some(read) = state.reads.pop_front() {
+                let data = read?;
+                let len = data.len().min(buf.len());
+                buf[..len].copy_from_slice(&data[..len]);
+                return Ok(len);
+            }
+            if state.disconnected {
+                return Err(AntError::UsbDeviceError(rusb::Error::NoDevice));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(AntError::UsbDeviceError(rusb::Error::Timeout));
+            }
+            state = self.inner.1.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+
+    fn write_with_timeout(&self, data: &[u8], _timeout: Duration) -> Result<usize> {
+        let mut state = self.state();
+        if state.disconnected {
+            return Err(AntError::UsbDeviceError(rusb::Error::NoDevice));
+        }
+        // Messages are written one at a time, so the data is a single encoded message.
+        if data.len() < 4 {
+            return Err(AntError::Malformed {
+                id: data.get(2).copied().unwrap_or_default(),
+                len: data.len(),
+            });
+        }
+        let mesg = Message::new(data[2], &data[3..data.len() - 1])?;
+        let replies = match &mut state.responder {
+            Some(responder) => responder(&mesg),
+            None => Vec::new(),
+        };
+        state.written.push(mesg);
+        state
+            .reads
+            .extend(replies.iter().map(|reply| Ok(reply.encode())));
+        self.inner.1.notify_all();
+        Ok(data.len())
+    }
+}
+
+// Replies of an ANT+ USB device to a message written to it.
+pub(crate) fn stick_replies(max_channels: u8, mesg: &Message) -> Vec<Message> {
+    let id = mesg.message_id();
+    let channel = mesg.data().first().copied().unwrap_or_default();
+    let response = |id: MessageId, code: u8| {
+        Message::with_payload(MessageId::ResponseEvent.into(), &[channel, id.into(), code])
+    };
+    match id {
+        MessageId::Reset => vec![Message::with_payload(MessageId::Startup.into(), &[0x00])],
+        MessageId::Request if mesg.data().get(1) == Some(&MessageId::Capabilities.into()) => {
+            vec![Message::with_payload(
+                MessageId::Capabilities.into(),
+                &[
+                    max_channels,
+                    STICK_MAX_NETWORKS,
+                    0,
+                    0,
+                    STICK_ADVANCED_OPTIONS2,
+                    0,
+                ],
+            )]
+        }
+        MessageId::CloseChannel => vec![
+            response(id, RESPONSE_NO_ERROR),
+            response(MessageId::Event, EVENT_CHANNEL_CLOSED),
+        ],
+        MessageId::AcknowledgeData => vec![response(MessageId::Event, EVENT_TRANSFER_TX_COMPLETED)],
+        MessageId::Request | MessageId::BroadcastData | MessageId::BurstData => Vec::new(),
+        _ => vec![response(id, RESPONSE_NO_ERROR)],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mock_short_write() {
+        let mock = MockTransport::new();
+        let timeout = Duration::from_millis(10);
+        assert!(matches!(
+            mock.write_with_timeout(&[0xA4, 0], timeout),
+            Err(AntError::Malformed { id: 0, len: 2 })
+        ));
+        let data = Message::with_payload(0x4A, &[0]).encode();
+        assert_eq!(mock.write_with_timeout(&data, timeout).unwrap(), data.len());
+        assert_eq!(mock.written().len(), 1);
+    }
+}
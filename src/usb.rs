@@ -2,58 +2,357 @@
 /// USB device.
 use std::time::Duration;
 
+use log::debug;
+
 pub use rusb::{Context, UsbContext};
-use rusb::{DeviceHandle, Error};
+use rusb::{DeviceDescriptor, DeviceHandle, Direction, Error, TransferType};
+
+use super::{error::AntError, transport::Transport, Result};
+
+const DYNASTREAM_VENDOR_ID: u16 = 0x0FCF;
+
+/// ANT+ USB devices known to work with the library. Any other ANT+ USB device from Dynastream is
+/// tried with the same settings as the ANT USB2 and ANT USB-m sticks. Other ANT+ USB devices can
+/// be added with RunConfig::stick_model.
+pub const KNOWN_STICKS: &[StickModel] = &[
+    StickModel::dynastream("ANT USB2 Stick", Some(0x1008)),
+    StickModel::dynastream("ANT USB-m Stick", Some(0x1009)),
+    StickModel::dynastream("Dynastream ANT USB device", None),
+];
+
+/// USB IDs, configuration, interface, and bulk endpoints of a model of ANT+ USB device. A model
+/// without a product ID matches any product from the vendor. The interface and endpoints are
+/// only used if the bulk endpoints can't be found in the USB device's active configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StickModel {
+    pub name: &'static str,
+    pub vendor_id: u16,
+    pub product_id: Option<u16>,
+    pub configuration: u8,
+    pub interface: u8,
+    pub endpoint_in: u8,
+    pub endpoint_out: u8,
+}
+
+impl StickModel {
+    const fn dynastream(name: &'static str, product_id: Option<u16>) -> Self {
+        Self {
+            name,
+            vendor_id: DYNASTREAM_VENDOR_ID,
+            product_id,
+            configuration: 1,
+            interface: 0,
+            endpoint_in: 0x81,
+            endpoint_out: 0x01,
+        }
+    }
+
+    fn matches(&self, vendor_id: u16, product_id: u16) -> bool {
+        // Models without a product ID match any product from the vendor.
+        vendor_id == self.vendor_id
+            && match self.product_id {
+                Some(id) => id == product_id,
+                None => true,
+            }
+    }
+}
+
+// Finds the first model matching the USB IDs of a USB device.
+pub(crate) fn find_model(
+    models: &[StickModel],
+    vendor_id: u16,
+    product_id: u16,
+) -> Option<StickModel> {
+    models
+        .iter()
+        .find(|model| model.matches(vendor_id, product_id))
+        .copied()
+}
+
+/// Interface and bulk endpoints used to talk to an ANT+ USB device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Endpoints {
+    pub interface: u8,
+    pub endpoint_in: u8,
+    pub endpoint_out: u8,
+}
+
+impl From<&StickModel> for Endpoints {
+    fn from(model: &StickModel) -> Self {
+        Self {
+            interface: model.interface,
+            endpoint_in: model.endpoint_in,
+            endpoint_out: model.endpoint_out,
+        }
+    }
+}
 
-use super::{error::AntError, Result};
+// Bulk endpoints of an interface in a USB device's active configuration.
+#[derive(Debug, Default)]
+pub(crate) struct BulkEndpoints {
+    pub interface: u8,
+    pub endpoint_in: Option<u8>,
+    pub endpoint_out: Option<u8>,
+}
+
+// Finds the first interface with both a bulk IN and a bulk OUT endpoint, preferring the model's
+// interface, so development boards and composite devices with the ANT interface elsewhere work.
+// Falls back to the model's interface and endpoints if no interface has both.
+pub(crate) fn find_endpoints<I>(interfaces: I, model: &StickModel) -> Endpoints
+where
+    I: IntoIterator<Item = BulkEndpoints>,
+{
+    let mut found: Vec<Endpoints> = interfaces
+        .into_iter()
+        .filter_map(|bulk| match (bulk.endpoint_in, bulk.endpoint_out) {
+            (Some(endpoint_in), Some(endpoint_out)) => Some(Endpoints {
+                interface: bulk.interface,
+                endpoint_in,
+                endpoint_out,
+            }),
+            _ => None,
+        })
+        .collect();
+    found.sort_by_key(|endpoints| endpoints.interface != model.interface);
+    found
+        .first()
+        .copied()
+        .unwrap_or_else(|| Endpoints::from(model))
+}
+
+// Bulk endpoints of the first alternate setting of each interface in a configuration.
+fn bulk_endpoints(config: &rusb::ConfigDescriptor) -> Vec<BulkEndpoints> {
+    let mut interfaces = Vec::new();
+    for interface in config.interfaces() {
+        let desc = match interface.descriptors().next() {
+            Some(desc) => desc,
+            None => continue,
+        };
+        let mut bulk = BulkEndpoints {
+            interface: desc.interface_number(),
+            ..Default::default()
+        };
+        for endpoint in desc.endpoint_descriptors() {
+            if endpoint.transfer_type() != TransferType::Bulk {
+                continue;
+            }
+            let address = match endpoint.direction() {
+                Direction::In => &mut bulk.endpoint_in,
+                Direction::Out => &mut bulk.endpoint_out,
+            };
+            address.get_or_insert(endpoint.address());
+        }
+        interfaces.push(bulk);
+    }
+    interfaces
+}
+
+/// Location of an ANT+ USB device on the USB bus. Used to select which ANT+ USB device a run
+/// loop uses when more than one is plugged in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StickAddress {
+    pub bus: u8,
+    pub address: u8,
+}
+
+/// Selects which ANT+ USB device is used when more than one is plugged in: the first one found,
+/// the one at an address on the USB bus, the one with a USB serial number, or the one at an
+/// index in the order ANT+ USB devices are found.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Selector {
+    #[default]
+    First,
+    Address(StickAddress),
+    Serial(String),
+    Index(usize),
+}
+
+impl Selector {
+    // Whether the ANT+ USB device at the address is selected, where index is the number of ANT+
+    // USB devices found before it. The serial number is only read for Serial, as reading it
+    // opens the USB device.
+    pub(crate) fn selects<F>(&self, address: StickAddress, index: usize, serial: F) -> bool
+    where
+        F: FnOnce() -> Option<String>,
+    {
+        match self {
+            Selector::First => true,
+            Selector::Address(stick) => *stick == address,
+            Selector::Serial(expected) => serial().as_ref() == Some(expected),
+            Selector::Index(i) => *i == index,
+        }
+    }
+}
+
+/// Identifies the ANT+ USB device a run loop is using. Strings the ANT+ USB device does not
+/// provide, or that could not be read, are None.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StickInfo {
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+    pub vendor_id: u16,
+    pub product_id: u16,
+}
+
+/// Lists the addresses of all known ANT+ USB devices plugged in.
+pub fn list_devices<T: UsbContext>(ctx: &T) -> Result<Vec<StickAddress>> {
+    list_devices_with_models(ctx, KNOWN_STICKS)
+}
 
-// TODO ANT settings are currently hardcoded and work with the test
-// USB device, but need to verify if these settings work with other
-// ANT+ USB devices.
-const VENDOR_ID: u16 = 0x0FCF;
-const USB_ANT_CONFIGURATION: u8 = 1;
-const USB_ANT_INTERFACE: u8 = 0;
-const USB_ANT_EP_IN: u8 = 0x81;
-const USB_ANT_EP_OUT: u8 = 0x01;
+/// Lists the addresses of all ANT+ USB devices plugged in that match one of the models.
+pub fn list_devices_with_models<T: UsbContext>(
+    ctx: &T,
+    models: &[StickModel],
+) -> Result<Vec<StickAddress>> {
+    let mut sticks = Vec::new();
+    for device in ctx.devices()?.iter() {
+        let desc = device.device_descriptor()?;
+        if find_model(models, desc.vendor_id(), desc.product_id()).is_some() {
+            sticks.push(StickAddress {
+                bus: device.bus_number(),
+                address: device.address(),
+            });
+        }
+    }
+    Ok(sticks)
+}
 
 /// UsbDevice struct that holds the device handle to the USB device
 /// along with a buffer to read data data from.
 pub struct UsbDevice<T: UsbContext> {
     handle: DeviceHandle<T>,
+    endpoints: Endpoints,
+    info: StickInfo,
 }
 
 impl<T: UsbContext> UsbDevice<T> {
-    /// Initialize the USB device for the ANT+ device plugged in.
-    pub fn init(ctx: &mut T) -> Result<UsbDevice<T>> {
+    /// Initialize the USB device for the ANT+ device plugged in. If a stick address is given,
+    /// only the ANT+ device at that address is used, otherwise the first ANT+ device found is
+    /// used.
+    pub fn init(ctx: &mut T, stick: Option<StickAddress>) -> Result<UsbDevice<T>> {
+        let selector = match stick {
+            Some(stick) => Selector::Address(stick),
+            None => Selector::First,
+        };
+        Self::init_with(ctx, &selector)
+    }
+
+    /// Initialize the USB device for the ANT+ device plugged in that matches the selector.
+    pub fn init_with(ctx: &mut T, selector: &Selector) -> Result<UsbDevice<T>> {
+        Self::init_with_models(ctx, selector, KNOWN_STICKS, true)
+    }
+
+    /// Initialize the USB device for the ANT+ device plugged in that matches the selector and
+    /// one of the models. Models are matched in order, so the settings of the first model
+    /// matching the ANT+ device are used. If detach_kernel_driver is set, a kernel driver that
+    /// claimed the ANT+ device, such as usb_serial or cdc_acm on Linux, is detached while the
+    /// ANT+ device is used. Detaching kernel drivers is only supported on Linux.
+    pub fn init_with_models(
+        ctx: &mut T,
+        selector: &Selector,
+        models: &[StickModel],
+        detach_kernel_driver: bool,
+    ) -> Result<UsbDevice<T>> {
+        let mut index = 0;
         for device in ctx.devices()?.iter() {
             let device_desc = device.device_descriptor()?;
-            if device_desc.vendor_id() == VENDOR_ID {
-                let mut handle = device.open()?;
-                match handle.reset() {
-                    Ok(_) => {
-                        handle.claim_interface(USB_ANT_INTERFACE)?;
-                    }
-                    Err(Error::NotFound) => {
-                        let mut handle = device.open()?;
-                        handle.claim_interface(USB_ANT_INTERFACE)?;
-                    }
-                    Err(e) => return Err(AntError::UsbDeviceError(e)),
-                }
-                return Ok(UsbDevice { handle });
+            let model = match find_model(models, device_desc.vendor_id(), device_desc.product_id())
+            {
+                Some(model) => model,
+                None => continue,
+            };
+            let address = StickAddress {
+                bus: device.bus_number(),
+                address: device.address(),
+            };
+            let selected = selector.selects(address, index, || {
+                device
+                    .open()
+                    .and_then(|handle| handle.read_serial_number_string_ascii(&device_desc))
+                    .ok()
+            });
+            index += 1;
+            if selected {
+                debug!("Opening {} at {:?}", model.name, address);
+                return Self::open(&device, &device_desc, model, detach_kernel_driver);
             }
         }
         Err(AntError::UsbDeviceError(Error::NoDevice))
     }
 
+    fn open(
+        device: &rusb::Device<T>,
+        device_desc: &DeviceDescriptor,
+        model: StickModel,
+        detach_kernel_driver: bool,
+    ) -> Result<UsbDevice<T>> {
+        let mut handle = Self::open_handle(device, detach_kernel_driver)?;
+        // Only set the configuration if needed, as setting it resets the USB device.
+        if handle.active_configuration()? != model.configuration {
+            handle.set_active_configuration(model.configuration)?;
+        }
+        let endpoints = match device.active_config_descriptor() {
+            Ok(config) => find_endpoints(bulk_endpoints(&config), &model),
+            Err(_) => Endpoints::from(&model),
+        };
+        debug!("Using {:?}", endpoints);
+        match handle.reset() {
+            Ok(_) => {
+                handle.claim_interface(endpoints.interface)?;
+            }
+            Err(Error::NotFound) => {
+                let mut handle = Self::open_handle(device, detach_kernel_driver)?;
+                handle.claim_interface(endpoints.interface)?;
+            }
+            Err(e) => return Err(AntError::UsbDeviceError(e)),
+        }
+        let info = StickInfo {
+            manufacturer: handle.read_manufacturer_string_ascii(device_desc).ok(),
+            product: handle.read_product_string_ascii(device_desc).ok(),
+            serial_number: handle.read_serial_number_string_ascii(device_desc).ok(),
+            vendor_id: device_desc.vendor_id(),
+            product_id: device_desc.product_id(),
+        };
+        Ok(UsbDevice {
+            handle,
+            endpoints,
+            info,
+        })
+    }
+
+    /// Manufacturer, product, serial number, and USB IDs of the USB device, read when the USB
+    /// device was initialized.
+    pub fn info(&self) -> &StickInfo {
+        &self.info
+    }
+
+    // Opens the USB device. libusb detaches a kernel driver when the interface is claimed and
+    // reattaches it when the interface is released if auto detach is enabled.
+    fn open_handle(
+        device: &rusb::Device<T>,
+        detach_kernel_driver: bool,
+    ) -> Result<DeviceHandle<T>> {
+        let mut handle = device.open()?;
+        if detach_kernel_driver {
+            match handle.set_auto_detach_kernel_driver(true) {
+                Ok(()) | Err(Error::NotSupported) => {}
+                Err(e) => return Err(AntError::UsbDeviceError(e)),
+            }
+        }
+        Ok(handle)
+    }
+
     /// Read from the USB device with a timeout of 10 milliseconds.
-    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+    pub fn read(&self, buf: &mut [u8]) -> Result<usize> {
         self.read_with_timeout(buf, Duration::from_millis(10))
     }
 
     /// Read from the USB device with the specified timeout.
-    pub fn read_with_timeout(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+    pub fn read_with_timeout(&self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
         self.handle
-            .read_bulk(USB_ANT_EP_IN, buf, timeout)
+            .read_bulk(self.endpoints.endpoint_in, buf, timeout)
             .map_err(AntError::UsbDeviceError)
     }
 
@@ -65,7 +364,110 @@ impl<T: UsbContext> UsbDevice<T> {
     /// Write message to the USB device with a specified timeout.
     pub fn write_with_timeout(&self, message: &[u8], timeout: Duration) -> Result<usize> {
         self.handle
-            .write_bulk(USB_ANT_EP_OUT, message, timeout)
+            .write_bulk(self.endpoints.endpoint_out, message, timeout)
             .map_err(AntError::UsbDeviceError)
     }
 }
+
+impl<T: UsbContext + 'static> Transport for UsbDevice<T> {
+    fn read_with_timeout(&self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        UsbDevice::read_with_timeout(self, buf, timeout)
+    }
+
+    fn write_with_timeout(&self, data: &[u8], timeout: Duration) -> Result<usize> {
+        UsbDevice::write_with_timeout(self, data, timeout)
+    }
+
+    fn stick_info(&self) -> Option<StickInfo> {
+        Some(self.info.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bulk(interface: u8, endpoint_in: Option<u8>, endpoint_out: Option<u8>) -> BulkEndpoints {
+        BulkEndpoints {
+            interface,
+            endpoint_in,
+            endpoint_out,
+        }
+    }
+
+    #[test]
+    fn find_bulk_endpoints() {
+        let model = KNOWN_STICKS[0];
+        let endpoints = |interface, endpoint_in, endpoint_out| Endpoints {
+            interface,
+            endpoint_in,
+            endpoint_out,
+        };
+
+        // Composite device with the bulk endpoints on the second interface.
+        let interfaces = vec![bulk(0, None, None), bulk(1, Some(0x83), Some(0x02))];
+        assert_eq!(find_endpoints(interfaces, &model), endpoints(1, 0x83, 0x02));
+
+        // The model's interface is preferred.
+        let interfaces = vec![
+            bulk(1, Some(0x83), Some(0x02)),
+            bulk(0, Some(0x82), Some(0x03)),
+        ];
+        assert_eq!(find_endpoints(interfaces, &model), endpoints(0, 0x82, 0x03));
+
+        // Falls back to the model without a bulk IN and OUT endpoint on an interface.
+        let interfaces = vec![bulk(0, Some(0x81), None), bulk(1, None, Some(0x01))];
+        assert_eq!(find_endpoints(interfaces, &model), endpoints(0, 0x81, 0x01));
+    }
+
+    #[test]
+    fn match_models() {
+        let usb2 = KNOWN_STICKS[0];
+        assert!(usb2.matches(DYNASTREAM_VENDOR_ID, 0x1008));
+        assert!(!usb2.matches(DYNASTREAM_VENDOR_ID, 0x1009));
+        assert!(!usb2.matches(0x1234, 0x1008));
+        // Models without a product ID match any product from the vendor.
+        let any = KNOWN_STICKS[2];
+        assert!(any.matches(DYNASTREAM_VENDOR_ID, 0x100C));
+        assert!(!any.matches(0x1234, 0x100C));
+
+        assert_eq!(
+            find_model(KNOWN_STICKS, DYNASTREAM_VENDOR_ID, 0x1009),
+            Some(KNOWN_STICKS[1])
+        );
+        assert_eq!(
+            find_model(KNOWN_STICKS, DYNASTREAM_VENDOR_ID, 0x100C),
+            Some(any)
+        );
+        assert_eq!(find_model(KNOWN_STICKS, 0x1234, 0x1008), None);
+        // Models are matched in order, so added models are matched first.
+        let clone = StickModel {
+            name: "Clone",
+            ..any
+        };
+        let models = [clone, usb2];
+        assert_eq!(
+            find_model(&models, DYNASTREAM_VENDOR_ID, 0x1008),
+            Some(clone)
+        );
+    }
+
+    #[test]
+    fn select_sticks() {
+        let address = StickAddress { bus: 1, address: 4 };
+        let serial = || Some(String::from("1234"));
+        assert!(Selector::default().selects(address, 2, serial));
+        assert!(Selector::Address(address).selects(address, 2, serial));
+        let other = StickAddress { bus: 2, address: 4 };
+        assert!(!Selector::Address(other).selects(address, 2, serial));
+        assert!(Selector::Index(2).selects(address, 2, serial));
+        assert!(!Selector::Index(0).selects(address, 2, serial));
+        assert!(Selector::Serial(String::from("1234")).selects(address, 2, serial));
+        assert!(!Selector::Serial(String::from("5678")).selects(address, 2, serial));
+        // A serial number that can't be read never matches.
+        assert!(!Selector::Serial(String::from("1234")).selects(address, 2, || None));
+        // The serial number is only read when selecting by serial number.
+        let unread = || -> Option<String> { panic!("Serial number read") };
+        assert!(Selector::Index(2).selects(address, 2, unread));
+    }
+}